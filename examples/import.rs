@@ -0,0 +1,57 @@
+//! Import a directory of DICOM files into Orthanc
+//!
+//! ```text
+//! cargo run --example import -- http://localhost:8042 /mnt/nas/dicom 4
+//! ```
+//!
+//! Credentials are read from `ORC_ORTHANC_USERNAME` and `ORC_ORTHANC_PASSWORD` environment
+//! variables, if set.
+use orthanc::Client;
+use std::env;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 3 || args.len() > 4 {
+        eprintln!("Usage: {} <server> <directory> [workers]", args[0]);
+        process::exit(2);
+    }
+    let workers = match args.get(3).map(|w| w.parse()) {
+        None => 4,
+        Some(Ok(w)) => w,
+        Some(Err(_)) => {
+            eprintln!("Invalid number of workers: {}", args[3]);
+            process::exit(2);
+        }
+    };
+
+    let mut client = Client::new(&args[1]);
+    if let (Ok(u), Ok(p)) = (
+        env::var("ORC_ORTHANC_USERNAME"),
+        env::var("ORC_ORTHANC_PASSWORD"),
+    ) {
+        client = client.auth(u, p);
+    }
+
+    let report = match client.import_directory(&args[2], workers) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("Import failed: {}", e);
+            process::exit(1);
+        }
+    };
+    for failure in &report.failures {
+        eprintln!("{}: {}", failure.path.display(), failure.reason);
+    }
+    println!(
+        "Stored: {}, already stored: {}, failed: {}, skipped: {}, bytes sent: {}",
+        report.success.len(),
+        report.already_stored.len(),
+        report.failures.len(),
+        report.skipped.len(),
+        report.total_bytes
+    );
+    if !report.failures.is_empty() {
+        process::exit(1);
+    }
+}
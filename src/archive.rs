@@ -0,0 +1,390 @@
+//! Reading and extracting ZIP archives, created by Orthanc
+//!
+//! Orthanc archives (e.g. the ones produced by [`Client::study_dicom`](crate::Client::study_dicom)
+//! or [`Client::create_archive`](crate::Client::create_archive)) can be processed in a streaming
+//! fashion, without saving the archive itself to disk first:
+//!
+//! ```
+//! let mut resp = Vec::new();
+//! client.study_dicom("9357491d-427a6c94-4080b6c8-1997f4aa-af658240", &mut resp).unwrap();
+//! for entry in ArchiveEntries::new(resp.as_slice()) {
+//!     let entry = entry.unwrap();
+//!     println!("{}: {} bytes", entry.path.display(), entry.data.len());
+//! }
+//! ```
+use crate::utils::is_dicom;
+use crate::{Error, Result};
+use std::fs;
+use std::io::prelude::*;
+use std::path::{Component, Path, PathBuf};
+
+/// Maximum number of bytes, that are allocated upfront for a file, read from an archive
+const MAX_PREALLOCATION: u64 = 64 * 1024 * 1024;
+
+/// A single file, read from an archive
+#[derive(Debug, Eq, PartialEq)]
+pub struct ArchiveEntry {
+    /// Path of the file inside the archive. Guaranteed to be relative and to not contain any
+    /// `..` components
+    pub path: PathBuf,
+    /// Contents of the file
+    pub data: Vec<u8>,
+}
+
+/// Iterator over the files of a ZIP archive, read from a stream
+///
+/// Directories are skipped. Every file is validated: its path must not escape the archive root,
+/// its checksum must match the one recorded in the archive and its contents must be a DICOM file.
+/// Iteration stops after the first error.
+#[derive(Debug)]
+pub struct ArchiveEntries<R: Read> {
+    reader: R,
+    done: bool,
+}
+
+impl<R: Read> ArchiveEntries<R> {
+    /// Creates a new iterator over the files of the archive, read from `reader`
+    pub fn new(reader: R) -> ArchiveEntries<R> {
+        ArchiveEntries {
+            reader,
+            done: false,
+        }
+    }
+
+    fn next_entry(&mut self) -> Result<Option<ArchiveEntry>> {
+        loop {
+            let mut file = match zip::read::read_zipfile_from_stream(&mut self.reader)? {
+                Some(f) => f,
+                None => return Ok(None),
+            };
+            if file.is_dir() {
+                continue;
+            }
+            let path = match file.enclosed_name().and_then(normalize) {
+                Some(p) => p,
+                None => {
+                    return Err(Error::new(
+                        &format!("Unsafe file path in archive: {}", file.name()),
+                        None,
+                    ))
+                }
+            };
+            // The size comes from the archive, so it is only trusted up to a limit
+            let mut data = Vec::with_capacity(file.size().min(MAX_PREALLOCATION) as usize);
+            // The checksum is verified once the file has been read to the end
+            file.read_to_end(&mut data)?;
+            if !is_dicom(&data) {
+                return Err(Error::new(
+                    &format!("Not a DICOM file: {}", path.display()),
+                    None,
+                ));
+            }
+            return Ok(Some(ArchiveEntry { path, data }));
+        }
+    }
+}
+
+impl<R: Read> Iterator for ArchiveEntries<R> {
+    type Item = Result<ArchiveEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let res = self.next_entry().transpose();
+        if !matches!(res, Some(Ok(_))) {
+            self.done = true;
+        }
+        res
+    }
+}
+
+/// Extract an archive, read from `reader`, into the directory `dir`
+///
+/// The directory structure of the archive is preserved. Returns the paths of all extracted files.
+///
+/// ```
+/// let file = fs::File::open("/tmp/study.zip").unwrap();
+/// extract_archive(file, "/tmp/study").unwrap();
+/// ```
+pub fn extract_archive<R: Read>(reader: R, dir: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+    let mut paths = vec![];
+    for entry in ArchiveEntries::new(reader) {
+        let entry = entry?;
+        let path = dir.as_ref().join(&entry.path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, &entry.data)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Template of a file path, built from DICOM tag values
+///
+/// Placeholders are names of DICOM tags in curly braces, e.g.
+/// `{PatientID}/{StudyDate}_{StudyDescription}/{SeriesNumber}/{InstanceNumber}.dcm`. Characters,
+/// that are not allowed in a file name, are replaced with `_` in tag values. Absent or empty tags
+/// are rendered as `unknown`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PathTemplate {
+    template: String,
+}
+
+impl PathTemplate {
+    /// Creates a new template
+    ///
+    /// ```
+    /// let template = PathTemplate::new("{PatientID}/{StudyDate}/{SeriesNumber}/{InstanceNumber}.dcm");
+    /// ```
+    pub fn new(template: impl Into<String>) -> PathTemplate {
+        PathTemplate {
+            template: template.into(),
+        }
+    }
+
+    /// Render the template into a relative path
+    ///
+    /// `tag` is called with the name of every placeholder and returns the value of the tag, if
+    /// any.
+    pub fn render<F: Fn(&str) -> Option<String>>(&self, tag: F) -> Result<PathBuf> {
+        let mut rendered = String::new();
+        let mut rest = self.template.as_str();
+        while let Some(start) = rest.find('{') {
+            rendered.push_str(&rest[..start]);
+            let end = match rest[start..].find('}') {
+                Some(e) => start + e,
+                None => {
+                    return Err(Error::new(
+                        &format!(
+                            "Unterminated placeholder in path template: {}",
+                            self.template
+                        ),
+                        None,
+                    ))
+                }
+            };
+            let value = tag(&rest[start + 1..end]).unwrap_or_default();
+            rendered.push_str(&sanitize(&value));
+            rest = &rest[end + 1..];
+        }
+        rendered.push_str(rest);
+
+        match normalize(Path::new(&rendered)) {
+            Some(p) if p.components().next().is_some() => Ok(p),
+            _ => Err(Error::new(
+                &format!("Path template renders to an invalid path: {}", rendered),
+                None,
+            )),
+        }
+    }
+}
+
+/// Make a tag value safe to be used as (a part of) a file name
+fn sanitize(value: &str) -> String {
+    let value: String = value
+        .trim()
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    match value.as_str() {
+        "" => "unknown".to_string(),
+        "." | ".." => "_".to_string(),
+        _ => value,
+    }
+}
+
+/// Strip `.` components from a relative path. [`None`] if the path is absolute or contains `..`
+fn normalize(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(c) => normalized.push(c),
+            Component::CurDir => (),
+            _ => return None,
+        }
+    }
+    Some(normalized)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use maplit::hashmap;
+    use std::io::Cursor;
+    use zip::write::{FileOptions, ZipWriter};
+
+    fn dicom(payload: &[u8]) -> Vec<u8> {
+        let mut data = vec![0; 128];
+        data.extend_from_slice(b"DICM");
+        data.extend_from_slice(payload);
+        data
+    }
+
+    fn zip(files: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in files {
+            writer.start_file(*name, FileOptions::default()).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_archive_entries() {
+        let archive = zip(&[
+            ("foo/bar/IM0.dcm", dicom(b"foo")),
+            ("foo/baz/IM1.dcm", dicom(b"bar")),
+        ]);
+        let entries: Vec<ArchiveEntry> = ArchiveEntries::new(archive.as_slice())
+            .collect::<Result<_>>()
+            .unwrap();
+
+        assert_eq!(
+            entries,
+            vec![
+                ArchiveEntry {
+                    path: PathBuf::from("foo/bar/IM0.dcm"),
+                    data: dicom(b"foo"),
+                },
+                ArchiveEntry {
+                    path: PathBuf::from("foo/baz/IM1.dcm"),
+                    data: dicom(b"bar"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_archive_entries_not_dicom() {
+        let archive = zip(&[
+            ("IM0.dcm", dicom(b"foo")),
+            ("README", b"foobar".to_vec()),
+            ("IM1.dcm", dicom(b"bar")),
+        ]);
+        let mut entries = ArchiveEntries::new(archive.as_slice());
+
+        assert!(entries.next().unwrap().is_ok());
+        assert_eq!(
+            entries.next().unwrap().unwrap_err(),
+            Error::new("Not a DICOM file: README", None)
+        );
+        assert!(entries.next().is_none());
+    }
+
+    #[test]
+    fn test_archive_entries_unsafe_path() {
+        let archive = zip(&[("../../etc/foo.dcm", dicom(b"foo"))]);
+        let mut entries = ArchiveEntries::new(archive.as_slice());
+
+        assert_eq!(
+            entries.next().unwrap().unwrap_err(),
+            Error::new("Unsafe file path in archive: ../../etc/foo.dcm", None)
+        );
+    }
+
+    #[test]
+    fn test_archive_entries_bogus_size() {
+        let mut archive = zip(&[("IM0.dcm", dicom(b"foo"))]);
+        // Uncompressed size in the local file header
+        archive[22..26].copy_from_slice(&u32::MAX.to_le_bytes());
+        let mut entries = ArchiveEntries::new(archive.as_slice());
+        // The claimed 4 GiB are not allocated, the buffer grows with the actual data
+        let entry = entries.next().unwrap().unwrap();
+        assert_eq!(entry.data, dicom(b"foo"));
+    }
+
+    #[test]
+    fn test_archive_entries_invalid_archive() {
+        let mut entries = ArchiveEntries::new(&b"foobarbaz"[..]);
+        assert!(entries.next().unwrap().is_err());
+        assert!(entries.next().is_none());
+    }
+
+    #[test]
+    fn test_extract_archive() {
+        let dir = std::env::temp_dir().join("orthanc_test_extract_archive");
+        let _ = fs::remove_dir_all(&dir);
+        let archive = zip(&[
+            ("foo/bar/IM0.dcm", dicom(b"foo")),
+            ("foo/IM1.dcm", dicom(b"bar")),
+        ]);
+        let paths = extract_archive(archive.as_slice(), &dir).unwrap();
+
+        assert_eq!(
+            paths,
+            vec![dir.join("foo/bar/IM0.dcm"), dir.join("foo/IM1.dcm")]
+        );
+        assert_eq!(
+            fs::read(dir.join("foo/bar/IM0.dcm")).unwrap(),
+            dicom(b"foo")
+        );
+        assert_eq!(fs::read(dir.join("foo/IM1.dcm")).unwrap(), dicom(b"bar"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_path_template_render() {
+        let tags = hashmap! {
+            "PatientID" => "patient_2",
+            "StudyDate" => "20110728",
+            "StudyDescription" => "CT/MR Brain",
+            "SeriesNumber" => "1",
+            "InstanceNumber" => "..",
+        };
+        let template = PathTemplate::new(
+            "{PatientID}/{StudyDate}_{StudyDescription}/{SeriesNumber}/{InstanceNumber}.dcm",
+        );
+
+        assert_eq!(
+            template
+                .render(|t| tags.get(t).map(|v| v.to_string()))
+                .unwrap(),
+            PathBuf::from("patient_2/20110728_CT_MR Brain/1/_.dcm")
+        );
+    }
+
+    #[test]
+    fn test_path_template_render_missing_tag() {
+        let template = PathTemplate::new("{PatientID}/{Foo}.dcm");
+        assert_eq!(
+            template
+                .render(|t| match t {
+                    "PatientID" => Some("  ".to_string()),
+                    _ => None,
+                })
+                .unwrap(),
+            PathBuf::from("unknown/unknown.dcm")
+        );
+    }
+
+    #[test]
+    fn test_path_template_render_error() {
+        assert_eq!(
+            PathTemplate::new("{PatientID/foo.dcm")
+                .render(|_| None)
+                .unwrap_err(),
+            Error::new(
+                "Unterminated placeholder in path template: {PatientID/foo.dcm",
+                None
+            )
+        );
+        assert_eq!(
+            PathTemplate::new("../{PatientID}.dcm")
+                .render(|_| None)
+                .unwrap_err(),
+            Error::new(
+                "Path template renders to an invalid path: ../unknown.dcm",
+                None
+            )
+        );
+        assert!(PathTemplate::new("/{PatientID}.dcm")
+            .render(|_| None)
+            .is_err());
+    }
+}
@@ -1,16 +1,26 @@
+use crate::archive::{extract_archive, PathTemplate};
 use crate::entity::*;
 use crate::error::Error;
+use crate::ids::{InstanceId, PatientId, SeriesId, StudyId};
+use crate::import::{failure_reason, find_dicom_files, ImportFailure, ImportReport};
 use crate::models::*;
-use crate::utils::check_http_error;
+use crate::progress::{Observer, Progress, ProgressReader, ProgressWriter, Transfer};
+use crate::utils::{check_http_error, check_http_error_response, parallel_map, Md5Writer};
 use crate::Result;
 use bytes::Bytes;
 use reqwest;
+use serde::de::DeserializeOwned;
 use serde_json::json;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
+use std::fs;
+use std::io;
 use std::io::prelude::*;
+use std::path::{Path, PathBuf};
 use std::str;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use std::time;
 
 /// Client type
@@ -41,6 +51,8 @@ pub struct Client {
     username: Option<String>,
     password: Option<String>,
     client: reqwest::blocking::Client,
+    progress: Option<Observer>,
+    verify_integrity: bool,
 }
 
 impl Client {
@@ -60,6 +72,8 @@ impl Client {
             username: None,
             password: None,
             client,
+            progress: None,
+            verify_integrity: false,
         }
     }
 
@@ -78,6 +92,40 @@ impl Client {
         self
     }
 
+    /// Adds a progress observer to the client instance
+    ///
+    /// The observer is notified about the progress of every upload and download, along with the
+    /// [`Transfer`] it belongs to. See [`progress`](crate::progress) for details.
+    ///
+    /// ```
+    /// let client = Client::new("http://localhost:8042")
+    ///     .progress(|t: &Transfer| println!("#{}: {} of {:?} bytes", t.id, t.transferred, t.total));
+    /// ```
+    pub fn progress(mut self, progress: impl Progress + 'static) -> Client {
+        self.progress = Some(Observer(Arc::new(progress)));
+        self
+    }
+
+    /// Enables or disables integrity verification of downloaded instances and attachments
+    ///
+    /// When enabled, the MD5 digest of the data, downloaded by [`Client::instance_dicom`],
+    /// [`Client::instance_dicom_resume`], [`Client::instance_attachment_data`] and
+    /// [`Client::instance_attachment_resume`], is compared to the one reported by Orthanc. A
+    /// mismatch results in an error, which carries the digests in [`Error::integrity`].
+    ///
+    /// The data is streamed, so it has already been written to the writer by the time the
+    /// mismatch is detected. On an integrity error the contents of the writer are corrupt and must
+    /// be discarded by the caller, e.g. by removing the written file. The resuming methods truncate
+    /// the file themselves, so that the next attempt downloads it from the start.
+    ///
+    /// ```
+    /// let client = Client::new("http://localhost:8042").verify_integrity(true);
+    /// ```
+    pub fn verify_integrity(mut self, verify: bool) -> Client {
+        self.verify_integrity = verify;
+        self
+    }
+
     fn add_auth(
         &self,
         request: reqwest::blocking::RequestBuilder,
@@ -90,7 +138,7 @@ impl Client {
 
     ////////// HTTP //////////
 
-    fn get(&self, path: &str) -> Result<Bytes> {
+    fn get_bytes(&self, path: &str) -> Result<Bytes> {
         let url = format!("{}/{}", self.server, &path);
         let mut request = self.client.get(&url);
         request = self.add_auth(request);
@@ -100,23 +148,60 @@ impl Client {
         check_http_error(status, body)
     }
 
-    fn get_stream<W: Write>(&self, path: &str, mut writer: W) -> Result<()> {
+    fn get_response(&self, path: &str) -> Result<reqwest::blocking::Response> {
         let url = format!("{}/{}", self.server, &path);
         let mut request = self.client.get(&url);
         request = self.add_auth(request);
-        let mut resp = request.send()?;
-        let status = resp.status();
+        check_http_error_response(request.send()?)
+    }
 
-        // TODO: Simplify this
-        if status >= reqwest::StatusCode::BAD_REQUEST {
-            let message = format!("API error: {}", status);
-            let body = resp.bytes()?;
-            if body.is_empty() {
-                return Err(Error::new(&message, None));
-            };
-            return Err(Error::new(&message, serde_json::from_slice(&body)?));
-        }
-        resp.copy_to(&mut writer)?;
+    fn get_range_response(
+        &self,
+        path: &str,
+        start: u64,
+        end: Option<u64>,
+    ) -> Result<reqwest::blocking::Response> {
+        let url = format!("{}/{}", self.server, &path);
+        let range = match end {
+            Some(e) => format!("bytes={}-{}", start, e),
+            None => format!("bytes={}-", start),
+        };
+        let mut request = self.client.get(&url).header(reqwest::header::RANGE, range);
+        request = self.add_auth(request);
+        check_http_error_response(request.send()?)
+    }
+
+    fn get_accept_response(
+        &self,
+        path: &str,
+        accept: &str,
+    ) -> Result<reqwest::blocking::Response> {
+        let url = format!("{}/{}", self.server, &path);
+        let mut request = self
+            .client
+            .get(&url)
+            .header(reqwest::header::ACCEPT, accept);
+        request = self.add_auth(request);
+        check_http_error_response(request.send()?)
+    }
+
+    fn get_stream<W: Write>(&self, path: &str, writer: W) -> Result<()> {
+        let resp = self.get_response(path)?;
+        self.copy_response(path, resp, writer)
+    }
+
+    fn copy_response<W: Write>(
+        &self,
+        path: &str,
+        mut resp: reqwest::blocking::Response,
+        mut writer: W,
+    ) -> Result<()> {
+        let total = resp.content_length();
+        resp.copy_to(&mut ProgressWriter::new(
+            &mut writer,
+            self.progress.clone(),
+            Transfer::new(path, total),
+        ))?;
         Ok(())
     }
 
@@ -142,26 +227,24 @@ impl Client {
         let url = format!("{}/{}", self.server, path);
         let mut request = self.client.post(&url).json(&data);
         request = self.add_auth(request);
-        let mut resp = request.send()?;
-        let status = resp.status();
-
-        // TODO: Simplify this
-        if status >= reqwest::StatusCode::BAD_REQUEST {
-            let message = format!("API error: {}", status);
-            let body = resp.bytes()?;
-            if body.is_empty() {
-                return Err(Error::new(&message, None));
-            };
-            return Err(Error::new(&message, serde_json::from_slice(&body)?));
-        }
-        resp.copy_to(&mut writer)?;
+        let mut resp = check_http_error_response(request.send()?)?;
+        let total = resp.content_length();
+        resp.copy_to(&mut ProgressWriter::new(
+            &mut writer,
+            self.progress.clone(),
+            Transfer::new(path, total),
+        ))?;
         Ok(())
     }
 
-    fn post_bytes(&self, path: &str, data: &[u8]) -> Result<Bytes> {
+    fn post_bytes(&self, path: &str, data: Vec<u8>) -> Result<Bytes> {
+        let size = data.len() as u64;
+        self.post_stream(path, io::Cursor::new(data), size)
+    }
+
+    fn post_body(&self, path: &str, body: reqwest::blocking::Body) -> Result<Bytes> {
         let url = format!("{}/{}", self.server, path);
-        // TODO: .to_vec() here is probably not a good idea?
-        let mut request = self.client.post(&url).body(data.to_vec());
+        let mut request = self.client.post(&url).body(body);
         request = self.add_auth(request);
         let resp = request.send()?;
         let status = resp.status();
@@ -169,6 +252,26 @@ impl Client {
         check_http_error(status, body)
     }
 
+    fn post_stream<R: Read + Send + 'static>(
+        &self,
+        path: &str,
+        reader: R,
+        size: u64,
+    ) -> Result<Bytes> {
+        self.post_transfer(reader, Transfer::new(path, Some(size)))
+    }
+
+    fn post_transfer<R: Read + Send + 'static>(
+        &self,
+        reader: R,
+        transfer: Transfer,
+    ) -> Result<Bytes> {
+        let path = transfer.path.clone();
+        let size = transfer.total.unwrap_or_default();
+        let reader = ProgressReader::new(reader, self.progress.clone(), transfer);
+        self.post_body(&path, reqwest::blocking::Body::sized(reader, size))
+    }
+
     fn put(&self, path: &str, data: Value) -> Result<Bytes> {
         let url = format!("{}/{}", self.server, path);
         let mut request = self.client.put(&url).json(&data);
@@ -179,7 +282,7 @@ impl Client {
         check_http_error(status, body)
     }
 
-    fn delete(&self, path: &str) -> Result<Bytes> {
+    fn delete_bytes(&self, path: &str) -> Result<Bytes> {
         let url = format!("{}/{}", self.server, &path);
         let mut request = self.client.delete(&url);
         request = self.add_auth(request);
@@ -191,8 +294,86 @@ impl Client {
 
     ////////// Helpers //////////
 
-    fn list(&self, entity: &str) -> Result<Vec<String>> {
-        let resp = self.get(entity)?;
+    fn get_stream_verified<W: Write>(
+        &self,
+        path: &str,
+        md5_path: &str,
+        writer: W,
+    ) -> Result<()> {
+        if !self.verify_integrity {
+            return self.get_stream(path, writer);
+        }
+        let expected = self.get_md5(md5_path)?;
+        let mut writer = Md5Writer::new(writer);
+        self.get_stream(path, &mut writer)?;
+        let actual = writer.digest();
+        if actual != expected {
+            return Err(Error::integrity(&expected, &actual));
+        }
+        Ok(())
+    }
+
+    fn get_md5(&self, path: &str) -> Result<String> {
+        let resp = self.get_bytes(path)?;
+        Ok(String::from_utf8_lossy(&resp).trim().to_string())
+    }
+
+    fn resume(
+        &self,
+        path: &str,
+        md5_path: &str,
+        file_path: &Path,
+        size: u64,
+    ) -> Result<u64> {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(file_path)?;
+        let mut start = file.metadata()?.len();
+        if start > size {
+            // Not a partial download of this file, start over
+            file.set_len(0)?;
+            start = 0;
+        }
+        if start < size {
+            let mut resp = self.get_range_response(path, start, None)?;
+            if resp.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+                // The server ignored the range and sends the whole file
+                file.set_len(0)?;
+            }
+            let total = resp.content_length();
+            resp.copy_to(&mut ProgressWriter::new(
+                &mut file,
+                self.progress.clone(),
+                Transfer::new(path, total),
+            ))?;
+        }
+        let downloaded = fs::metadata(file_path)?.len();
+        if downloaded != size {
+            return Err(Error::new(
+                &format!(
+                    "Size of the downloaded file ({} bytes) does not match the expected size ({} bytes)",
+                    downloaded, size
+                ),
+                None,
+            ));
+        }
+        if self.verify_integrity {
+            let expected = self.get_md5(md5_path)?;
+            let mut writer = Md5Writer::new(io::sink());
+            io::copy(&mut fs::File::open(file_path)?, &mut writer)?;
+            let actual = writer.digest();
+            if actual != expected {
+                // The corrupt data must not be resumed from, the next attempt starts over
+                file.set_len(0)?;
+                return Err(Error::integrity(&expected, &actual));
+            }
+        }
+        Ok(downloaded)
+    }
+
+    fn list_ids(&self, path: &str) -> Result<Vec<String>> {
+        let resp = self.get_bytes(path)?;
         let json: Vec<String> = serde_json::from_slice(&resp)?;
         Ok(json)
     }
@@ -203,17 +384,8 @@ impl Client {
         id: &str,
         anonymization: Option<Anonymization>,
     ) -> Result<ModificationResult> {
-        let data = match anonymization {
-            Some(a) => a,
-            // TODO: Just pass an empty object?
-            None => Anonymization {
-                replace: None,
-                keep: None,
-                keep_private_tags: None,
-                dicom_version: None,
-                force: None,
-            },
-        };
+        let mut data = anonymization.unwrap_or_default();
+        data.asynchronous = None;
         let resp = self.post(
             &format!("{}/{}/anonymize", entity, id),
             Some(serde_json::to_value(data)?),
@@ -222,12 +394,44 @@ impl Client {
         Ok(json)
     }
 
+    fn anonymize_async(
+        &self,
+        entity: &str,
+        id: &str,
+        anonymization: Option<Anonymization>,
+    ) -> Result<Job> {
+        let mut data = anonymization.unwrap_or_default();
+        data.asynchronous = Some(true);
+        let resp = self.post(
+            &format!("{}/{}/anonymize", entity, id),
+            Some(serde_json::to_value(data)?),
+        )?;
+        let json: Job = serde_json::from_slice(&resp)?;
+        Ok(json)
+    }
+
+    fn modify_async(
+        &self,
+        entity: &str,
+        id: &str,
+        mut modification: Modification,
+    ) -> Result<Job> {
+        modification.asynchronous = Some(true);
+        let resp = self.post(
+            &format!("{}/{}/modify", entity, id),
+            Some(serde_json::to_value(modification)?),
+        )?;
+        let json: Job = serde_json::from_slice(&resp)?;
+        Ok(json)
+    }
+
     fn modify(
         &self,
         entity: &str,
         id: &str,
-        modification: Modification,
+        mut modification: Modification,
     ) -> Result<ModificationResult> {
+        modification.asynchronous = None;
         let resp = self.post(
             &format!("{}/{}/modify", entity, id),
             Some(serde_json::to_value(modification)?),
@@ -236,16 +440,172 @@ impl Client {
         Ok(json)
     }
 
+    fn navigate<T: DeserializeOwned>(
+        &self,
+        kind: EntityKind,
+        id: &str,
+        target: &str,
+    ) -> Result<T> {
+        let resp = self.get_bytes(&format!("{}/{}/{}", kind.path(), id, target))?;
+        let json: T = serde_json::from_slice(&resp)?;
+        Ok(json)
+    }
+
+    fn tags(
+        &self,
+        kind: EntityKind,
+        id: &str,
+        target: &str,
+        format: TagsFormat,
+    ) -> Result<Tags> {
+        let resp = self.get_bytes(&format!(
+            "{}/{}/{}{}",
+            kind.path(),
+            id,
+            target,
+            format.query()
+        ))?;
+        Tags::from_value(format, serde_json::from_slice(&resp)?)
+    }
+
+    fn instances_tags(
+        &self,
+        kind: EntityKind,
+        id: &str,
+        format: TagsFormat,
+    ) -> Result<HashMap<InstanceId, Tags>> {
+        let resp = self.get_bytes(&format!(
+            "{}/{}/instances-tags{}",
+            kind.path(),
+            id,
+            format.query()
+        ))?;
+        let json: HashMap<InstanceId, Value> = serde_json::from_slice(&resp)?;
+        json.into_iter()
+            .map(|(id, tags)| Ok((id, Tags::from_value(format, tags)?)))
+            .collect()
+    }
+
+    fn find_by_uid<T: Entity>(&self, uid: &str) -> Result<Option<T>> {
+        let kind = T::kind();
+        let found = self.lookup(uid)?.into_iter().find(|r| r.entity == kind);
+        match found {
+            Some(r) => self.get::<T>(r.id).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn archive<W: Write>(&self, path: &str, mut archive: Archive, writer: W) -> Result<()> {
+        archive.synchronous = Some(true);
+        self.post_receive_stream(path, serde_json::to_value(archive)?, writer)
+    }
+
+    fn archive_async(&self, path: &str, mut archive: Archive) -> Result<Job> {
+        archive.synchronous = Some(false);
+        let resp = self.post(path, Some(serde_json::to_value(archive)?))?;
+        let json: Job = serde_json::from_slice(&resp)?;
+        Ok(json)
+    }
+
+    fn media<W: Write>(
+        &self,
+        entity: &str,
+        id: &str,
+        transcode: Option<TransferSyntax>,
+        writer: W,
+    ) -> Result<()> {
+        let path = match transcode {
+            Some(t) => format!("{}/{}/media?transcode={}", entity, id, t.uid()),
+            None => format!("{}/{}/media", entity, id),
+        };
+        self.get_stream(&path, writer)
+    }
+
+    fn extract(
+        &self,
+        entity: &str,
+        id: &str,
+        dir: &Path,
+        template: Option<&PathTemplate>,
+    ) -> Result<Vec<PathBuf>> {
+        let template = match template {
+            Some(t) => t,
+            None => {
+                let path = format!("{}/{}/archive", entity, id);
+                let resp = self.get_response(&path)?;
+                let total = resp.content_length();
+                return extract_archive(
+                    ProgressReader::new(
+                        resp,
+                        self.progress.clone(),
+                        Transfer::new(&path, total),
+                    ),
+                    dir,
+                );
+            }
+        };
+
+        let resp = self.get_bytes(&format!("{}/{}/instances", entity, id))?;
+        let instances: Vec<Instance> = serde_json::from_slice(&resp)?;
+        let mut series: HashMap<SeriesId, Series> = HashMap::new();
+        let mut studies: HashMap<StudyId, Study> = HashMap::new();
+        let mut seen = HashSet::new();
+        let mut targets = vec![];
+
+        for instance in instances {
+            if !series.contains_key(&instance.parent_series) {
+                let s = self.series(&instance.parent_series)?;
+                series.insert(instance.parent_series.clone(), s);
+            }
+            let s = &series[&instance.parent_series];
+            if !studies.contains_key(&s.parent_study) {
+                studies.insert(s.parent_study.clone(), self.study(&s.parent_study)?);
+            }
+            let st = &studies[&s.parent_study];
+
+            let path = dir.join(template.render(|tag| {
+                instance
+                    .main_dicom_tag(tag)
+                    .or_else(|| s.main_dicom_tag(tag))
+                    .or_else(|| st.main_dicom_tag(tag))
+                    .map(str::to_string)
+            })?);
+            if !seen.insert(path.clone()) {
+                return Err(Error::new(
+                    &format!(
+                        "Path template renders to a duplicate path: {}",
+                        path.display()
+                    ),
+                    None,
+                ));
+            }
+            targets.push((instance.id, path));
+        }
+
+        // All paths are resolved before anything is written, so that a duplicate path does not
+        // leave a partially extracted tree behind
+        let mut paths = vec![];
+        for (id, path) in targets {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let file = fs::File::create(&path)?;
+            self.instance_dicom(&id, file)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
     ////////// Modalities //////////
 
     /// List modalities
     pub fn modalities(&self) -> Result<Vec<String>> {
-        self.list("modalities")
+        self.list_ids("modalities")
     }
 
     /// List all modalities in an expanded format
     pub fn modalities_expanded(&self) -> Result<HashMap<String, Modality>> {
-        let resp = self.get("modalities?expand")?;
+        let resp = self.get_bytes("modalities?expand")?;
         let json: HashMap<String, Modality> = serde_json::from_slice(&resp)?;
         Ok(json)
     }
@@ -271,7 +631,8 @@ impl Client {
 
     /// Delete a modality
     pub fn delete_modality(&self, name: &str) -> Result<()> {
-        self.delete(&format!("modalities/{}", name)).map(|_| ())
+        self.delete_bytes(&format!("modalities/{}", name))
+            .map(|_| ())
     }
 
     /// Send a C-ECHO request to a remote modality
@@ -301,6 +662,10 @@ impl Client {
     ///
     /// `ids` is a slice of entity IDs to send. An ID can signify either of [`Patient`], [`Study`],
     /// [`Series`] or [`Instance`]
+    ///
+    /// If the modality does not accept the transfer syntax of an instance, Orthanc transcodes the
+    /// instance to a syntax accepted by the modality, provided that [`Modality::allow_transcoding`]
+    /// is enabled.
     pub fn modality_store(
         &self,
         modality: &str,
@@ -361,12 +726,12 @@ impl Client {
 
     /// List peers
     pub fn peers(&self) -> Result<Vec<String>> {
-        self.list("peers")
+        self.list_ids("peers")
     }
 
     /// List all peers in an expanded format
     pub fn peers_expanded(&self) -> Result<HashMap<String, Peer>> {
-        let resp = self.get("peers?expand")?;
+        let resp = self.get_bytes("peers?expand")?;
         let json: HashMap<String, Peer> = serde_json::from_slice(&resp)?;
         Ok(json)
     }
@@ -386,7 +751,7 @@ impl Client {
 
     /// Delete a peer
     pub fn delete_peer(&self, name: &str) -> Result<()> {
-        self.delete(&format!("peers/{}", name)).map(|_| ())
+        self.delete_bytes(&format!("peers/{}", name)).map(|_| ())
     }
 
     /// Send entities to a peer
@@ -402,25 +767,140 @@ impl Client {
         Ok(json)
     }
 
+    /// Send entities to a peer, transcoding them to `transfer_syntax` beforehand
+    ///
+    /// `ids` is a slice of entity IDs to send. An ID can signify either of [`Patient`], [`Study`],
+    /// [`Series`] or [`Instance`]
+    pub fn peer_store_transcoded(
+        &self,
+        peer: &str,
+        ids: &[&str],
+        transfer_syntax: TransferSyntax,
+    ) -> Result<PeerStoreResult> {
+        let body = PeerStore {
+            resources: ids.iter().map(|id| id.to_string()).collect(),
+            transcode: Some(transfer_syntax),
+            synchronous: Some(true),
+        };
+        let resp = self.post(
+            &format!("peers/{}/store", peer),
+            Some(serde_json::to_value(body)?),
+        )?;
+        let json: PeerStoreResult = serde_json::from_slice(&resp)?;
+        Ok(json)
+    }
+
+    ////////// Entities //////////
+
+    /// List the IDs of all entities of a kind
+    ///
+    /// ```
+    /// let studies = client.list::<Study>().unwrap();
+    /// ```
+    pub fn list<T: Entity>(&self) -> Result<Vec<T::Id>> {
+        let ids = self.list_ids(T::kind().path())?;
+        Ok(ids.into_iter().map(T::Id::from).collect())
+    }
+
+    /// List all entities of a kind in an expanded format
+    pub fn list_expanded<T: Entity>(&self) -> Result<Vec<T>> {
+        let resp = self.get_bytes(&format!("{}?expand", T::kind().path()))?;
+        let json: Vec<T> = serde_json::from_slice(&resp)?;
+        Ok(json)
+    }
+
+    /// Get an entity by its ID
+    ///
+    /// ```
+    /// let study = client
+    ///     .get::<Study>("63bf5d42-b5382159-01971752-e0ceea3d-399bbca5")
+    ///     .unwrap();
+    /// ```
+    pub fn get<T: Entity>(&self, id: impl Into<T::Id>) -> Result<T> {
+        let resp = self.get_bytes(&format!("{}/{}", T::kind().path(), id.into()))?;
+        let json: T = serde_json::from_slice(&resp)?;
+        Ok(json)
+    }
+
+    /// Delete an entity
+    pub fn delete<T: Entity>(&self, id: impl Into<T::Id>) -> Result<RemainingAncestor> {
+        let resp = self.delete_bytes(&format!("{}/{}", T::kind().path(), id.into()))?;
+        let json: RemainingAncestor = serde_json::from_slice(&resp)?;
+        Ok(json)
+    }
+
+    /// Download an entity
+    ///
+    /// Accepts a mutable reference to an object, that implements a [`Write`] trait, and mutates the
+    /// object, writing the data into it in a streaming fashion.
+    ///
+    /// An [`Instance`] is downloaded as a DICOM file (see [`Client::instance_dicom`]), other
+    /// entities as a ZIP archive of DICOM files.
+    pub fn download<T: Entity>(
+        &self,
+        id: impl Into<T::Id>,
+        writer: impl Write,
+    ) -> Result<()> {
+        let kind = T::kind();
+        let id = id.into();
+        match kind {
+            EntityKind::Instance => {
+                let path = format!("instances/{}/file", id);
+                let md5_path = format!("instances/{}/attachments/dicom/md5", id);
+                self.get_stream_verified(&path, &md5_path, writer)
+            }
+            _ => self.get_stream(&format!("{}/{}/archive", kind.path(), id), writer),
+        }
+    }
+
     ////////// Patients //////////
 
     /// List patients
-    pub fn patients(&self) -> Result<Vec<String>> {
-        self.list("patients")
+    pub fn patients(&self) -> Result<Vec<PatientId>> {
+        self.list::<Patient>()
     }
 
     /// List all patients in an expanded format
     pub fn patients_expanded(&self) -> Result<Vec<Patient>> {
-        let resp = self.get("patients?expand")?;
-        let json: Vec<Patient> = serde_json::from_slice(&resp)?;
-        Ok(json)
+        self.list_expanded::<Patient>()
     }
 
     /// Get a patient by its ID
-    pub fn patient(&self, id: &str) -> Result<Patient> {
-        let resp = self.get(&format!("patients/{}", id))?;
-        let json: Patient = serde_json::from_slice(&resp)?;
-        Ok(json)
+    pub fn patient(&self, id: impl Into<PatientId>) -> Result<Patient> {
+        self.get::<Patient>(id)
+    }
+
+    /// Get all studies of a patient
+    pub fn patient_studies(&self, id: impl Into<PatientId>) -> Result<Vec<Study>> {
+        self.navigate(EntityKind::Patient, &id.into(), "studies")
+    }
+
+    /// Get all series of a patient
+    pub fn patient_series(&self, id: impl Into<PatientId>) -> Result<Vec<Series>> {
+        self.navigate(EntityKind::Patient, &id.into(), "series")
+    }
+
+    /// Get all instances of a patient
+    pub fn patient_instances(&self, id: impl Into<PatientId>) -> Result<Vec<Instance>> {
+        self.navigate(EntityKind::Patient, &id.into(), "instances")
+    }
+
+    /// Get the patient module tags of a patient
+    pub fn patient_module(
+        &self,
+        id: impl Into<PatientId>,
+        format: TagsFormat,
+    ) -> Result<Tags> {
+        self.tags(EntityKind::Patient, &id.into(), "module", format)
+    }
+
+    /// Get the DICOM tags of all instances of a patient, keyed by instance ID
+    pub fn patient_instances_tags(
+        &self,
+        id: impl Into<PatientId>,
+        format: TagsFormat,
+    ) -> Result<HashMap<InstanceId, Tags>> {
+        self.instances_tags(EntityKind::Patient, &id.into(), format)
     }
 
     /// Download a patient as a collection of DICOM files
@@ -436,55 +916,171 @@ impl Client {
     /// let mut file = fs::File::create("/tmp/patient.zip").unwrap();
     /// client().patient_dicom("3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c", &mut file).unwrap();
     /// ```
-    pub fn patient_dicom<W: Write>(&self, id: &str, writer: W) -> Result<()> {
-        let path = format!("patients/{}/archive", id);
-        self.get_stream(&path, writer)
+    pub fn patient_dicom<W: Write>(
+        &self,
+        id: impl Into<PatientId>,
+        writer: W,
+    ) -> Result<()> {
+        self.download::<Patient>(id, writer)
+    }
+
+    /// Download a patient as a DICOM media (a ZIP archive with a DICOMDIR)
+    ///
+    /// Accepts a mutable reference to an object, that implements a [`Write`] trait, and mutates the
+    /// object, writing the data into it in a streaming fashion.
+    ///
+    /// `transcode` is an optional transfer syntax to transcode the DICOM files to.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let mut file = fs::File::create("/tmp/patient_media.zip").unwrap();
+    /// client().patient_media("3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c", None, &mut file).unwrap();
+    /// ```
+    pub fn patient_media<W: Write>(
+        &self,
+        id: impl Into<PatientId>,
+        transcode: Option<TransferSyntax>,
+        writer: W,
+    ) -> Result<()> {
+        let id = id.into();
+        self.media("patients", &id, transcode, writer)
+    }
+
+    /// Download a patient and extract its DICOM files into a directory
+    ///
+    /// Without a `template`, the ZIP archive of the patient is streamed and extracted into `dir`,
+    /// preserving the directory structure, created by Orthanc. With a `template`, every instance
+    /// is downloaded into the path the template renders to, using the main DICOM tags of the
+    /// instance, its series, study and patient. Returns the paths of all extracted files.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let template = PathTemplate::new("{PatientID}/{StudyDate}_{StudyDescription}/{SeriesNumber}/{InstanceNumber}.dcm");
+    /// client().extract_patient("3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c", "/tmp/dicom", Some(&template)).unwrap();
+    /// ```
+    pub fn extract_patient(
+        &self,
+        id: impl Into<PatientId>,
+        dir: impl AsRef<Path>,
+        template: Option<&PathTemplate>,
+    ) -> Result<Vec<PathBuf>> {
+        let id = id.into();
+        self.extract("patients", &id, dir.as_ref(), template)
     }
 
     /// Anonymize a patient
     pub fn anonymize_patient(
         &self,
-        id: &str,
+        id: impl Into<PatientId>,
         anonymization: Option<Anonymization>,
     ) -> Result<ModificationResult> {
-        self.anonymize("patients", id, anonymization)
+        let id = id.into();
+        self.anonymize("patients", &id, anonymization)
+    }
+
+    /// Anonymize a patient asynchronously
+    ///
+    /// Returns the created [`Job`], that can be monitored with [`Client::job`]
+    pub fn anonymize_patient_async(
+        &self,
+        id: impl Into<PatientId>,
+        anonymization: Option<Anonymization>,
+    ) -> Result<Job> {
+        let id = id.into();
+        self.anonymize_async("patients", &id, anonymization)
     }
 
     /// Modify a patient
     pub fn modify_patient(
         &self,
-        id: &str,
+        id: impl Into<PatientId>,
         modification: Modification,
     ) -> Result<ModificationResult> {
-        self.modify("patients", id, modification)
+        let id = id.into();
+        self.modify("patients", &id, modification)
+    }
+
+    /// Modify a patient asynchronously
+    ///
+    /// Returns the created [`Job`], that can be monitored with [`Client::job`]
+    pub fn modify_patient_async(
+        &self,
+        id: impl Into<PatientId>,
+        modification: Modification,
+    ) -> Result<Job> {
+        let id = id.into();
+        self.modify_async("patients", &id, modification)
     }
 
     /// Delete a patient
-    pub fn delete_patient(&self, id: &str) -> Result<RemainingAncestor> {
-        let resp = self.delete(&format!("patients/{}", id))?;
-        let json: RemainingAncestor = serde_json::from_slice(&resp)?;
-        Ok(json)
+    pub fn delete_patient(&self, id: impl Into<PatientId>) -> Result<RemainingAncestor> {
+        self.delete::<Patient>(id)
     }
 
     ////////// Studies //////////
 
     /// List studies
-    pub fn studies(&self) -> Result<Vec<String>> {
-        self.list("studies")
+    pub fn studies(&self) -> Result<Vec<StudyId>> {
+        self.list::<Study>()
     }
 
     /// List all studies in an expanded format
     pub fn studies_expanded(&self) -> Result<Vec<Study>> {
-        let resp = self.get("studies?expand")?;
-        let json: Vec<Study> = serde_json::from_slice(&resp)?;
-        Ok(json)
+        self.list_expanded::<Study>()
     }
 
     /// Get a study by its ID
-    pub fn study(&self, id: &str) -> Result<Study> {
-        let resp = self.get(&format!("studies/{}", id))?;
-        let json: Study = serde_json::from_slice(&resp)?;
-        Ok(json)
+    pub fn study(&self, id: impl Into<StudyId>) -> Result<Study> {
+        self.get::<Study>(id)
+    }
+
+    /// Get the patient of a study
+    pub fn study_patient(&self, id: impl Into<StudyId>) -> Result<Patient> {
+        self.navigate(EntityKind::Study, &id.into(), "patient")
+    }
+
+    /// Get all series of a study
+    pub fn study_series(&self, id: impl Into<StudyId>) -> Result<Vec<Series>> {
+        self.navigate(EntityKind::Study, &id.into(), "series")
+    }
+
+    /// Get all instances of a study
+    pub fn study_instances(&self, id: impl Into<StudyId>) -> Result<Vec<Instance>> {
+        self.navigate(EntityKind::Study, &id.into(), "instances")
+    }
+
+    /// Get the DICOM tags, that have the same value in all instances of a study
+    pub fn study_shared_tags(
+        &self,
+        id: impl Into<StudyId>,
+        format: TagsFormat,
+    ) -> Result<Tags> {
+        self.tags(EntityKind::Study, &id.into(), "shared-tags", format)
+    }
+
+    /// Get the study module tags of a study
+    pub fn study_module(&self, id: impl Into<StudyId>, format: TagsFormat) -> Result<Tags> {
+        self.tags(EntityKind::Study, &id.into(), "module", format)
+    }
+
+    /// Get the patient module tags of a study
+    pub fn study_patient_module(
+        &self,
+        id: impl Into<StudyId>,
+        format: TagsFormat,
+    ) -> Result<Tags> {
+        self.tags(EntityKind::Study, &id.into(), "module-patient", format)
+    }
+
+    /// Get the DICOM tags of all instances of a study, keyed by instance ID
+    pub fn study_instances_tags(
+        &self,
+        id: impl Into<StudyId>,
+        format: TagsFormat,
+    ) -> Result<HashMap<InstanceId, Tags>> {
+        self.instances_tags(EntityKind::Study, &id.into(), format)
     }
 
     /// Download a study as a collection of DICOM files
@@ -500,56 +1096,286 @@ impl Client {
     /// let mut file = fs::File::create("/tmp/study.zip").unwrap();
     /// client().study_dicom("3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c", &mut file).unwrap();
     /// ```
-    pub fn study_dicom<W: Write>(&self, id: &str, writer: W) -> Result<()> {
-        let path = format!("studies/{}/archive", id);
-        self.get_stream(&path, writer)?;
-        Ok(())
+    pub fn study_dicom<W: Write>(&self, id: impl Into<StudyId>, writer: W) -> Result<()> {
+        self.download::<Study>(id, writer)
+    }
+
+    /// Download a study as a DICOM media (a ZIP archive with a DICOMDIR)
+    ///
+    /// Accepts a mutable reference to an object, that implements a [`Write`] trait, and mutates the
+    /// object, writing the data into it in a streaming fashion.
+    ///
+    /// `transcode` is an optional transfer syntax to transcode the DICOM files to.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let mut file = fs::File::create("/tmp/study_media.zip").unwrap();
+    /// client().study_media("3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c", None, &mut file).unwrap();
+    /// ```
+    pub fn study_media<W: Write>(
+        &self,
+        id: impl Into<StudyId>,
+        transcode: Option<TransferSyntax>,
+        writer: W,
+    ) -> Result<()> {
+        let id = id.into();
+        self.media("studies", &id, transcode, writer)
+    }
+
+    /// Download a study and extract its DICOM files into a directory
+    ///
+    /// Without a `template`, the ZIP archive of the study is streamed and extracted into `dir`,
+    /// preserving the directory structure, created by Orthanc. With a `template`, every instance
+    /// is downloaded into the path the template renders to, using the main DICOM tags of the
+    /// instance, its series, study and patient. Returns the paths of all extracted files.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let template = PathTemplate::new("{PatientID}/{StudyDate}_{StudyDescription}/{SeriesNumber}/{InstanceNumber}.dcm");
+    /// client().extract_study("3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c", "/tmp/dicom", Some(&template)).unwrap();
+    /// ```
+    pub fn extract_study(
+        &self,
+        id: impl Into<StudyId>,
+        dir: impl AsRef<Path>,
+        template: Option<&PathTemplate>,
+    ) -> Result<Vec<PathBuf>> {
+        let id = id.into();
+        self.extract("studies", &id, dir.as_ref(), template)
     }
 
     /// Anonymize a study
     pub fn anonymize_study(
         &self,
-        id: &str,
+        id: impl Into<StudyId>,
         anonymization: Option<Anonymization>,
     ) -> Result<ModificationResult> {
-        self.anonymize("studies", id, anonymization)
+        let id = id.into();
+        self.anonymize("studies", &id, anonymization)
+    }
+
+    /// Anonymize a study asynchronously
+    ///
+    /// Returns the created [`Job`], that can be monitored with [`Client::job`]
+    pub fn anonymize_study_async(
+        &self,
+        id: impl Into<StudyId>,
+        anonymization: Option<Anonymization>,
+    ) -> Result<Job> {
+        let id = id.into();
+        self.anonymize_async("studies", &id, anonymization)
     }
 
     /// Modify a study
     pub fn modify_study(
         &self,
-        id: &str,
+        id: impl Into<StudyId>,
         modification: Modification,
     ) -> Result<ModificationResult> {
-        self.modify("studies", id, modification)
+        let id = id.into();
+        self.modify("studies", &id, modification)
     }
 
-    /// Delete a study
-    pub fn delete_study(&self, id: &str) -> Result<RemainingAncestor> {
-        let resp = self.delete(&format!("studies/{}", id))?;
-        let json: RemainingAncestor = serde_json::from_slice(&resp)?;
+    /// Modify a study asynchronously
+    ///
+    /// Returns the created [`Job`], that can be monitored with [`Client::job`]
+    pub fn modify_study_async(
+        &self,
+        id: impl Into<StudyId>,
+        modification: Modification,
+    ) -> Result<Job> {
+        let id = id.into();
+        self.modify_async("studies", &id, modification)
+    }
+
+    /// Split a study
+    ///
+    /// Moves the series and/or instances listed in `split` into a new study and returns the ID
+    /// of the new study.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let split = StudySplit {
+    ///     series: Some(vec!["1a2b3c4d-5e6f7a8b-9c0d1e2f-3a4b5c6d-7e8f9a0b".into()]),
+    ///     replace: Some(hashmap! {"StudyDescription".to_string() => "Misfiled".to_string()}),
+    ///     ..Default::default()
+    /// };
+    /// let result = client()
+    ///     .split_study("3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c", split)
+    ///     .unwrap();
+    /// ```
+    pub fn split_study(
+        &self,
+        id: impl Into<StudyId>,
+        mut split: StudySplit,
+    ) -> Result<StudySplitResult> {
+        let id = id.into();
+        split.asynchronous = None;
+        let resp = self.post(
+            &format!("studies/{}/split", id),
+            Some(serde_json::to_value(split)?),
+        )?;
+        let json: StudySplitResult = serde_json::from_slice(&resp)?;
+        Ok(json)
+    }
+
+    /// Split a study asynchronously
+    ///
+    /// Returns the created [`Job`], that can be monitored with [`Client::job`]
+    pub fn split_study_async(
+        &self,
+        id: impl Into<StudyId>,
+        mut split: StudySplit,
+    ) -> Result<Job> {
+        let id = id.into();
+        split.asynchronous = Some(true);
+        let resp = self.post(
+            &format!("studies/{}/split", id),
+            Some(serde_json::to_value(split)?),
+        )?;
+        let json: Job = serde_json::from_slice(&resp)?;
         Ok(json)
     }
 
+    /// Merge studies, series or instances into a study
+    ///
+    /// Moves the entities listed in `merge` into the study `id` and returns the ID of the study.
+    pub fn merge_study(
+        &self,
+        id: impl Into<StudyId>,
+        mut merge: StudyMerge,
+    ) -> Result<StudyMergeResult> {
+        let id = id.into();
+        merge.asynchronous = None;
+        let resp = self.post(
+            &format!("studies/{}/merge", id),
+            Some(serde_json::to_value(merge)?),
+        )?;
+        let json: StudyMergeResult = serde_json::from_slice(&resp)?;
+        Ok(json)
+    }
+
+    /// Merge studies, series or instances into a study asynchronously
+    ///
+    /// Returns the created [`Job`], that can be monitored with [`Client::job`]
+    pub fn merge_study_async(
+        &self,
+        id: impl Into<StudyId>,
+        mut merge: StudyMerge,
+    ) -> Result<Job> {
+        let id = id.into();
+        merge.asynchronous = Some(true);
+        let resp = self.post(
+            &format!("studies/{}/merge", id),
+            Some(serde_json::to_value(merge)?),
+        )?;
+        let json: Job = serde_json::from_slice(&resp)?;
+        Ok(json)
+    }
+
+    /// Delete a study
+    pub fn delete_study(&self, id: impl Into<StudyId>) -> Result<RemainingAncestor> {
+        self.delete::<Study>(id)
+    }
+
     ////////// Series //////////
 
     /// List series
-    pub fn series_list(&self) -> Result<Vec<String>> {
-        self.list("series")
+    pub fn series_list(&self) -> Result<Vec<SeriesId>> {
+        self.list::<Series>()
     }
 
     /// List all series in an expanded format
     pub fn series_expanded(&self) -> Result<Vec<Series>> {
-        let resp = self.get("series?expand")?;
-        let json: Vec<Series> = serde_json::from_slice(&resp)?;
-        Ok(json)
+        self.list_expanded::<Series>()
     }
 
     /// Get a series by its ID
-    pub fn series(&self, id: &str) -> Result<Series> {
-        let resp = self.get(&format!("series/{}", id))?;
-        let json: Series = serde_json::from_slice(&resp)?;
-        Ok(json)
+    pub fn series(&self, id: impl Into<SeriesId>) -> Result<Series> {
+        self.get::<Series>(id)
+    }
+
+    /// Get the patient of a series
+    pub fn series_patient(&self, id: impl Into<SeriesId>) -> Result<Patient> {
+        self.navigate(EntityKind::Series, &id.into(), "patient")
+    }
+
+    /// Get the study of a series
+    pub fn series_study(&self, id: impl Into<SeriesId>) -> Result<Study> {
+        self.navigate(EntityKind::Series, &id.into(), "study")
+    }
+
+    /// Get all instances of a series
+    pub fn series_instances(&self, id: impl Into<SeriesId>) -> Result<Vec<Instance>> {
+        self.navigate(EntityKind::Series, &id.into(), "instances")
+    }
+
+    /// Get the instances of a series, ordered by the position of their slices
+    ///
+    /// Orthanc sorts the slices by their position in the 3D volume, if the series is a volume,
+    /// and by their instance number otherwise (see [`OrderedSlices::sorting`]).
+    pub fn series_ordered_slices(&self, id: impl Into<SeriesId>) -> Result<OrderedSlices> {
+        self.navigate(EntityKind::Series, &id.into(), "ordered-slices")
+    }
+
+    /// Iterate over the instances of a series in the order of their slices
+    ///
+    /// Only the order is fetched eagerly, every instance is fetched from Orthanc when the
+    /// iterator reaches it.
+    pub fn series_ordered_instances(
+        &self,
+        id: impl Into<SeriesId>,
+    ) -> Result<impl Iterator<Item = Result<Instance>> + '_> {
+        let ids = self.series_ordered_slices(id)?.instance_ids();
+        Ok(ids.into_iter().map(move |id| self.instance(id)))
+    }
+
+    /// Download the pixel data of all instances of a series as a NumPy array
+    ///
+    /// Accepts a mutable reference to an object, that implements a [`Write`] trait, and mutates the
+    /// object, writing the data into it in a streaming fashion.
+    ///
+    /// Streamed data is a `.npy` file, or a `.npz` archive if [`NumpyOptions::compress`] is set
+    pub fn series_numpy<W: Write>(
+        &self,
+        id: impl Into<SeriesId>,
+        options: NumpyOptions,
+        writer: W,
+    ) -> Result<()> {
+        let id = id.into();
+        self.get_stream(&format!("series/{}/numpy{}", id, options.query()), writer)
+    }
+
+    /// Get the DICOM tags, that have the same value in all instances of a series
+    ///
+    /// Unlike fetching the tags of every instance, this only takes a single request.
+    pub fn series_shared_tags(
+        &self,
+        id: impl Into<SeriesId>,
+        format: TagsFormat,
+    ) -> Result<Tags> {
+        self.tags(EntityKind::Series, &id.into(), "shared-tags", format)
+    }
+
+    /// Get the series module tags of a series
+    pub fn series_module(
+        &self,
+        id: impl Into<SeriesId>,
+        format: TagsFormat,
+    ) -> Result<Tags> {
+        self.tags(EntityKind::Series, &id.into(), "module", format)
+    }
+
+    /// Get the DICOM tags of all instances of a series, keyed by instance ID
+    pub fn series_instances_tags(
+        &self,
+        id: impl Into<SeriesId>,
+        format: TagsFormat,
+    ) -> Result<HashMap<InstanceId, Tags>> {
+        self.instances_tags(EntityKind::Series, &id.into(), format)
     }
 
     /// Download a series as a collection of DICOM files
@@ -565,55 +1391,144 @@ impl Client {
     /// let mut file = fs::File::create("/tmp/series.zip").unwrap();
     /// client().series_dicom("3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c", &mut file).unwrap();
     /// ```
-    pub fn series_dicom<W: Write>(&self, id: &str, writer: W) -> Result<()> {
-        let path = format!("series/{}/archive", id);
-        self.get_stream(&path, writer)
+    pub fn series_dicom<W: Write>(&self, id: impl Into<SeriesId>, writer: W) -> Result<()> {
+        self.download::<Series>(id, writer)
+    }
+
+    /// Download a series as a DICOM media (a ZIP archive with a DICOMDIR)
+    ///
+    /// Accepts a mutable reference to an object, that implements a [`Write`] trait, and mutates the
+    /// object, writing the data into it in a streaming fashion.
+    ///
+    /// `transcode` is an optional transfer syntax to transcode the DICOM files to.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let mut file = fs::File::create("/tmp/series_media.zip").unwrap();
+    /// client().series_media("3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c", None, &mut file).unwrap();
+    /// ```
+    pub fn series_media<W: Write>(
+        &self,
+        id: impl Into<SeriesId>,
+        transcode: Option<TransferSyntax>,
+        writer: W,
+    ) -> Result<()> {
+        let id = id.into();
+        self.media("series", &id, transcode, writer)
+    }
+
+    /// Download a series and extract its DICOM files into a directory
+    ///
+    /// Without a `template`, the ZIP archive of the series is streamed and extracted into `dir`,
+    /// preserving the directory structure, created by Orthanc. With a `template`, every instance
+    /// is downloaded into the path the template renders to, using the main DICOM tags of the
+    /// instance, its series, study and patient. Returns the paths of all extracted files.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let template = PathTemplate::new("{PatientID}/{StudyDate}_{StudyDescription}/{SeriesNumber}/{InstanceNumber}.dcm");
+    /// client().extract_series("3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c", "/tmp/dicom", Some(&template)).unwrap();
+    /// ```
+    pub fn extract_series(
+        &self,
+        id: impl Into<SeriesId>,
+        dir: impl AsRef<Path>,
+        template: Option<&PathTemplate>,
+    ) -> Result<Vec<PathBuf>> {
+        let id = id.into();
+        self.extract("series", &id, dir.as_ref(), template)
     }
 
     /// Anonymize a series
     pub fn anonymize_series(
         &self,
-        id: &str,
+        id: impl Into<SeriesId>,
         anonymization: Option<Anonymization>,
     ) -> Result<ModificationResult> {
-        self.anonymize("series", id, anonymization)
+        let id = id.into();
+        self.anonymize("series", &id, anonymization)
+    }
+
+    /// Anonymize a series asynchronously
+    ///
+    /// Returns the created [`Job`], that can be monitored with [`Client::job`]
+    pub fn anonymize_series_async(
+        &self,
+        id: impl Into<SeriesId>,
+        anonymization: Option<Anonymization>,
+    ) -> Result<Job> {
+        let id = id.into();
+        self.anonymize_async("series", &id, anonymization)
     }
 
     /// Modify a series
     pub fn modify_series(
         &self,
-        id: &str,
+        id: impl Into<SeriesId>,
         modification: Modification,
     ) -> Result<ModificationResult> {
-        self.modify("series", id, modification)
+        let id = id.into();
+        self.modify("series", &id, modification)
+    }
+
+    /// Modify a series asynchronously
+    ///
+    /// Returns the created [`Job`], that can be monitored with [`Client::job`]
+    pub fn modify_series_async(
+        &self,
+        id: impl Into<SeriesId>,
+        modification: Modification,
+    ) -> Result<Job> {
+        let id = id.into();
+        self.modify_async("series", &id, modification)
     }
 
     /// Delete a series
-    pub fn delete_series(&self, id: &str) -> Result<RemainingAncestor> {
-        let resp = self.delete(&format!("series/{}", id))?;
-        let json: RemainingAncestor = serde_json::from_slice(&resp)?;
-        Ok(json)
+    pub fn delete_series(&self, id: impl Into<SeriesId>) -> Result<RemainingAncestor> {
+        self.delete::<Series>(id)
     }
 
     ////////// Instances //////////
 
     /// List instances
-    pub fn instances(&self) -> Result<Vec<String>> {
-        self.list("instances")
+    pub fn instances(&self) -> Result<Vec<InstanceId>> {
+        self.list::<Instance>()
     }
 
     /// List all instances in an expanded format
     pub fn instances_expanded(&self) -> Result<Vec<Instance>> {
-        let resp = self.get("instances?expand")?;
-        let json: Vec<Instance> = serde_json::from_slice(&resp)?;
-        Ok(json)
+        self.list_expanded::<Instance>()
     }
 
     /// Get an instance by its ID
-    pub fn instance(&self, id: &str) -> Result<Instance> {
-        let resp = self.get(&format!("instances/{}", id))?;
-        let json: Instance = serde_json::from_slice(&resp)?;
-        Ok(json)
+    pub fn instance(&self, id: impl Into<InstanceId>) -> Result<Instance> {
+        self.get::<Instance>(id)
+    }
+
+    /// Get the patient of an instance
+    pub fn instance_patient(&self, id: impl Into<InstanceId>) -> Result<Patient> {
+        self.navigate(EntityKind::Instance, &id.into(), "patient")
+    }
+
+    /// Get the study of an instance
+    pub fn instance_study(&self, id: impl Into<InstanceId>) -> Result<Study> {
+        self.navigate(EntityKind::Instance, &id.into(), "study")
+    }
+
+    /// Get the series of an instance
+    pub fn instance_series(&self, id: impl Into<InstanceId>) -> Result<Series> {
+        self.navigate(EntityKind::Instance, &id.into(), "series")
+    }
+
+    /// Get the instance module tags of an instance
+    pub fn instance_module(
+        &self,
+        id: impl Into<InstanceId>,
+        format: TagsFormat,
+    ) -> Result<Tags> {
+        self.tags(EntityKind::Instance, &id.into(), "module", format)
     }
 
     /// Get all DICOM tags of an instance in a simplified format
@@ -621,8 +1536,9 @@ impl Client {
     /// See related Orthanc documentation
     /// [section](https://book.orthanc-server.com/users/rest.html#accessing-the-dicom-fields-of-an-instance-as-a-json-file)
     /// for details
-    pub fn instance_tags(&self, id: &str) -> Result<Value> {
-        let resp = self.get(&format!("instances/{}/simplified-tags", id))?;
+    pub fn instance_tags(&self, id: impl Into<InstanceId>) -> Result<Value> {
+        let id = id.into();
+        let resp = self.get_bytes(&format!("instances/{}/simplified-tags", id))?;
         let json: Value = serde_json::from_slice(&resp)?;
         Ok(json)
     }
@@ -632,17 +1548,58 @@ impl Client {
     /// See related Orthanc documentation
     /// [section](https://book.orthanc-server.com/users/rest.html#accessing-the-dicom-fields-of-an-instance-as-a-json-file)
     /// for details
-    pub fn instance_tags_expanded(&self, id: &str) -> Result<Value> {
-        let resp = self.get(&format!("instances/{}/tags", id))?;
+    pub fn instance_tags_expanded(&self, id: impl Into<InstanceId>) -> Result<Value> {
+        let id = id.into();
+        let resp = self.get_bytes(&format!("instances/{}/tags", id))?;
         let json: Value = serde_json::from_slice(&resp)?;
         Ok(json)
     }
 
+    /// Download the pixel data of an instance as a NumPy array
+    ///
+    /// Accepts a mutable reference to an object, that implements a [`Write`] trait, and mutates the
+    /// object, writing the data into it in a streaming fashion.
+    ///
+    /// Streamed data is a `.npy` file, or a `.npz` archive if [`NumpyOptions::compress`] is set
+    pub fn instance_numpy<W: Write>(
+        &self,
+        id: impl Into<InstanceId>,
+        options: NumpyOptions,
+        writer: W,
+    ) -> Result<()> {
+        let id = id.into();
+        self.get_stream(
+            &format!("instances/{}/numpy{}", id, options.query()),
+            writer,
+        )
+    }
+
+    /// Get the file meta information of an instance
+    ///
+    /// Contains the transfer syntax of the stored DICOM file and the AE title of the modality,
+    /// that has sent it.
+    pub fn instance_header(
+        &self,
+        id: impl Into<InstanceId>,
+    ) -> Result<FileMetaInformation> {
+        let id = id.into();
+        let resp = self.get_bytes(&format!("instances/{}/header?simplify", id))?;
+        let json: FileMetaInformation = serde_json::from_slice(&resp)?;
+        Ok(json)
+    }
+
+    /// Get the file meta information of an instance in an expanded format, with the names, types
+    /// and values of all tags of the group
+    pub fn instance_header_expanded(&self, id: impl Into<InstanceId>) -> Result<Tags> {
+        self.tags(EntityKind::Instance, &id.into(), "header", TagsFormat::Full)
+    }
+
     /// Get all DICOM tags' codings of an instance
     ///
     /// Returns a [`Vec`]<[`String`]> of the following format: `["0008-0018", "0040-0260", "0040-0254"]`
-    pub fn instance_content(&self, id: &str) -> Result<Vec<String>> {
-        let resp = self.get(&format!("instances/{}/content", id))?;
+    pub fn instance_content(&self, id: impl Into<InstanceId>) -> Result<Vec<String>> {
+        let id = id.into();
+        let resp = self.get_bytes(&format!("instances/{}/content", id))?;
         let json = serde_json::from_slice(&resp)?;
         Ok(json)
     }
@@ -650,8 +1607,9 @@ impl Client {
     /// Get the value of a specific DICOM tag of an instance
     ///
     /// `tag` is the DICOM tag coding, e.g. `0008-0018`
-    pub fn instance_tag(&self, id: &str, tag: &str) -> Result<String> {
-        let resp = self.get(&format!("instances/{}/content/{}", id, tag))?;
+    pub fn instance_tag(&self, id: impl Into<InstanceId>, tag: &str) -> Result<String> {
+        let id = id.into();
+        let resp = self.get_bytes(&format!("instances/{}/content/{}", id, tag))?;
         Ok(String::from_utf8_lossy(&resp).trim().to_string())
     }
 
@@ -666,9 +1624,195 @@ impl Client {
     /// let mut file = fs::File::create("/tmp/instance.dcm").unwrap();
     /// client().instance_dicom("3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c", &mut file).unwrap();
     /// ```
-    pub fn instance_dicom<W: Write>(&self, id: &str, writer: W) -> Result<()> {
+    pub fn instance_dicom<W: Write>(
+        &self,
+        id: impl Into<InstanceId>,
+        writer: W,
+    ) -> Result<()> {
+        self.download::<Instance>(id, writer)
+    }
+
+    /// Download an instance as a DICOM file, transcoded to `transfer_syntax`
+    ///
+    /// The transcoding is requested with an `Accept` HTTP header and performed by Orthanc on the
+    /// fly. The stored instance is not changed. Integrity verification is not applicable to
+    /// transcoded files.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let mut file = fs::File::create("/tmp/instance.dcm").unwrap();
+    /// client()
+    ///     .instance_dicom_transcoded(
+    ///         "3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c",
+    ///         TransferSyntax::ExplicitVrLittleEndian,
+    ///         &mut file,
+    ///     )
+    ///     .unwrap();
+    /// ```
+    pub fn instance_dicom_transcoded<W: Write>(
+        &self,
+        id: impl Into<InstanceId>,
+        transfer_syntax: TransferSyntax,
+        writer: W,
+    ) -> Result<()> {
+        let id = id.into();
+        let accept = format!(
+            "application/dicom; transfer-syntax={}",
+            transfer_syntax.uid()
+        );
         let path = format!("instances/{}/file", id);
-        self.get_stream(&path, writer)
+        let resp = self.get_accept_response(&path, &accept)?;
+        self.copy_response(&path, resp, writer)
+    }
+
+    /// Get the transfer syntax, an instance is stored with
+    pub fn instance_transfer_syntax(
+        &self,
+        id: impl Into<InstanceId>,
+    ) -> Result<TransferSyntax> {
+        let id = id.into();
+        let resp = self.get_bytes(&format!("instances/{}/metadata/TransferSyntax", id))?;
+        Ok(TransferSyntax::from(
+            String::from_utf8_lossy(&resp).as_ref(),
+        ))
+    }
+
+    /// Download an instance as a DICOM file, resuming a previously interrupted download
+    ///
+    /// If the file at `path` exists, only the missing part of the instance is requested from
+    /// Orthanc (using an HTTP `Range` request) and appended to the file. If the server does not
+    /// support ranges, the file is downloaded from the start. The size of the resulting file is
+    /// verified against [`Instance::file_size`]. Returns the size of the file.
+    ///
+    /// ```
+    /// let size = client()
+    ///     .instance_dicom_resume("3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c", "/tmp/instance.dcm")
+    ///     .unwrap();
+    /// ```
+    pub fn instance_dicom_resume(
+        &self,
+        id: impl Into<InstanceId>,
+        path: impl AsRef<Path>,
+    ) -> Result<u64> {
+        let id = id.into();
+        let size = self.instance(&id)?.file_size;
+        self.resume(
+            &format!("instances/{}/file", id),
+            &format!("instances/{}/attachments/dicom/md5", id),
+            path.as_ref(),
+            size,
+        )
+    }
+
+    /// Download a byte range of an instance's DICOM file
+    ///
+    /// `start` and `end` are zero-based and inclusive. If `end` is [`None`], the data is
+    /// downloaded up to the end of the file. Returns an error if the server does not support
+    /// range requests.
+    ///
+    /// ```
+    /// let mut preamble: Vec<u8> = vec![];
+    /// client()
+    ///     .instance_dicom_range("3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c", 0, Some(131), &mut preamble)
+    ///     .unwrap();
+    /// ```
+    pub fn instance_dicom_range<W: Write>(
+        &self,
+        id: impl Into<InstanceId>,
+        start: u64,
+        end: Option<u64>,
+        mut writer: W,
+    ) -> Result<()> {
+        let id = id.into();
+        let path = format!("instances/{}/file", id);
+        let mut resp = self.get_range_response(&path, start, end)?;
+        if resp.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            return Err(Error::new("Range requests are not supported", None));
+        }
+        let total = resp.content_length();
+        resp.copy_to(&mut ProgressWriter::new(
+            &mut writer,
+            self.progress.clone(),
+            Transfer::new(&path, total),
+        ))?;
+        Ok(())
+    }
+
+    /// List the names of the attachments of an instance, e.g. `["dicom", "dicom-as-json"]`
+    pub fn instance_attachments(&self, id: impl Into<InstanceId>) -> Result<Vec<String>> {
+        let id = id.into();
+        let resp = self.get_bytes(&format!("instances/{}/attachments", id))?;
+        let json: Vec<String> = serde_json::from_slice(&resp)?;
+        Ok(json)
+    }
+
+    /// Get the (uncompressed) size of an instance's attachment in bytes
+    pub fn instance_attachment_size(
+        &self,
+        id: impl Into<InstanceId>,
+        name: &str,
+    ) -> Result<u64> {
+        let id = id.into();
+        let resp =
+            self.get_bytes(&format!("instances/{}/attachments/{}/size", id, name))?;
+        let json: u64 = serde_json::from_slice(&resp)?;
+        Ok(json)
+    }
+
+    /// Download the data of an instance's attachment
+    ///
+    /// Accepts a mutable reference to an object, that implements a [`Write`] trait, and mutates the
+    /// object, writing the data into it in a streaming fashion.
+    ///
+    /// ```
+    /// let mut file = fs::File::create("/tmp/instance.dcm").unwrap();
+    /// client()
+    ///     .instance_attachment_data("3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c", "dicom", &mut file)
+    ///     .unwrap();
+    /// ```
+    pub fn instance_attachment_data<W: Write>(
+        &self,
+        id: impl Into<InstanceId>,
+        name: &str,
+        writer: W,
+    ) -> Result<()> {
+        let id = id.into();
+        self.get_stream_verified(
+            &format!("instances/{}/attachments/{}/data", id, name),
+            &format!("instances/{}/attachments/{}/md5", id, name),
+            writer,
+        )
+    }
+
+    /// Get the MD5 digest of the (uncompressed) data of an instance's attachment
+    pub fn instance_attachment_md5(
+        &self,
+        id: impl Into<InstanceId>,
+        name: &str,
+    ) -> Result<String> {
+        let id = id.into();
+        self.get_md5(&format!("instances/{}/attachments/{}/md5", id, name))
+    }
+
+    /// Download the data of an instance's attachment, resuming a previously interrupted download
+    ///
+    /// Works like [`Client::instance_dicom_resume`], the size of the resulting file is verified
+    /// against [`Client::instance_attachment_size`]. Returns the size of the file.
+    pub fn instance_attachment_resume(
+        &self,
+        id: impl Into<InstanceId>,
+        name: &str,
+        path: impl AsRef<Path>,
+    ) -> Result<u64> {
+        let id = id.into();
+        let size = self.instance_attachment_size(&id, name)?;
+        self.resume(
+            &format!("instances/{}/attachments/{}/data", id, name),
+            &format!("instances/{}/attachments/{}/md5", id, name),
+            path.as_ref(),
+            size,
+        )
     }
 
     /// Anonymize an instance
@@ -684,21 +1828,14 @@ impl Client {
     /// ```
     pub fn anonymize_instance<W: Write>(
         &self,
-        id: &str,
+        id: impl Into<InstanceId>,
         anonymization: Option<Anonymization>,
         writer: W,
     ) -> Result<()> {
-        let data = match anonymization {
-            Some(a) => a,
-            // TODO: Just pass an empty object?
-            None => Anonymization {
-                replace: None,
-                keep: None,
-                keep_private_tags: None,
-                dicom_version: None,
-                force: None,
-            },
-        };
+        let id = id.into();
+        let mut data = anonymization.unwrap_or_default();
+        // An instance is always anonymized synchronously
+        data.asynchronous = None;
         self.post_receive_stream(
             &format!("instances/{}/anonymize", id),
             serde_json::to_value(data)?,
@@ -716,19 +1853,16 @@ impl Client {
     ///
     /// ```
     /// let mut file = fs::File::create("/tmp/modified_instance.dcm").unwrap();
-    /// let modification = Modification {
-    ///     replace: None,
-    ///     remove: vec!["PatientName"],
-    ///     force: false,
-    /// };
+    /// let modification = ModificationBuilder::new().remove("PatientName").build().unwrap();
     /// client().modify_instance("3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c", modification, &mut file).unwrap();
     /// ```
     pub fn modify_instance<W: Write>(
         &self,
-        id: &str,
+        id: impl Into<InstanceId>,
         modification: Modification,
         writer: W,
     ) -> Result<()> {
+        let id = id.into();
         self.post_receive_stream(
             &format!("instances/{}/modify", id),
             serde_json::to_value(modification)?,
@@ -738,49 +1872,133 @@ impl Client {
     }
 
     /// Delete an instance
-    pub fn delete_instance(&self, id: &str) -> Result<RemainingAncestor> {
-        let resp = self.delete(&format!("instances/{}", id))?;
-        let json: RemainingAncestor = serde_json::from_slice(&resp)?;
+    pub fn delete_instance(&self, id: impl Into<InstanceId>) -> Result<RemainingAncestor> {
+        self.delete::<Instance>(id)
+    }
+
+    ////////// Archives //////////
+
+    /// Create a ZIP archive out of a set of entities
+    ///
+    /// Accepts a mutable reference to an object, that implements a [`Write`] trait, and mutates the
+    /// object, writing the data into it in a streaming fashion. The archive is created
+    /// synchronously, regardless of the value of `archive.synchronous`.
+    ///
+    /// Example:
+    ///
+    /// ```
+    /// let mut file = fs::File::create("/tmp/archive.zip").unwrap();
+    /// let archive = Archive {
+    ///     resources: vec!["3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c".to_string()],
+    ///     ..Default::default()
+    /// };
+    /// client().create_archive(archive, &mut file).unwrap();
+    /// ```
+    pub fn create_archive<W: Write>(&self, archive: Archive, writer: W) -> Result<()> {
+        self.archive("tools/create-archive", archive, writer)
+    }
+
+    /// Create a ZIP archive out of a set of entities in an asynchronous job
+    ///
+    /// Once the job has succeeded, the archive can be downloaded with [`Client::job_archive`]
+    pub fn create_archive_async(&self, archive: Archive) -> Result<Job> {
+        self.archive_async("tools/create-archive", archive)
+    }
+
+    /// Create a DICOM media (a ZIP archive with a DICOMDIR) out of a set of entities
+    ///
+    /// Accepts a mutable reference to an object, that implements a [`Write`] trait, and mutates the
+    /// object, writing the data into it in a streaming fashion. The media is created
+    /// synchronously, regardless of the value of `archive.synchronous`.
+    pub fn create_media<W: Write>(&self, archive: Archive, writer: W) -> Result<()> {
+        self.archive("tools/create-media", archive, writer)
+    }
+
+    /// Create a DICOM media (a ZIP archive with a DICOMDIR) out of a set of entities in an
+    /// asynchronous job
+    ///
+    /// Once the job has succeeded, the media can be downloaded with [`Client::job_archive`]
+    pub fn create_media_async(&self, archive: Archive) -> Result<Job> {
+        self.archive_async("tools/create-media", archive)
+    }
+
+    /// Create a DICOM media with an extended DICOMDIR (containing type 3 tags) out of a set of
+    /// entities
+    ///
+    /// Accepts a mutable reference to an object, that implements a [`Write`] trait, and mutates the
+    /// object, writing the data into it in a streaming fashion. The media is created
+    /// synchronously, regardless of the value of `archive.synchronous`.
+    pub fn create_media_extended<W: Write>(
+        &self,
+        archive: Archive,
+        writer: W,
+    ) -> Result<()> {
+        self.archive("tools/create-media-extended", archive, writer)
+    }
+
+    /// Create a DICOM media with an extended DICOMDIR (containing type 3 tags) out of a set of
+    /// entities in an asynchronous job
+    ///
+    /// Once the job has succeeded, the media can be downloaded with [`Client::job_archive`]
+    pub fn create_media_extended_async(&self, archive: Archive) -> Result<Job> {
+        self.archive_async("tools/create-media-extended", archive)
+    }
+
+    ////////// Jobs //////////
+
+    /// Get information about a job
+    pub fn job(&self, id: &str) -> Result<JobInfo> {
+        let resp = self.get_bytes(&format!("jobs/{}", id))?;
+        let json: JobInfo = serde_json::from_slice(&resp)?;
         Ok(json)
     }
 
+    /// Download the archive or media, created by a successful job
+    ///
+    /// Accepts a mutable reference to an object, that implements a [`Write`] trait, and mutates the
+    /// object, writing the data into it in a streaming fashion.
+    pub fn job_archive<W: Write>(&self, id: &str, writer: W) -> Result<()> {
+        self.get_stream(&format!("jobs/{}/archive", id), writer)
+    }
+
     ////////// Queries //////////
 
     /// List queries
     pub fn queries(&self) -> Result<Vec<String>> {
-        self.list("queries")
+        self.list_ids("queries")
     }
 
     /// Get query level
     pub fn query_level(&self, id: &str) -> Result<EntityKind> {
         Ok(EntityKind::try_from(
-            self.get(&format!("queries/{}/level", id))?,
+            self.get_bytes(&format!("queries/{}/level", id))?,
         )?)
     }
 
     /// Get query modality
     pub fn query_modality(&self, id: &str) -> Result<String> {
-        let resp = self.get(&format!("queries/{}/modality", id))?;
+        let resp = self.get_bytes(&format!("queries/{}/modality", id))?;
         Ok(str::from_utf8(&resp)?.to_string())
     }
 
     /// Get query query
     pub fn query_query(&self, id: &str) -> Result<Value> {
-        let resp = self.get(&format!("queries/{}/query", id))?;
+        let resp = self.get_bytes(&format!("queries/{}/query", id))?;
         let json: Value = serde_json::from_slice(&resp)?;
         Ok(json)
     }
 
     /// List query answers
     pub fn query_answers(&self, id: &str) -> Result<Vec<String>> {
-        let resp = self.get(&format!("queries/{}/answers", id))?;
+        let resp = self.get_bytes(&format!("queries/{}/answers", id))?;
         let json: Vec<String> = serde_json::from_slice(&resp)?;
         Ok(json)
     }
 
     /// Get query answer
     pub fn query_answer(&self, id: &str, answer_id: &str) -> Result<Value> {
-        let resp = self.get(&format!("queries/{}/answers/{}/content", id, answer_id))?;
+        let resp =
+            self.get_bytes(&format!("queries/{}/answers/{}/content", id, answer_id))?;
         let json: Value = serde_json::from_slice(&resp)?;
         Ok(json)
     }
@@ -820,7 +2038,7 @@ impl Client {
 
     /// System information
     pub fn system(&self) -> Result<System> {
-        let resp = self.get("system")?;
+        let resp = self.get_bytes("system")?;
         let json: System = serde_json::from_slice(&resp)?;
         Ok(json)
     }
@@ -833,11 +2051,138 @@ impl Client {
     /// client.upload(&data).unwrap();
     /// ```
     pub fn upload(&self, data: &[u8]) -> Result<UploadResult> {
-        let resp = self.post_bytes("instances", data)?;
+        // reqwest needs to own the body. `upload_reader` avoids copying the data.
+        let resp = self.post_bytes("instances", data.to_vec())?;
+        let json: UploadResult = serde_json::from_slice(&resp)?;
+        Ok(json)
+    }
+
+    /// Upload a ZIP archive of DICOM files to Orthanc, reading it from `reader`
+    ///
+    /// `size` is the size of the archive in bytes. The archive is streamed to Orthanc, without
+    /// being read into memory first. Returns the result of every instance, contained in the
+    /// archive
+    ///
+    /// ```
+    /// let file = fs::File::open("/tmp/study.zip").unwrap();
+    /// let size = file.metadata().unwrap().len();
+    /// let client = Client::new("http://localhost:8042");
+    /// let results = client.upload_archive(file, size).unwrap();
+    /// let duplicates = results
+    ///     .iter()
+    ///     .filter(|r| r.status == UploadStatus::AlreadyStored)
+    ///     .count();
+    /// ```
+    pub fn upload_archive<R: Read + Send + 'static>(
+        &self,
+        reader: R,
+        size: u64,
+    ) -> Result<Vec<UploadResult>> {
+        let resp = self.post_stream("instances", reader, size)?;
+        let json: Vec<UploadResult> = serde_json::from_slice(&resp)?;
+        Ok(json)
+    }
+
+    /// Upload a ZIP archive of DICOM files to Orthanc from the file system
+    ///
+    /// The archive is streamed to Orthanc, without being read into memory first.
+    pub fn upload_archive_file(&self, path: impl AsRef<Path>) -> Result<Vec<UploadResult>> {
+        let file = fs::File::open(path)?;
+        let size = file.metadata()?.len();
+        self.upload_archive(file, size)
+    }
+
+    /// Upload a DICOM file to Orthanc, reading it from `reader`
+    ///
+    /// `size` is the size of the data in bytes. The data is streamed to Orthanc, without being
+    /// read into memory first.
+    ///
+    /// ```
+    /// let file = fs::File::open("/tmp/instance.dcm").unwrap();
+    /// let size = file.metadata().unwrap().len();
+    /// let client = Client::new("http://localhost:8042");
+    /// client.upload_reader(file, size).unwrap();
+    /// ```
+    pub fn upload_reader<R: Read + Send + 'static>(
+        &self,
+        reader: R,
+        size: u64,
+    ) -> Result<UploadResult> {
+        let resp = self.post_stream("instances", reader, size)?;
+        let json: UploadResult = serde_json::from_slice(&resp)?;
+        Ok(json)
+    }
+
+    /// Upload a DICOM file to Orthanc from the file system
+    ///
+    /// The file is streamed to Orthanc, without being read into memory first.
+    ///
+    /// ```
+    /// let client = Client::new("http://localhost:8042");
+    /// client.upload_file("/tmp/instance.dcm").unwrap();
+    /// ```
+    pub fn upload_file(&self, path: impl AsRef<Path>) -> Result<UploadResult> {
+        let file = fs::File::open(&path)?;
+        let size = file.metadata()?.len();
+        let transfer = Transfer::new("instances", Some(size)).file(path.as_ref());
+        let resp = self.post_transfer(file, transfer)?;
         let json: UploadResult = serde_json::from_slice(&resp)?;
         Ok(json)
     }
 
+    /// Upload all DICOM files from a directory and its subdirectories to Orthanc
+    ///
+    /// Files that do not start with a DICOM preamble are skipped. Files are uploaded by `workers`
+    /// threads in parallel. A failure to read or upload a single file or subdirectory does not stop
+    /// the import, it is recorded in the report instead. Only fails if `dir` itself can not be
+    /// read. Symbolic links to directories are not followed.
+    ///
+    /// ```
+    /// let client = Client::new("http://localhost:8042");
+    /// let report = client.import_directory("/mnt/nas/dicom", 4).unwrap();
+    /// for failure in report.failures {
+    ///     println!("{}: {}", failure.path.display(), failure.reason);
+    /// }
+    /// ```
+    pub fn import_directory(
+        &self,
+        dir: impl AsRef<Path>,
+        workers: usize,
+    ) -> Result<ImportReport> {
+        let found = find_dicom_files(dir.as_ref())?;
+        let files = found.dicom;
+        let results = parallel_map(&files, workers, |path| {
+            let size = fs::metadata(path)?.len();
+            self.upload_file(path).map(|r| (r, size))
+        });
+
+        let mut report = ImportReport {
+            skipped: found.other,
+            failures: found.failures,
+            ..Default::default()
+        };
+        for (path, res) in files.into_iter().zip(results) {
+            match res {
+                Ok((r, size)) => {
+                    report.total_bytes += size;
+                    match r.status {
+                        UploadStatus::Success => report.success.push(path),
+                        UploadStatus::AlreadyStored => report.already_stored.push(path),
+                        status => report.failures.push(ImportFailure {
+                            path,
+                            reason: format!("{:?}", status),
+                        }),
+                    }
+                }
+                Err(e) => report.failures.push(ImportFailure {
+                    path,
+                    reason: failure_reason(&e),
+                }),
+            }
+        }
+        Ok(report)
+    }
+
     /// Search for Entities in Orthanc
     pub fn search<T: Entity>(&self, query: HashMap<String, String>) -> Result<Vec<T>> {
         let kind = T::kind();
@@ -850,6 +2195,91 @@ impl Client {
         let json: Vec<T> = serde_json::from_slice(&resp)?;
         Ok(json)
     }
+
+    /// Look up Orthanc entities by a DICOM identifier
+    ///
+    /// `uid` is either of `PatientID`, `StudyInstanceUID`, `SeriesInstanceUID` or
+    /// `SOPInstanceUID`. Returns an empty [`Vec`] if no entity is found.
+    pub fn lookup(&self, uid: &str) -> Result<Vec<LookupResult>> {
+        let resp = self.post_body(
+            "tools/lookup",
+            reqwest::blocking::Body::from(uid.to_string()),
+        )?;
+        let json: Vec<LookupResult> = serde_json::from_slice(&resp)?;
+        Ok(json)
+    }
+
+    /// Find a patient by its `PatientID`. Returns [`None`] if the patient does not exist
+    pub fn find_patient_by_patient_id(&self, patient_id: &str) -> Result<Option<Patient>> {
+        self.find_by_uid(patient_id)
+    }
+
+    /// Find a study by its `StudyInstanceUID`. Returns [`None`] if the study does not exist
+    pub fn find_study_by_uid(&self, uid: &str) -> Result<Option<Study>> {
+        self.find_by_uid(uid)
+    }
+
+    /// Find a series by its `SeriesInstanceUID`. Returns [`None`] if the series does not exist
+    pub fn find_series_by_uid(&self, uid: &str) -> Result<Option<Series>> {
+        self.find_by_uid(uid)
+    }
+
+    /// Find an instance by its `SOPInstanceUID`. Returns [`None`] if the instance does not exist
+    pub fn find_instance_by_uid(&self, uid: &str) -> Result<Option<Instance>> {
+        self.find_by_uid(uid)
+    }
+
+    /// Reconstruct an entity from its DICOM files
+    ///
+    /// Re-reads the main DICOM tags of the entity and all its children from the stored DICOM
+    /// files, e.g. after the `MainDicomTags` configuration of Orthanc has been changed.
+    pub fn reconstruct(&self, kind: EntityKind, id: &str) -> Result<()> {
+        self.post(&format!("{}/{}/reconstruct", kind.path(), id), None)
+            .map(|_| ())
+    }
+
+    /// Reconstruct all studies from their DICOM files
+    ///
+    /// Studies are reconstructed by `workers` threads in parallel. `progress` is called with the
+    /// number of processed studies and the total number of studies every time a study has been
+    /// processed. A failure to reconstruct a single study does not stop the process, it is
+    /// recorded in the report instead.
+    ///
+    /// ```
+    /// let client = Client::new("http://localhost:8042");
+    /// let report = client
+    ///     .reconstruct_studies(4, |done, total| println!("{}/{}", done, total))
+    ///     .unwrap();
+    /// for failure in report.failures {
+    ///     println!("{}: {}", failure.id, failure.reason);
+    /// }
+    /// ```
+    pub fn reconstruct_studies(
+        &self,
+        workers: usize,
+        progress: impl Fn(usize, usize) + Sync,
+    ) -> Result<ReconstructReport> {
+        let studies = self.studies()?;
+        let total = studies.len();
+        let done = AtomicUsize::new(0);
+        let results = parallel_map(&studies, workers, |id| {
+            let res = self.reconstruct(EntityKind::Study, id);
+            progress(done.fetch_add(1, Ordering::SeqCst) + 1, total);
+            res
+        });
+
+        let mut report = ReconstructReport::default();
+        for (id, res) in studies.into_iter().zip(results) {
+            match res {
+                Ok(()) => report.reconstructed.push(id),
+                Err(e) => report.failures.push(ReconstructFailure {
+                    id,
+                    reason: failure_reason(&e),
+                }),
+            }
+        }
+        Ok(report)
+    }
 }
 
 #[cfg(test)]
@@ -865,6 +2295,14 @@ mod tests {
         assert_eq!(cl.server, "http://localhost:8042".to_string());
         assert_eq!(cl.username, None);
         assert_eq!(cl.password, None);
+        assert!(cl.progress.is_none());
+        assert!(!cl.verify_integrity);
+    }
+
+    #[test]
+    fn test_progress() {
+        let cl = Client::new("http://localhost:8042").progress(|_: &Transfer| ());
+        assert!(cl.progress.is_some());
     }
 
     #[test]
@@ -888,7 +2326,7 @@ mod tests {
             .create_on(&mock_server);
 
         let cl = Client::new(url).auth("foo", "bar");
-        let resp = cl.get("foo").unwrap();
+        let resp = cl.get_bytes("foo").unwrap();
 
         assert_eq!(resp, "bar");
         assert_eq!(m.times_called(), 1);
@@ -998,7 +2436,31 @@ mod tests {
             .create_on(&mock_server);
 
         let cl = Client::new(url).auth("foo", "bar");
-        let resp = cl.post_bytes("foo", "bar".as_bytes()).unwrap();
+        let resp = cl.post_bytes("foo", b"bar".to_vec()).unwrap();
+
+        assert_eq!(resp, "baz");
+        assert_eq!(m.times_called(), 1);
+    }
+
+    #[test]
+    fn test_post_body() {
+        let mock_server = MockServer::start();
+        let url = mock_server.url("");
+
+        let m = Mock::new()
+            .expect_method(Method::POST)
+            .expect_path("/foo")
+            .expect_body("bar")
+            .expect_header("Authorization", "Basic Zm9vOmJhcg==")
+            .expect_header("Content-Length", "3")
+            .return_header("Content-Type", "application/json")
+            .return_status(200)
+            .return_body("baz")
+            .create_on(&mock_server);
+
+        let cl = Client::new(url).auth("foo", "bar");
+        let body = reqwest::blocking::Body::sized(std::io::Cursor::new("bar"), 3);
+        let resp = cl.post_body("foo", body).unwrap();
 
         assert_eq!(resp, "baz");
         assert_eq!(m.times_called(), 1);
@@ -1017,7 +2479,7 @@ mod tests {
             .create_on(&mock_server);
 
         let cl = Client::new(url).auth("foo", "bar");
-        let resp = cl.delete("foo").unwrap();
+        let resp = cl.delete_bytes("foo").unwrap();
 
         assert_eq!(resp, "");
         assert_eq!(m.times_called(), 1);
@@ -1049,7 +2511,7 @@ mod tests {
             .create_on(&mock_server);
 
         let cl = Client::new(url);
-        let resp = cl.get("foo");
+        let resp = cl.get_bytes("foo");
 
         assert_eq!(
             resp.unwrap_err(),
@@ -1067,6 +2529,7 @@ mod tests {
                     orthanc_status: 15,
                     orthanc_error: "Bad file format".to_string(),
                 },),
+                integrity: None,
             },
         );
         assert_eq!(m.times_called(), 1);
@@ -1117,6 +2580,7 @@ mod tests {
                     orthanc_status: 15,
                     orthanc_error: "Bad file format".to_string(),
                 },),
+                integrity: None,
             },
         );
         assert_eq!(m.times_called(), 1);
@@ -1142,6 +2606,7 @@ mod tests {
             Error {
                 message: "API error: 400 Bad Request".to_string(),
                 details: None,
+                integrity: None,
             },
         );
         assert_eq!(m.times_called(), 1);
@@ -1191,6 +2656,7 @@ mod tests {
                     orthanc_status: 15,
                     orthanc_error: "Bad file format".to_string(),
                 },),
+                integrity: None,
             },
         );
         assert_eq!(m.times_called(), 1);
@@ -1241,6 +2707,7 @@ mod tests {
                     orthanc_status: 15,
                     orthanc_error: "Bad file format".to_string(),
                 },),
+                integrity: None,
             },
         );
         assert_eq!(m.times_called(), 1);
@@ -1266,6 +2733,7 @@ mod tests {
             Error {
                 message: "API error: 400 Bad Request".to_string(),
                 details: None,
+                integrity: None,
             },
         );
         assert_eq!(m.times_called(), 1);
@@ -1297,7 +2765,7 @@ mod tests {
             .create_on(&mock_server);
 
         let cl = Client::new(url);
-        let resp = cl.post_bytes("foo", &[13, 42, 17]);
+        let resp = cl.post_bytes("foo", vec![13, 42, 17]);
 
         assert_eq!(
             resp.unwrap_err(),
@@ -1315,6 +2783,7 @@ mod tests {
                     orthanc_status: 15,
                     orthanc_error: "Bad file format".to_string(),
                 },),
+                integrity: None,
             },
         );
         assert_eq!(m.times_called(), 1);
@@ -1386,6 +2855,7 @@ mod tests {
                     orthanc_status: 15,
                     orthanc_error: "Bad file format".to_string(),
                 },),
+                integrity: None,
             },
         );
         assert_eq!(m.times_called(), 1);
@@ -1417,7 +2887,7 @@ mod tests {
             .create_on(&mock_server);
 
         let cl = Client::new(url);
-        let resp = cl.delete("foo");
+        let resp = cl.delete_bytes("foo");
 
         assert_eq!(
             resp.unwrap_err(),
@@ -1435,6 +2905,7 @@ mod tests {
                     orthanc_status: 15,
                     orthanc_error: "Bad file format".to_string(),
                 },),
+                integrity: None,
             },
         );
         assert_eq!(m.times_called(), 1);
@@ -1452,7 +2923,7 @@ mod tests {
             .create_on(&mock_server);
 
         let cl = Client::new(url);
-        let resp = cl.get("foo");
+        let resp = cl.get_bytes("foo");
 
         assert!(resp.is_err());
         assert_eq!(
@@ -1460,6 +2931,7 @@ mod tests {
             Error {
                 message: "API error: 404 Not Found".to_string(),
                 details: None,
+                integrity: None,
             },
         );
         assert_eq!(m.times_called(), 1);
@@ -1479,7 +2951,7 @@ mod tests {
             .create_on(&mock_server);
 
         let cl = Client::new(url).auth("foo", "bar");
-        let resp = cl.list("foos").unwrap();
+        let resp = cl.list_ids("foos").unwrap();
 
         assert_eq!(resp, vec!["bar", "baz", "qux"]);
         assert_eq!(m.times_called(), 1);
@@ -1494,9 +2966,10 @@ mod tests {
             .expect_method(Method::POST)
             .expect_path("/studies/foo/modify")
             .expect_json_body(&Modification {
-                replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
+                replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
                 remove: Some(vec!["Tag2".to_string()]),
                 force: None,
+                ..Default::default()
             })
             .return_status(200)
             .return_body(
@@ -1517,9 +2990,10 @@ mod tests {
                 "studies",
                 "foo",
                 Modification {
-                    replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
+                    replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
                     remove: Some(vec!["Tag2".to_string()]),
                     force: None,
+                    ..Default::default()
                 },
             )
             .unwrap();
@@ -1527,8 +3001,8 @@ mod tests {
         assert_eq!(
             resp,
             ModificationResult {
-                id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-                patient_id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
+                id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".into(),
+                patient_id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".into(),
                 path: "/studies/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
                 entity: EntityKind::Study
             }
@@ -1545,11 +3019,12 @@ mod tests {
             .expect_method(Method::POST)
             .expect_path("/studies/foo/anonymize")
             .expect_json_body(&Anonymization {
-                replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
+                replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
                 keep: Some(vec!["Tag2".to_string(), "Tag3".to_string()]),
                 keep_private_tags: None,
                 dicom_version: None,
                 force: None,
+                ..Default::default()
             })
             .return_status(200)
             .return_body(
@@ -1570,11 +3045,12 @@ mod tests {
                 "studies",
                 "foo",
                 Some(Anonymization {
-                    replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
+                    replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
                     keep: Some(vec!["Tag2".to_string(), "Tag3".to_string()]),
                     keep_private_tags: None,
                     dicom_version: None,
                     force: None,
+                    ..Default::default()
                 }),
             )
             .unwrap();
@@ -1582,8 +3058,8 @@ mod tests {
         assert_eq!(
             resp,
             ModificationResult {
-                id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-                patient_id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
+                id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".into(),
+                patient_id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".into(),
                 path: "/studies/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
                 entity: EntityKind::Study,
             }
@@ -1,15 +1,18 @@
+use crate::client::Client;
+use crate::ids::{InstanceId, PatientId, SeriesId, StudyId};
 use crate::Error;
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::fmt;
 use std::str;
 
 /// Orthanc entity kinds (types).
 ///
 /// Orthanc operates with 4 entity kinds, which correspond to the ones, available in DICOM.
 /// In descending hierarchical order: Patient, Study, Series, Instance
-#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
 pub enum EntityKind {
     Patient,
     Study,
@@ -33,8 +36,26 @@ impl TryFrom<bytes::Bytes> for EntityKind {
     }
 }
 
+impl EntityKind {
+    /// The REST API path of the entities of this kind (e.g. `studies`)
+    pub(crate) fn path(&self) -> &'static str {
+        match self {
+            EntityKind::Patient => "patients",
+            EntityKind::Study => "studies",
+            EntityKind::Series => "series",
+            EntityKind::Instance => "instances",
+        }
+    }
+}
+
 /// A trait, that implements common methods for all entity kinds
 pub trait Entity: serde::de::DeserializeOwned {
+    /// Type of the ID of the entity (e.g. [`StudyId`] for [`Study`])
+    type Id: From<String> + fmt::Display;
+    /// Type of the IDs of the entity's children (e.g. [`SeriesId`] for [`Study`]). Unused for
+    /// [`Instance`], which does not have children
+    type ChildId: AsRef<str>;
+
     /// Entity kind
     fn kind() -> EntityKind;
 
@@ -53,7 +74,7 @@ pub trait Entity: serde::de::DeserializeOwned {
 
     /// The list of ID of the entity's children (studies for [`Patient`], series for [`Study`],
     /// instances for [`Series`])
-    fn children(&self) -> &[String] {
+    fn children(&self) -> &[Self::ChildId] {
         &[]
     }
 
@@ -97,19 +118,22 @@ pub trait Entity: serde::de::DeserializeOwned {
 #[serde(rename_all = "PascalCase")]
 pub struct Patient {
     #[serde(rename = "ID")]
-    pub id: String,
+    pub id: PatientId,
     pub is_stable: bool,
     #[serde(with = "datetime_format")]
     pub last_update: NaiveDateTime,
     pub main_dicom_tags: HashMap<String, String>,
-    pub studies: Vec<String>,
+    pub studies: Vec<StudyId>,
     #[serde(rename = "Type")]
     pub entity: EntityKind,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub anonymized_from: Option<String>,
+    pub anonymized_from: Option<PatientId>,
 }
 
 impl Entity for Patient {
+    type Id = PatientId;
+    type ChildId = StudyId;
+
     /// Returns the [`EntityKind::Patient`] variant
     fn kind() -> EntityKind {
         EntityKind::Patient
@@ -131,7 +155,7 @@ impl Entity for Patient {
     }
 
     /// Returns the list of IDs of all studies that belong to this patient
-    fn children(&self) -> &[String] {
+    fn children(&self) -> &[StudyId] {
         &self.studies
     }
 
@@ -141,26 +165,36 @@ impl Entity for Patient {
     }
 }
 
+impl Patient {
+    /// Get all studies of the patient from Orthanc
+    pub fn child_entities(&self, client: &Client) -> crate::Result<Vec<Study>> {
+        client.patient_studies(&self.id)
+    }
+}
+
 /// Study
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub struct Study {
     #[serde(rename = "ID")]
-    pub id: String,
+    pub id: StudyId,
     pub is_stable: bool,
     #[serde(with = "datetime_format")]
     pub last_update: NaiveDateTime,
     pub main_dicom_tags: HashMap<String, String>,
-    pub parent_patient: String,
+    pub parent_patient: PatientId,
     pub patient_main_dicom_tags: HashMap<String, String>,
-    pub series: Vec<String>,
+    pub series: Vec<SeriesId>,
     #[serde(rename = "Type")]
     pub entity: EntityKind,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub anonymized_from: Option<String>,
+    pub anonymized_from: Option<StudyId>,
 }
 
 impl Entity for Study {
+    type Id = StudyId;
+    type ChildId = SeriesId;
+
     /// Returns the [`EntityKind::Study`] variant
     fn kind() -> EntityKind {
         EntityKind::Study
@@ -190,7 +224,7 @@ impl Entity for Study {
     }
 
     /// Returns the list of IDs of all series that belong to this study
-    fn children(&self) -> &[String] {
+    fn children(&self) -> &[SeriesId] {
         &self.series
     }
 
@@ -205,27 +239,42 @@ impl Entity for Study {
     }
 }
 
+impl Study {
+    /// Get the patient of the study from Orthanc
+    pub fn parent(&self, client: &Client) -> crate::Result<Patient> {
+        client.patient(&self.parent_patient)
+    }
+
+    /// Get all series of the study from Orthanc
+    pub fn child_entities(&self, client: &Client) -> crate::Result<Vec<Series>> {
+        client.study_series(&self.id)
+    }
+}
+
 /// Series
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub struct Series {
     #[serde(rename = "ID")]
-    pub id: String,
+    pub id: SeriesId,
     pub status: String,
     pub is_stable: bool,
     #[serde(with = "datetime_format")]
     pub last_update: NaiveDateTime,
     pub main_dicom_tags: HashMap<String, String>,
-    pub parent_study: String,
+    pub parent_study: StudyId,
     pub expected_number_of_instances: Option<u32>,
-    pub instances: Vec<String>,
+    pub instances: Vec<InstanceId>,
     #[serde(rename = "Type")]
     pub entity: EntityKind,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub anonymized_from: Option<String>,
+    pub anonymized_from: Option<SeriesId>,
 }
 
 impl Entity for Series {
+    type Id = SeriesId;
+    type ChildId = InstanceId;
+
     fn kind() -> EntityKind {
         EntityKind::Series
     }
@@ -250,7 +299,7 @@ impl Entity for Series {
     }
 
     /// Returns the list of IDs of all instances that belong to this series
-    fn children(&self) -> &[String] {
+    fn children(&self) -> &[InstanceId] {
         &self.instances
     }
 
@@ -265,26 +314,41 @@ impl Entity for Series {
     }
 }
 
+impl Series {
+    /// Get the study of the series from Orthanc
+    pub fn parent(&self, client: &Client) -> crate::Result<Study> {
+        client.study(&self.parent_study)
+    }
+
+    /// Get all instances of the series from Orthanc
+    pub fn child_entities(&self, client: &Client) -> crate::Result<Vec<Instance>> {
+        client.series_instances(&self.id)
+    }
+}
+
 /// Instance
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub struct Instance {
     #[serde(rename = "ID")]
-    pub id: String,
+    pub id: InstanceId,
     pub main_dicom_tags: HashMap<String, String>,
-    pub parent_series: String,
+    pub parent_series: SeriesId,
     pub index_in_series: Option<u32>,
     pub file_uuid: String,
     pub file_size: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub modified_from: Option<String>,
+    pub modified_from: Option<InstanceId>,
     #[serde(rename = "Type")]
     pub entity: EntityKind,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub anonymized_from: Option<String>,
+    pub anonymized_from: Option<InstanceId>,
 }
 
 impl Entity for Instance {
+    type Id = InstanceId;
+    type ChildId = InstanceId;
+
     /// Returns the [`EntityKind::Instance`] variant
     fn kind() -> EntityKind {
         EntityKind::Instance
@@ -320,6 +384,13 @@ impl Entity for Instance {
     }
 }
 
+impl Instance {
+    /// Get the series of the instance from Orthanc
+    pub fn parent(&self, client: &Client) -> crate::Result<Series> {
+        client.series(&self.parent_series)
+    }
+}
+
 mod datetime_format {
     use chrono::NaiveDateTime;
     use serde::{self, Deserialize, Deserializer, Serializer};
@@ -373,18 +444,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_entity_kind_path() {
+        assert_eq!(EntityKind::Patient.path(), "patients");
+        assert_eq!(EntityKind::Study.path(), "studies");
+        assert_eq!(EntityKind::Series.path(), "series");
+        assert_eq!(EntityKind::Instance.path(), "instances");
+    }
+
     #[test]
     fn test_entity_trait_patient() {
         assert_eq!(Patient::kind(), EntityKind::Patient);
 
         let patient = Patient {
-            id: "f88cbd3f-a00dfc59-9ca1ac2d-7ce9851a-40e5b493".to_string(),
+            id: "f88cbd3f-a00dfc59-9ca1ac2d-7ce9851a-40e5b493".into(),
             is_stable: true,
             last_update: NaiveDate::from_ymd(2020, 1, 1).and_hms(15, 46, 17),
             main_dicom_tags: hashmap! {
                 "PatientName".to_string() => "Rick Sanchez".to_string(),
             },
-            studies: ["e8cafcbe-caf08c39-6e205f15-18554bb8-b3f9ef04".to_string()].to_vec(),
+            studies: ["e8cafcbe-caf08c39-6e205f15-18554bb8-b3f9ef04".into()].to_vec(),
             entity: EntityKind::Patient,
             anonymized_from: None,
         };
@@ -409,19 +488,19 @@ mod tests {
         assert_eq!(Study::kind(), EntityKind::Study);
 
         let study = Study {
-            id: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".to_string(),
+            id: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".into(),
             is_stable: true,
             last_update: NaiveDate::from_ymd(2020, 8, 30).and_hms(19, 11, 09),
             main_dicom_tags: hashmap! {
                 "AccessionNumber".to_string() => "foobar".to_string(),
             },
-            parent_patient: "7e43f8d3-e50280e6-470079e9-02241af1-d286bdbe".to_string(),
+            parent_patient: "7e43f8d3-e50280e6-470079e9-02241af1-d286bdbe".into(),
             patient_main_dicom_tags: hashmap! {
                 "PatientName".to_string() => "Rick Sanchez".to_string(),
             },
             series: [
-                "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".to_string(),
-                "2ab7dbe7-f1a18a78-86145443-18a8ff93-0b65f2b2".to_string(),
+                "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".into(),
+                "2ab7dbe7-f1a18a78-86145443-18a8ff93-0b65f2b2".into(),
             ]
             .to_vec(),
             entity: EntityKind::Study,
@@ -438,7 +517,7 @@ mod tests {
         assert_eq!(
             study.children(),
             [
-                "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".to_string(),
+                "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".into(),
                 "2ab7dbe7-f1a18a78-86145443-18a8ff93-0b65f2b2".to_string()
             ]
         );
@@ -455,18 +534,18 @@ mod tests {
         assert_eq!(Series::kind(), EntityKind::Series);
 
         let series = Series {
-            id: "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".to_string(),
+            id: "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".into(),
             status: "Unknown".to_string(),
             is_stable: true,
             last_update: NaiveDate::from_ymd(2020, 8, 30).and_hms(19, 11, 09),
             main_dicom_tags: hashmap! {
                 "BodyPartExamined".to_string() => "ABDOMEN".to_string(),
             },
-            parent_study: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".to_string(),
+            parent_study: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".into(),
             expected_number_of_instances: Some(17),
             instances: [
-                "556530b5-de7c487b-110b9d0e-12cfdbb9-f06b546e".to_string(),
-                "c46605db-836489fa-cb55fbbc-13c8a913-b0bad6ac".to_string(),
+                "556530b5-de7c487b-110b9d0e-12cfdbb9-f06b546e".into(),
+                "c46605db-836489fa-cb55fbbc-13c8a913-b0bad6ac".into(),
             ]
             .to_vec(),
             entity: EntityKind::Series,
@@ -482,7 +561,7 @@ mod tests {
         assert_eq!(
             series.children(),
             [
-                "556530b5-de7c487b-110b9d0e-12cfdbb9-f06b546e".to_string(),
+                "556530b5-de7c487b-110b9d0e-12cfdbb9-f06b546e".into(),
                 "c46605db-836489fa-cb55fbbc-13c8a913-b0bad6ac".to_string()
             ]
         );
@@ -499,15 +578,15 @@ mod tests {
         assert_eq!(Instance::kind(), EntityKind::Instance);
 
         let instance = Instance {
-            id: "29fa4d9d-51a69d1d-70e2b29a-fd824316-50850d0c".to_string(),
+            id: "29fa4d9d-51a69d1d-70e2b29a-fd824316-50850d0c".into(),
             main_dicom_tags: hashmap! {
                 "SOPInstanceUID".to_string() => "1.2.3.4.5.6789".to_string(),
             },
-            parent_series: "82081568-b6f8f4e6-ced76876-6504da25-ed0dfe03".to_string(),
+            parent_series: "82081568-b6f8f4e6-ced76876-6504da25-ed0dfe03".into(),
             index_in_series: Some(13),
             file_uuid: "d8c5eff3-986c-4fe4-b06e-7e52b2a4238e".to_string(),
             file_size: 139402,
-            modified_from: Some("22c54cb6-28302a69-3ff454a3-676b98f4-b84cd80a".to_string()),
+            modified_from: Some("22c54cb6-28302a69-3ff454a3-676b98f4-b84cd80a".into()),
             entity: EntityKind::Instance,
             anonymized_from: None,
         };
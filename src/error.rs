@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::io;
 use std::str;
 
 /// Structure of Orthanc's API error
@@ -16,12 +17,23 @@ pub struct ApiError {
     pub orthanc_error: String,
 }
 
+/// MD5 digests of data, that failed an integrity check
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IntegrityMismatch {
+    /// The digest reported by Orthanc
+    pub expected: String,
+    /// The digest of the received data
+    pub actual: String,
+}
+
 /// Error type
 #[derive(Debug, Eq, PartialEq)]
 pub struct Error {
     pub message: String,
     // TODO: This is pretty ugly
     pub details: Option<ApiError>,
+    /// Set, if the error is caused by a failed integrity check of downloaded data
+    pub integrity: Option<IntegrityMismatch>,
 }
 
 impl fmt::Display for Error {
@@ -35,8 +47,29 @@ impl Error {
         Error {
             message: msg.to_string(),
             details: api_error,
+            integrity: None,
+        }
+    }
+
+    pub(crate) fn integrity(expected_md5: &str, actual_md5: &str) -> Error {
+        Error {
+            message: format!(
+                "Integrity check failed: expected MD5 {}, got {}",
+                expected_md5, actual_md5
+            ),
+            details: None,
+            integrity: Some(IntegrityMismatch {
+                expected: expected_md5.to_string(),
+                actual: actual_md5.to_string(),
+            }),
         }
     }
+
+    /// Whether the error is caused by a mismatch between the MD5 of the downloaded data and the
+    /// MD5 reported by Orthanc
+    pub fn is_integrity_error(&self) -> bool {
+        self.integrity.is_some()
+    }
 }
 
 impl From<reqwest::Error> for Error {
@@ -57,6 +90,18 @@ impl From<str::Utf8Error> for Error {
     }
 }
 
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::new(&e.to_string(), None)
+    }
+}
+
+impl From<zip::result::ZipError> for Error {
+    fn from(e: zip::result::ZipError) -> Self {
+        Error::new(&e.to_string(), None)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -80,6 +125,7 @@ mod tests {
                 orthanc_status: 15,
                 orthanc_error: "Bad file format".to_string(),
             }),
+            integrity: None,
         };
 
         // TODO: Any way to make the formatting nicer?
@@ -100,6 +146,25 @@ mod tests {
         assert_eq!(format!("{}", error), expected_error_str);
     }
 
+    #[test]
+    fn test_integrity_error() {
+        let error = Error::integrity("foo", "bar");
+        assert_eq!(
+            error.message,
+            "Integrity check failed: expected MD5 foo, got bar"
+        );
+        assert_eq!(
+            error.integrity,
+            Some(IntegrityMismatch {
+                expected: "foo".to_string(),
+                actual: "bar".to_string(),
+            })
+        );
+        assert!(error.is_integrity_error());
+        assert!(!Error::new("foo", None).is_integrity_error());
+        assert!(!Error::new("Integrity check failed: foo", None).is_integrity_error());
+    }
+
     #[test]
     fn test_error_from_serde_json() {
         let serde_error: Result<Value, SerdeError> = serde_json::from_str("foobar");
@@ -108,6 +173,7 @@ mod tests {
             Error {
                 message: "expected ident at line 1 column 2".to_string(),
                 details: None,
+                integrity: None,
             },
         )
     }
@@ -128,10 +194,24 @@ mod tests {
             Error {
                 message: expected_err.to_string(),
                 details: None,
+                integrity: None,
             },
         );
     }
 
+    #[test]
+    fn test_error_from_io() {
+        let io_error = io::Error::new(io::ErrorKind::NotFound, "foo not found");
+        assert_eq!(
+            Error::from(io_error),
+            Error {
+                message: "foo not found".to_string(),
+                details: None,
+                integrity: None,
+            }
+        );
+    }
+
     #[test]
     fn test_error_from_utf8() {
         let sparkle_heart = vec![0, 159, 146, 150];
@@ -142,6 +222,7 @@ mod tests {
             Error {
                 message: "invalid utf-8 sequence of 1 bytes from index 1".to_string(),
                 details: None,
+                integrity: None,
             }
         );
     }
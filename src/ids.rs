@@ -0,0 +1,234 @@
+//! Computation of Orthanc entity IDs from DICOM identifiers
+//!
+//! Orthanc derives the ID of an entity from the DICOM identifiers of the entity and its ancestors,
+//! so the IDs can be computed before the data is uploaded:
+//!
+//! ```
+//! let id = ids::study_id("patient_2", "1.3.46.670589.11.1.5.0.6560.2011072814060507000");
+//! let study = client.study(&id).unwrap();
+//! ```
+//!
+//! The identifiers are joined with `|` and hashed with SHA-1. The hex digest is split into 5
+//! groups of 8 characters, separated with `-`.
+//!
+//! The module also provides a newtype per entity kind ([`PatientId`], [`StudyId`], [`SeriesId`],
+//! [`InstanceId`]), so that an ID of one entity kind can not be accidentally passed where an ID
+//! of another one is expected. The newtypes can be created from a [`String`] or a [`&str`], and
+//! dereference to [`str`].
+use serde::{Deserialize, Serialize};
+use sha1_smol::Sha1;
+use std::fmt;
+use std::ops::Deref;
+
+macro_rules! entity_id {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, Hash, PartialOrd, Ord)]
+        #[serde(transparent)]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(id: String) -> Self {
+                $name(id)
+            }
+        }
+
+        impl From<&String> for $name {
+            fn from(id: &String) -> Self {
+                $name(id.clone())
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(id: &str) -> Self {
+                $name(id.to_string())
+            }
+        }
+
+        impl From<&$name> for $name {
+            fn from(id: &$name) -> Self {
+                id.clone()
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl Deref for $name {
+            type Target = str;
+
+            fn deref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialEq<String> for $name {
+            fn eq(&self, other: &String) -> bool {
+                &self.0 == other
+            }
+        }
+    };
+}
+
+entity_id!(
+    /// Orthanc ID of a patient
+    PatientId
+);
+entity_id!(
+    /// Orthanc ID of a study
+    StudyId
+);
+entity_id!(
+    /// Orthanc ID of a series
+    SeriesId
+);
+entity_id!(
+    /// Orthanc ID of an instance
+    InstanceId
+);
+
+/// Compute the Orthanc ID of a patient from its `PatientID`
+pub fn patient_id(patient_id: &str) -> PatientId {
+    PatientId(hash(&[patient_id]))
+}
+
+/// Compute the Orthanc ID of a study from its `PatientID` and `StudyInstanceUID`
+pub fn study_id(patient_id: &str, study_instance_uid: &str) -> StudyId {
+    StudyId(hash(&[patient_id, study_instance_uid]))
+}
+
+/// Compute the Orthanc ID of a series from its `PatientID`, `StudyInstanceUID` and
+/// `SeriesInstanceUID`
+pub fn series_id(
+    patient_id: &str,
+    study_instance_uid: &str,
+    series_instance_uid: &str,
+) -> SeriesId {
+    SeriesId(hash(&[patient_id, study_instance_uid, series_instance_uid]))
+}
+
+/// Compute the Orthanc ID of an instance from its `PatientID`, `StudyInstanceUID`,
+/// `SeriesInstanceUID` and `SOPInstanceUID`
+pub fn instance_id(
+    patient_id: &str,
+    study_instance_uid: &str,
+    series_instance_uid: &str,
+    sop_instance_uid: &str,
+) -> InstanceId {
+    InstanceId(hash(&[
+        patient_id,
+        study_instance_uid,
+        series_instance_uid,
+        sop_instance_uid,
+    ]))
+}
+
+fn hash(identifiers: &[&str]) -> String {
+    // Orthanc strips the leading and trailing spaces of DICOM values
+    let input: Vec<&str> = identifiers.iter().map(|i| i.trim()).collect();
+    let digest = Sha1::from(input.join("|")).digest().to_string();
+    let groups: Vec<&str> = (0..5).map(|i| &digest[i * 8..(i + 1) * 8]).collect();
+    groups.join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATIENT_ID: &str = "patient_2";
+    const STUDY_INSTANCE_UID: &str = "1.3.46.670589.11.1.5.0.6560.2011072814060507000";
+    const SERIES_INSTANCE_UID: &str = "1.3.46.670589.11.1.5.0.3724.2011072815265926000";
+    const SOP_INSTANCE_UID: &str = "1.3.46.670589.11.1.5.0.3724.2011072815265975004";
+
+    #[test]
+    fn test_patient_id() {
+        assert_eq!(
+            patient_id(PATIENT_ID),
+            "3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c"
+        );
+        assert_eq!(
+            patient_id(" patient_2 "),
+            "3693b9d5-8b0e2a80-2cf45dda-d19e7c22-8749103c"
+        );
+    }
+
+    #[test]
+    fn test_study_id() {
+        assert_eq!(
+            study_id(PATIENT_ID, STUDY_INSTANCE_UID),
+            "ab7a6e26-18072a37-5f2a2210-8a7f0823-f2fa9119"
+        );
+    }
+
+    #[test]
+    fn test_series_id() {
+        assert_eq!(
+            series_id(PATIENT_ID, STUDY_INSTANCE_UID, SERIES_INSTANCE_UID),
+            "dc0e4c94-39f97dc4-b2c25ae2-a423cb85-d363880c"
+        );
+    }
+
+    #[test]
+    fn test_instance_id() {
+        assert_eq!(
+            instance_id(
+                PATIENT_ID,
+                STUDY_INSTANCE_UID,
+                SERIES_INSTANCE_UID,
+                SOP_INSTANCE_UID
+            ),
+            "c0680933-cc6b1e9d-f1bbb796-f155e4dc-3f321656"
+        );
+    }
+
+    #[test]
+    fn test_entity_id_conversions() {
+        let id = StudyId::from("foo");
+        assert_eq!(id, "foo");
+        assert_eq!(id, StudyId::from("foo".to_string()));
+        assert_eq!(id.as_str(), "foo");
+        assert_eq!(id.len(), 3);
+        assert_eq!(format!("{}", id), "foo");
+        assert_eq!(String::from(id), "foo".to_string());
+    }
+
+    #[test]
+    fn test_entity_id_serde() {
+        let id: SeriesId = serde_json::from_str(r#""foo""#).unwrap();
+        assert_eq!(id, SeriesId::from("foo"));
+        assert_eq!(serde_json::to_string(&id).unwrap(), r#""foo""#);
+    }
+}
@@ -0,0 +1,224 @@
+//! Importing directories of DICOM files into Orthanc
+//!
+//! See [`Client::import_directory`](crate::Client::import_directory)
+use crate::utils::is_dicom;
+use crate::{Error, Result};
+use std::fs;
+use std::io;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// A file that could not be imported
+#[derive(Debug, Eq, PartialEq)]
+pub struct ImportFailure {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+/// Report of a directory import
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct ImportReport {
+    /// Files that have been stored
+    pub success: Vec<PathBuf>,
+    /// Files that had already been stored before
+    pub already_stored: Vec<PathBuf>,
+    /// Files that could not be stored, along with the reasons
+    pub failures: Vec<ImportFailure>,
+    /// Files that have been skipped, because they are not DICOM files
+    pub skipped: Vec<PathBuf>,
+    /// Total size of the files, sent to Orthanc, in bytes
+    pub total_bytes: u64,
+}
+
+/// Files found in a directory, sorted by path
+#[derive(Debug, Default, Eq, PartialEq)]
+pub(crate) struct FoundFiles {
+    /// Files that start with a DICOM preamble
+    pub dicom: Vec<PathBuf>,
+    /// Files that do not start with a DICOM preamble
+    pub other: Vec<PathBuf>,
+    /// Files and subdirectories that could not be read
+    pub failures: Vec<ImportFailure>,
+}
+
+/// Recursively list all files in `dir`, splitting them into DICOM files and others
+///
+/// Only fails if `dir` itself can not be read. Files and subdirectories, that can not be read,
+/// are recorded in [`FoundFiles::failures`]. Symbolic links to files are followed, symbolic links
+/// to directories are not, so that a link loop can not make the traversal recurse endlessly.
+pub(crate) fn find_dicom_files(dir: &Path) -> Result<FoundFiles> {
+    let mut files = vec![];
+    let mut found = FoundFiles::default();
+    list_files(dir, fs::read_dir(dir)?, &mut files, &mut found.failures);
+    files.sort();
+
+    for file in files {
+        match has_dicom_preamble(&file) {
+            Ok(true) => found.dicom.push(file),
+            Ok(false) => found.other.push(file),
+            Err(e) => found.failures.push(failure(file, &e)),
+        }
+    }
+    found.failures.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(found)
+}
+
+fn list_files(
+    dir: &Path,
+    entries: fs::ReadDir,
+    files: &mut Vec<PathBuf>,
+    failures: &mut Vec<ImportFailure>,
+) {
+    for entry in entries {
+        let entry = match entry {
+            Ok(e) => e,
+            Err(e) => {
+                failures.push(failure(dir.to_path_buf(), &e));
+                continue;
+            }
+        };
+        let path = entry.path();
+        // `DirEntry::file_type` does not follow symbolic links
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => match fs::read_dir(&path) {
+                Ok(entries) => list_files(&path, entries, files, failures),
+                Err(e) => failures.push(failure(path, &e)),
+            },
+            Ok(t) if t.is_symlink() => match fs::metadata(&path) {
+                Ok(m) if m.is_file() => files.push(path),
+                Ok(_) => (),
+                Err(e) => failures.push(failure(path, &e)),
+            },
+            Ok(t) if t.is_file() => files.push(path),
+            Ok(_) => (),
+            Err(e) => failures.push(failure(path, &e)),
+        }
+    }
+}
+
+fn failure(path: PathBuf, error: &io::Error) -> ImportFailure {
+    ImportFailure {
+        path,
+        reason: error.to_string(),
+    }
+}
+
+fn has_dicom_preamble(path: &Path) -> io::Result<bool> {
+    let mut preamble = Vec::with_capacity(132);
+    fs::File::open(path)?.take(132).read_to_end(&mut preamble)?;
+    Ok(is_dicom(&preamble))
+}
+
+/// Human-readable reason of an import failure
+pub(crate) fn failure_reason(error: &Error) -> String {
+    match &error.details {
+        Some(d) => format!(
+            "{}: {}",
+            error.message,
+            d.details.as_ref().unwrap_or(&d.message)
+        ),
+        None => error.message.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ApiError;
+
+    #[test]
+    fn test_find_dicom_files() {
+        let dir = std::env::temp_dir().join("orthanc_test_find_dicom_files");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("foo/bar")).unwrap();
+        let mut dicom = vec![0; 128];
+        dicom.extend_from_slice(b"DICMfoo");
+        fs::write(dir.join("foo/bar/IM1"), &dicom).unwrap();
+        fs::write(dir.join("foo/IM0"), &dicom).unwrap();
+        fs::write(dir.join("foo/README"), "foobar").unwrap();
+        fs::write(dir.join("empty"), "").unwrap();
+
+        assert_eq!(
+            find_dicom_files(&dir).unwrap(),
+            FoundFiles {
+                dicom: vec![dir.join("foo/IM0"), dir.join("foo/bar/IM1")],
+                other: vec![dir.join("empty"), dir.join("foo/README")],
+                failures: vec![],
+            }
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_dicom_files_symlinks_and_unreadable() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let dir = std::env::temp_dir().join("orthanc_test_find_dicom_files_symlinks");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("foo")).unwrap();
+        fs::create_dir_all(dir.join("locked")).unwrap();
+        fs::write(dir.join("foo/README"), "foobar").unwrap();
+        fs::write(dir.join("locked/README"), "foobar").unwrap();
+        // A link loop, a link to a file and a dangling link
+        symlink(&dir, dir.join("foo/loop")).unwrap();
+        symlink(dir.join("foo/README"), dir.join("link")).unwrap();
+        symlink(dir.join("missing"), dir.join("dangling")).unwrap();
+        fs::set_permissions(dir.join("locked"), fs::Permissions::from_mode(0o000)).unwrap();
+        // Permissions are not enforced for a privileged user
+        let locked = fs::read_dir(dir.join("locked")).is_err();
+
+        let found = find_dicom_files(&dir).unwrap();
+        fs::set_permissions(dir.join("locked"), fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(found.dicom, Vec::<PathBuf>::new());
+        let mut other = vec![dir.join("foo/README"), dir.join("link")];
+        let mut failed = vec![dir.join("dangling")];
+        if locked {
+            failed.push(dir.join("locked"));
+        } else {
+            other.push(dir.join("locked/README"));
+        }
+        assert_eq!(found.other, other);
+        assert_eq!(
+            found.failures.iter().map(|f| &f.path).collect::<Vec<_>>(),
+            failed.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            found.failures[0].reason,
+            "No such file or directory (os error 2)"
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_find_dicom_files_error() {
+        assert_eq!(
+            find_dicom_files(Path::new("/foo/bar/baz")).unwrap_err(),
+            Error::new("No such file or directory (os error 2)", None)
+        );
+    }
+
+    #[test]
+    fn test_failure_reason() {
+        assert_eq!(failure_reason(&Error::new("foo", None)), "foo");
+        assert_eq!(
+            failure_reason(&Error::new(
+                "API error: 400 Bad Request",
+                Some(ApiError {
+                    method: "POST".to_string(),
+                    uri: "/instances".to_string(),
+                    message: "Bad file format".to_string(),
+                    details: Some(
+                        "Cannot parse an invalid DICOM file (size: 12 bytes)".to_string()
+                    ),
+                    http_status: 400,
+                    http_error: "Bad Request".to_string(),
+                    orthanc_status: 15,
+                    orthanc_error: "Bad file format".to_string(),
+                })
+            )),
+            "API error: 400 Bad Request: Cannot parse an invalid DICOM file (size: 12 bytes)"
+        );
+    }
+}
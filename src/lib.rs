@@ -55,6 +55,14 @@
 //! client.study_dicom(study_id, &mut file).unwrap();
 //! ```
 //!
+//! Or extract it into a directory tree, named after the DICOM tags of its instances:
+//!
+//! ```rust
+//! use orthanc::archive::PathTemplate;
+//! let template = PathTemplate::new("{PatientID}/{StudyDate}/{SeriesNumber}/{InstanceNumber}.dcm");
+//! client.extract_study(study_id, "/tmp/study", Some(&template)).unwrap();
+//! ```
+//!
 //! Even though the operation is not very efficient, Orthanc allows uploading DICOM files over
 //! REST API:
 //!
@@ -62,15 +70,43 @@
 //! let data = fs::read("/tmp/instance.dcm").unwrap();
 //! client.upload(&data).unwrap();
 //! ```
+//!
+//! Large files are better streamed from disk:
+//!
+//! ```rust
+//! client.upload_file("/tmp/instance.dcm").unwrap();
+//! ```
+//!
+//! Whole directory trees can be imported in parallel:
+//!
+//! ```rust
+//! let report = client.import_directory("/mnt/nas/dicom", 4).unwrap();
+//! println!("{} stored, {} failed", report.success.len(), report.failures.len());
+//! ```
+//!
+//! Progress of uploads and downloads can be observed by attaching a callback to the client:
+//!
+//! ```rust
+//! let client = Client::new("http://localhost:8042")
+//!     .progress(|t: &Transfer| println!("{}: {} of {:?} bytes", t.path, t.transferred, t.total));
+//! ```
 
 pub use client::Client;
-pub use error::{ApiError, Error};
+pub use error::{ApiError, Error, IntegrityMismatch};
 use std::result;
 
+pub mod archive;
 pub mod client;
 pub mod entity;
 pub mod error;
+pub mod ids;
+pub mod import;
 pub mod models;
+pub mod numpy;
+pub mod phi;
+pub mod progress;
+pub mod pseudonymization;
 mod utils;
+pub mod walker;
 
 type Result<T> = result::Result<T, Error>;
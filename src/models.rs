@@ -1,7 +1,11 @@
 use crate::entity::EntityKind;
+use crate::ids::{InstanceId, PatientId, SeriesId, StudyId};
+use crate::{Error, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use serde_with::skip_serializing_none;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 /// System
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
@@ -64,33 +68,504 @@ pub struct Peer {
 }
 
 /// Anonymization request body
+///
+/// Values in `replace` are usually strings, but can also be arrays of objects to replace
+/// sequences, like in a [`Modification`].
+///
+/// Can be created directly or with an [`AnonymizationBuilder`], which supports named
+/// [profiles](AnonymizationProfile) and validates the combination of options.
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq)]
 pub struct Anonymization {
     #[serde(rename(serialize = "Replace"))]
-    pub replace: Option<HashMap<String, String>>,
+    pub replace: Option<HashMap<String, Value>>,
     #[serde(rename(serialize = "Keep"))]
     pub keep: Option<Vec<String>>,
+    #[serde(rename(serialize = "Remove"))]
+    pub remove: Option<Vec<String>>,
     #[serde(rename(serialize = "KeepPrivateTags"))]
     pub keep_private_tags: Option<bool>,
+    /// Keep the original resources (only relevant for studies, series and patients)
+    #[serde(rename(serialize = "KeepSource"))]
+    pub keep_source: Option<bool>,
+    /// Private creator to use for the private tags in `replace`
+    #[serde(rename(serialize = "PrivateCreator"))]
+    pub private_creator: Option<String>,
+    /// Transfer syntax to transcode the anonymized DICOM files to
+    #[serde(rename(serialize = "Transcode"))]
+    pub transcode: Option<TransferSyntax>,
+    #[serde(rename(serialize = "Priority"))]
+    pub priority: Option<i32>,
+    /// Set by the client, depending on whether a synchronous or an asynchronous method is called
+    #[serde(rename(serialize = "Asynchronous"))]
+    pub asynchronous: Option<bool>,
+    /// Version of the DICOM standard, the de-identification profile is taken from
     #[serde(rename(serialize = "DicomVersion"))]
     pub dicom_version: Option<String>,
     #[serde(rename(serialize = "Force"))]
     pub force: Option<bool>,
 }
 
+/// Named anonymization profile
+///
+/// Orthanc always applies the Basic Application Level Confidentiality Profile of DICOM PS3.15.
+/// The other profiles correspond to the options of PS3.15 that retain some of the information,
+/// which the basic profile would otherwise remove.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum AnonymizationProfile {
+    /// Basic Application Level Confidentiality Profile
+    Basic,
+    /// Retain Longitudinal Temporal Information with Full Dates Option
+    RetainDates,
+    /// Retain Device Identity Option
+    RetainDeviceIdentity,
+    /// Retain Patient Characteristics Option along with full dates. The patient must be given a
+    /// pseudonym by replacing `PatientID` and `PatientName`
+    ResearchPseudonymization,
+}
+
+const DATE_TAGS: [&str; 12] = [
+    "StudyDate",
+    "StudyTime",
+    "SeriesDate",
+    "SeriesTime",
+    "AcquisitionDate",
+    "AcquisitionTime",
+    "AcquisitionDateTime",
+    "ContentDate",
+    "ContentTime",
+    "InstanceCreationDate",
+    "InstanceCreationTime",
+    "PerformedProcedureStepStartDate",
+];
+
+const DEVICE_TAGS: [&str; 8] = [
+    "StationName",
+    "DeviceSerialNumber",
+    "DeviceUID",
+    "DetectorID",
+    "GantryID",
+    "PlateID",
+    "CassetteID",
+    "GeneratorID",
+];
+
+const PATIENT_CHARACTERISTICS_TAGS: [&str; 6] = [
+    "PatientSex",
+    "PatientAge",
+    "PatientSize",
+    "PatientWeight",
+    "EthnicGroup",
+    "SmokingStatus",
+];
+
+/// UIDs, that Orthanc only allows to be kept or replaced during anonymization with `Force`
+const UID_TAGS: [&str; 3] = ["StudyInstanceUID", "SeriesInstanceUID", "SOPInstanceUID"];
+
+impl AnonymizationProfile {
+    /// Tags, that are kept by the profile
+    pub fn keep(&self) -> Vec<&'static str> {
+        match self {
+            AnonymizationProfile::Basic => vec![],
+            AnonymizationProfile::RetainDates => DATE_TAGS.to_vec(),
+            AnonymizationProfile::RetainDeviceIdentity => DEVICE_TAGS.to_vec(),
+            AnonymizationProfile::ResearchPseudonymization => DATE_TAGS
+                .iter()
+                .chain(PATIENT_CHARACTERISTICS_TAGS.iter())
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// Tags, that are removed by the profile on top of the basic profile
+    pub fn remove(&self) -> Vec<&'static str> {
+        match self {
+            AnonymizationProfile::ResearchPseudonymization => {
+                vec!["PatientBirthDate", "OtherPatientIDs", "OtherPatientNames"]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Tags, that must be replaced explicitly when the profile is used
+    fn required_replacements(&self) -> Vec<&'static str> {
+        match self {
+            AnonymizationProfile::ResearchPseudonymization => {
+                vec!["PatientID", "PatientName"]
+            }
+            _ => vec![],
+        }
+    }
+}
+
+/// Builder of an [`Anonymization`]
+///
+/// Tags, that are explicitly replaced, kept or removed, take precedence over the ones of the
+/// profiles.
+///
+/// ```
+/// let anonymization = AnonymizationBuilder::new()
+///     .profile(AnonymizationProfile::ResearchPseudonymization)
+///     .replace("PatientID", "SUBJ-0042")
+///     .replace("PatientName", "SUBJ-0042")
+///     .keep_source(false)
+///     .build()
+///     .unwrap();
+/// client.anonymize_study("9357491d-427a6c94-4080b6c8-1997f4aa-af658240", Some(anonymization)).unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct AnonymizationBuilder {
+    anonymization: Anonymization,
+    profiles: Vec<AnonymizationProfile>,
+}
+
+impl AnonymizationBuilder {
+    /// Creates a new builder of an anonymization with the basic profile
+    pub fn new() -> AnonymizationBuilder {
+        AnonymizationBuilder::default()
+    }
+
+    /// Apply a profile. Several profiles can be combined
+    pub fn profile(mut self, profile: AnonymizationProfile) -> Self {
+        if !self.profiles.contains(&profile) {
+            self.profiles.push(profile);
+        }
+        self
+    }
+
+    /// Replace the value of a tag. `value` can be a string or, for sequences, a JSON array
+    pub fn replace(mut self, tag: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.anonymization
+            .replace
+            .get_or_insert_with(HashMap::new)
+            .insert(tag.into(), value.into());
+        self
+    }
+
+    /// Keep the original value of a tag, that would otherwise be removed or replaced by the
+    /// profiles
+    pub fn keep(mut self, tag: impl Into<String>) -> Self {
+        self.anonymization
+            .keep
+            .get_or_insert_with(Vec::new)
+            .push(tag.into());
+        self
+    }
+
+    /// Remove a tag, that would otherwise be kept
+    pub fn remove(mut self, tag: impl Into<String>) -> Self {
+        self.anonymization
+            .remove
+            .get_or_insert_with(Vec::new)
+            .push(tag.into());
+        self
+    }
+
+    /// Keep the private tags, which are removed by default
+    pub fn keep_private_tags(mut self, keep_private_tags: bool) -> Self {
+        self.anonymization.keep_private_tags = Some(keep_private_tags);
+        self
+    }
+
+    /// Keep the original resources after the anonymization (only relevant for patients, studies
+    /// and series). By default, Orthanc keeps them
+    pub fn keep_source(mut self, keep_source: bool) -> Self {
+        self.anonymization.keep_source = Some(keep_source);
+        self
+    }
+
+    /// Private creator to use for the private tags, that are replaced
+    pub fn private_creator(mut self, private_creator: impl Into<String>) -> Self {
+        self.anonymization.private_creator = Some(private_creator.into());
+        self
+    }
+
+    /// Transcode the anonymized DICOM files to the given transfer syntax
+    pub fn transcode(mut self, transfer_syntax: impl Into<TransferSyntax>) -> Self {
+        self.anonymization.transcode = Some(transfer_syntax.into());
+        self
+    }
+
+    /// Priority of the job, that performs an asynchronous anonymization
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.anonymization.priority = Some(priority);
+        self
+    }
+
+    /// Version of the DICOM standard, the de-identification profile is taken from
+    pub fn dicom_version(mut self, dicom_version: impl Into<String>) -> Self {
+        self.anonymization.dicom_version = Some(dicom_version.into());
+        self
+    }
+
+    /// Allow keeping or replacing the UIDs, which Orthanc otherwise refuses
+    pub fn force(mut self, force: bool) -> Self {
+        self.anonymization.force = Some(force);
+        self
+    }
+
+    /// Validate the options, expand the profiles and build the [`Anonymization`]
+    ///
+    /// Fails if a tag is explicitly kept and replaced or removed at the same time, if a tag is
+    /// both replaced and removed, if a UID is kept or replaced without `Force`, or if a tag,
+    /// required by a profile (e.g. `PatientID` for
+    /// [`ResearchPseudonymization`](AnonymizationProfile::ResearchPseudonymization)), is not
+    /// replaced.
+    pub fn build(self) -> Result<Anonymization> {
+        let mut a = self.anonymization;
+        let replaced: Vec<String> =
+            a.replace.iter().flat_map(|r| r.keys().cloned()).collect();
+        let kept: Vec<String> = a.keep.iter().flatten().cloned().collect();
+        let removed: Vec<String> = a.remove.iter().flatten().cloned().collect();
+
+        for tag in &kept {
+            if replaced.contains(tag) || removed.contains(tag) {
+                return Err(Error::new(
+                    &format!("Tag {} is both kept and modified", tag),
+                    None,
+                ));
+            }
+        }
+        for tag in &removed {
+            if replaced.contains(tag) {
+                return Err(Error::new(
+                    &format!("Tag {} is both replaced and removed", tag),
+                    None,
+                ));
+            }
+        }
+        if a.force != Some(true) {
+            if let Some(tag) = kept
+                .iter()
+                .chain(replaced.iter())
+                .find(|t| UID_TAGS.contains(&t.as_str()))
+            {
+                return Err(Error::new(
+                    &format!("Keeping or replacing {} requires Force", tag),
+                    None,
+                ));
+            }
+        }
+
+        let explicit = |tag: &str| {
+            kept.iter()
+                .chain(replaced.iter())
+                .chain(removed.iter())
+                .any(|t| t == tag)
+        };
+        for profile in &self.profiles {
+            for tag in profile.required_replacements() {
+                if !replaced.iter().any(|t| t == tag) {
+                    return Err(Error::new(
+                        &format!("Profile {:?} requires {} to be replaced", profile, tag),
+                        None,
+                    ));
+                }
+            }
+            for tag in profile.keep() {
+                let keep = a.keep.get_or_insert_with(Vec::new);
+                if !explicit(tag) && !keep.iter().any(|t| t == tag) {
+                    keep.push(tag.to_string());
+                }
+            }
+            for tag in profile.remove() {
+                let remove = a.remove.get_or_insert_with(Vec::new);
+                if !explicit(tag) && !remove.iter().any(|t| t == tag) {
+                    remove.push(tag.to_string());
+                }
+            }
+        }
+        Ok(a)
+    }
+}
+
 /// Modification request body
+///
+/// Values in `replace` are usually strings, but can also be arrays of objects to replace
+/// sequences, e.g. `json!([{"CodeValue": "12345", "CodingSchemeDesignator": "99ORTHANC"}])`.
+///
+/// Can be created directly or with a [`ModificationBuilder`], which validates the combination of
+/// options.
 #[skip_serializing_none]
-#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Default, Eq, PartialEq)]
 pub struct Modification {
     #[serde(rename(serialize = "Replace"))]
-    pub replace: Option<HashMap<String, String>>,
+    pub replace: Option<HashMap<String, Value>>,
     #[serde(rename(serialize = "Remove"))]
     pub remove: Option<Vec<String>>,
+    #[serde(rename(serialize = "Keep"))]
+    pub keep: Option<Vec<String>>,
+    /// Keep the original resources (only relevant for studies, series and patients)
+    #[serde(rename(serialize = "KeepSource"))]
+    pub keep_source: Option<bool>,
+    #[serde(rename(serialize = "RemovePrivateTags"))]
+    pub remove_private_tags: Option<bool>,
+    /// Private creator to use for the private tags in `replace`
+    #[serde(rename(serialize = "PrivateCreator"))]
+    pub private_creator: Option<String>,
+    /// Transfer syntax to transcode the modified DICOM files to
+    #[serde(rename(serialize = "Transcode"))]
+    pub transcode: Option<TransferSyntax>,
+    /// Ignore errors in the individual instances
+    #[serde(rename(serialize = "Permissive"))]
+    pub permissive: Option<bool>,
+    #[serde(rename(serialize = "Priority"))]
+    pub priority: Option<i32>,
+    /// Set by the client, depending on whether a synchronous or an asynchronous method is called
+    #[serde(rename(serialize = "Asynchronous"))]
+    pub asynchronous: Option<bool>,
     #[serde(rename(serialize = "Force"))]
     pub force: Option<bool>,
 }
 
+/// Tags, that Orthanc only allows to be modified with `Force`
+const FORCE_TAGS: [&str; 4] = [
+    "PatientID",
+    "StudyInstanceUID",
+    "SeriesInstanceUID",
+    "SOPInstanceUID",
+];
+
+/// Builder of a [`Modification`]
+///
+/// ```
+/// let modification = ModificationBuilder::new()
+///     .replace("StudyInstanceUID", "1.2.3.4")
+///     .remove("InstitutionName")
+///     .keep_source(false)
+///     .force(true)
+///     .build()
+///     .unwrap();
+/// client.modify_study("9357491d-427a6c94-4080b6c8-1997f4aa-af658240", modification).unwrap();
+/// ```
+#[derive(Debug, Default)]
+pub struct ModificationBuilder {
+    modification: Modification,
+}
+
+impl ModificationBuilder {
+    /// Creates a new builder of an empty modification
+    pub fn new() -> ModificationBuilder {
+        ModificationBuilder::default()
+    }
+
+    /// Replace the value of a tag. `value` can be a string or, for sequences, a JSON array
+    pub fn replace(mut self, tag: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.modification
+            .replace
+            .get_or_insert_with(HashMap::new)
+            .insert(tag.into(), value.into());
+        self
+    }
+
+    /// Remove a tag
+    pub fn remove(mut self, tag: impl Into<String>) -> Self {
+        self.modification
+            .remove
+            .get_or_insert_with(Vec::new)
+            .push(tag.into());
+        self
+    }
+
+    /// Keep the original value of a tag, that Orthanc would otherwise regenerate (e.g. a UID)
+    pub fn keep(mut self, tag: impl Into<String>) -> Self {
+        self.modification
+            .keep
+            .get_or_insert_with(Vec::new)
+            .push(tag.into());
+        self
+    }
+
+    /// Keep the original resources after the modification (only relevant for patients, studies
+    /// and series). By default, Orthanc deletes them
+    pub fn keep_source(mut self, keep_source: bool) -> Self {
+        self.modification.keep_source = Some(keep_source);
+        self
+    }
+
+    /// Remove all the private tags
+    pub fn remove_private_tags(mut self, remove_private_tags: bool) -> Self {
+        self.modification.remove_private_tags = Some(remove_private_tags);
+        self
+    }
+
+    /// Private creator to use for the private tags, that are replaced
+    pub fn private_creator(mut self, private_creator: impl Into<String>) -> Self {
+        self.modification.private_creator = Some(private_creator.into());
+        self
+    }
+
+    /// Transcode the modified DICOM files to the given transfer syntax
+    pub fn transcode(mut self, transfer_syntax: impl Into<TransferSyntax>) -> Self {
+        self.modification.transcode = Some(transfer_syntax.into());
+        self
+    }
+
+    /// Ignore the errors in the individual instances, instead of failing the whole modification
+    pub fn permissive(mut self, permissive: bool) -> Self {
+        self.modification.permissive = Some(permissive);
+        self
+    }
+
+    /// Priority of the job, that performs an asynchronous modification
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.modification.priority = Some(priority);
+        self
+    }
+
+    /// Allow modifying or keeping the identifiers (`PatientID` and the UIDs), which Orthanc
+    /// otherwise refuses
+    pub fn force(mut self, force: bool) -> Self {
+        self.modification.force = Some(force);
+        self
+    }
+
+    /// Validate the options and build the [`Modification`]
+    ///
+    /// Fails if a tag is both replaced and removed, or removed/replaced and kept at the same
+    /// time, or if an identifier (`PatientID` or a UID) is replaced, removed or kept without
+    /// `Force`.
+    pub fn build(self) -> Result<Modification> {
+        let m = self.modification;
+        let replaced: Vec<&String> = m.replace.iter().flat_map(|r| r.keys()).collect();
+        let removed: Vec<&String> = m.remove.iter().flatten().collect();
+        let kept: Vec<&String> = m.keep.iter().flatten().collect();
+
+        for tag in &removed {
+            if replaced.contains(tag) {
+                return Err(Error::new(
+                    &format!("Tag {} is both replaced and removed", tag),
+                    None,
+                ));
+            }
+        }
+        for tag in &kept {
+            if replaced.contains(tag) || removed.contains(tag) {
+                return Err(Error::new(
+                    &format!("Tag {} is both kept and modified", tag),
+                    None,
+                ));
+            }
+        }
+        if m.force != Some(true) {
+            if let Some(tag) = replaced
+                .iter()
+                .chain(removed.iter())
+                .find(|t| FORCE_TAGS.contains(&t.as_str()))
+            {
+                return Err(Error::new(
+                    &format!("Modifying {} requires Force", tag),
+                    None,
+                ));
+            }
+            if let Some(tag) = kept.iter().find(|t| FORCE_TAGS.contains(&t.as_str())) {
+                return Err(Error::new(&format!("Keeping {} requires Force", tag), None));
+            }
+        }
+        Ok(m)
+    }
+}
+
 /// Ancestor of an entity
 ///
 /// Returned as response body in DELETE responses to indicate the remaining ancestor of the deleted
@@ -155,17 +630,357 @@ pub struct ModalityRetrieve {
     pub target_aet: String,
 }
 
-/// Result of a DICOM upload request
+/// DICOM transfer syntax
+///
+/// Serialized as (and deserialized from) its UID. Transfer syntaxes, that don't have a
+/// dedicated variant, are represented by [`TransferSyntax::Other`].
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
+#[serde(from = "String", into = "String")]
+pub enum TransferSyntax {
+    ImplicitVrLittleEndian,
+    ExplicitVrLittleEndian,
+    DeflatedExplicitVrLittleEndian,
+    ExplicitVrBigEndian,
+    JpegBaseline,
+    JpegExtended,
+    JpegLossless,
+    JpegLosslessFirstOrder,
+    JpegLsLossless,
+    JpegLsNearLossless,
+    Jpeg2000Lossless,
+    Jpeg2000,
+    RleLossless,
+    Other(String),
+}
+
+impl TransferSyntax {
+    /// UID of the transfer syntax, e.g. `1.2.840.10008.1.2.1`
+    pub fn uid(&self) -> &str {
+        match self {
+            TransferSyntax::ImplicitVrLittleEndian => "1.2.840.10008.1.2",
+            TransferSyntax::ExplicitVrLittleEndian => "1.2.840.10008.1.2.1",
+            TransferSyntax::DeflatedExplicitVrLittleEndian => "1.2.840.10008.1.2.1.99",
+            TransferSyntax::ExplicitVrBigEndian => "1.2.840.10008.1.2.2",
+            TransferSyntax::JpegBaseline => "1.2.840.10008.1.2.4.50",
+            TransferSyntax::JpegExtended => "1.2.840.10008.1.2.4.51",
+            TransferSyntax::JpegLossless => "1.2.840.10008.1.2.4.57",
+            TransferSyntax::JpegLosslessFirstOrder => "1.2.840.10008.1.2.4.70",
+            TransferSyntax::JpegLsLossless => "1.2.840.10008.1.2.4.80",
+            TransferSyntax::JpegLsNearLossless => "1.2.840.10008.1.2.4.81",
+            TransferSyntax::Jpeg2000Lossless => "1.2.840.10008.1.2.4.90",
+            TransferSyntax::Jpeg2000 => "1.2.840.10008.1.2.4.91",
+            TransferSyntax::RleLossless => "1.2.840.10008.1.2.5",
+            TransferSyntax::Other(uid) => uid,
+        }
+    }
+}
+
+impl From<&str> for TransferSyntax {
+    fn from(uid: &str) -> Self {
+        match uid.trim() {
+            "1.2.840.10008.1.2" => TransferSyntax::ImplicitVrLittleEndian,
+            "1.2.840.10008.1.2.1" => TransferSyntax::ExplicitVrLittleEndian,
+            "1.2.840.10008.1.2.1.99" => TransferSyntax::DeflatedExplicitVrLittleEndian,
+            "1.2.840.10008.1.2.2" => TransferSyntax::ExplicitVrBigEndian,
+            "1.2.840.10008.1.2.4.50" => TransferSyntax::JpegBaseline,
+            "1.2.840.10008.1.2.4.51" => TransferSyntax::JpegExtended,
+            "1.2.840.10008.1.2.4.57" => TransferSyntax::JpegLossless,
+            "1.2.840.10008.1.2.4.70" => TransferSyntax::JpegLosslessFirstOrder,
+            "1.2.840.10008.1.2.4.80" => TransferSyntax::JpegLsLossless,
+            "1.2.840.10008.1.2.4.81" => TransferSyntax::JpegLsNearLossless,
+            "1.2.840.10008.1.2.4.90" => TransferSyntax::Jpeg2000Lossless,
+            "1.2.840.10008.1.2.4.91" => TransferSyntax::Jpeg2000,
+            "1.2.840.10008.1.2.5" => TransferSyntax::RleLossless,
+            other => TransferSyntax::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for TransferSyntax {
+    fn from(uid: String) -> Self {
+        TransferSyntax::from(uid.as_str())
+    }
+}
+
+impl From<TransferSyntax> for String {
+    fn from(transfer_syntax: TransferSyntax) -> Self {
+        transfer_syntax.uid().to_string()
+    }
+}
+
+impl fmt::Display for TransferSyntax {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.uid())
+    }
+}
+
+/// Format, in which Orthanc returns DICOM tags
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TagsFormat {
+    /// Tag values, keyed by tag names (e.g. `PatientName`)
+    Simplified,
+    /// Tag values, keyed by tag codings (e.g. `0010,0010`)
+    Short,
+    /// Tag names, types and values, keyed by tag codings
+    Full,
+}
+
+impl TagsFormat {
+    pub(crate) fn query(&self) -> &'static str {
+        match self {
+            TagsFormat::Simplified => "?simplify",
+            TagsFormat::Short => "?short",
+            TagsFormat::Full => "",
+        }
+    }
+}
+
+/// A DICOM tag in the [`TagsFormat::Full`] format
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
 #[serde(rename_all = "PascalCase")]
-pub struct UploadResult {
+pub struct DicomTag {
+    pub name: String,
+    /// `String`, `Sequence`, `Null`, `TooLong` or `Binary`
+    #[serde(rename = "Type")]
+    pub value_type: String,
+    pub value: Value,
+}
+
+/// DICOM tags of an entity
+///
+/// The variant matches the [`TagsFormat`] the tags have been requested in. Values of sequences
+/// are arrays of nested tags of the same format.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Tags {
+    Simplified(HashMap<String, Value>),
+    Short(HashMap<String, Value>),
+    Full(HashMap<String, DicomTag>),
+}
+
+impl Tags {
+    pub(crate) fn from_value(format: TagsFormat, value: Value) -> Result<Tags> {
+        Ok(match format {
+            TagsFormat::Simplified => Tags::Simplified(serde_json::from_value(value)?),
+            TagsFormat::Short => Tags::Short(serde_json::from_value(value)?),
+            TagsFormat::Full => Tags::Full(serde_json::from_value(value)?),
+        })
+    }
+
+    /// Value of a tag. `tag` is a tag name for [`Tags::Simplified`] and a tag coding (e.g.
+    /// `0010,0010`) otherwise
+    pub fn value(&self, tag: &str) -> Option<&Value> {
+        match self {
+            Tags::Simplified(tags) | Tags::Short(tags) => tags.get(tag),
+            Tags::Full(tags) => tags.get(tag).map(|t| &t.value),
+        }
+    }
+}
+
+/// Instances of a series, ordered by the position of their slices in the 3D volume
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct OrderedSlices {
+    /// Paths of the DICOM files of the instances, e.g. `/instances/{id}/file`
+    pub dicom: Vec<String>,
+    /// Paths of the frames, e.g. `/instances/{id}/frames/0`
+    pub slices: Vec<String>,
+    pub slices_short: Vec<Slice>,
+    #[serde(rename = "Type")]
+    pub sorting: SliceSorting,
+}
+
+impl OrderedSlices {
+    /// IDs of the instances in the order of their slices, without duplicates
+    pub fn instance_ids(&self) -> Vec<InstanceId> {
+        let mut seen = HashSet::new();
+        self.slices_short
+            .iter()
+            .filter(|slice| seen.insert(&slice.instance))
+            .map(|slice| slice.instance.clone())
+            .collect()
+    }
+}
+
+/// A run of consecutive frames of one instance
+///
+/// Serialized as (and deserialized from) an `[instance, first_frame, frame_count]` array.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq)]
+#[serde(from = "(InstanceId, u32, u32)", into = "(InstanceId, u32, u32)")]
+pub struct Slice {
+    pub instance: InstanceId,
+    pub first_frame: u32,
+    pub frame_count: u32,
+}
+
+impl From<(InstanceId, u32, u32)> for Slice {
+    fn from((instance, first_frame, frame_count): (InstanceId, u32, u32)) -> Self {
+        Slice {
+            instance,
+            first_frame,
+            frame_count,
+        }
+    }
+}
+
+impl From<Slice> for (InstanceId, u32, u32) {
+    fn from(slice: Slice) -> Self {
+        (slice.instance, slice.first_frame, slice.frame_count)
+    }
+}
+
+/// How Orthanc has sorted the slices of a series
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SliceSorting {
+    /// By the position of the slices along the normal of the series
+    Volume,
+    /// By the instance number or the index of the instances in the series
+    Sequence,
+}
+
+/// Options of a NumPy export of the pixel data of an instance or a series
+///
+/// See [`numpy`](crate::numpy) for parsing the exported data.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct NumpyOptions {
+    /// Export a compressed `.npz` archive instead of a `.npy` file
+    pub compress: bool,
+    /// Apply the rescale slope and intercept to grayscale images, producing floating-point values
+    pub rescale: bool,
+}
+
+impl NumpyOptions {
+    pub(crate) fn query(&self) -> String {
+        format!("?compress={}&rescale={}", self.compress, self.rescale)
+    }
+}
+
+/// File meta information (group `0002`) of a DICOM file
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct FileMetaInformation {
+    #[serde(rename = "MediaStorageSOPClassUID")]
+    pub media_storage_sop_class_uid: String,
+    #[serde(rename = "MediaStorageSOPInstanceUID")]
+    pub media_storage_sop_instance_uid: Option<String>,
+    #[serde(rename = "TransferSyntaxUID")]
+    pub transfer_syntax_uid: TransferSyntax,
+    #[serde(rename = "ImplementationClassUID")]
+    pub implementation_class_uid: String,
+    pub implementation_version_name: Option<String>,
+    pub source_application_entity_title: Option<String>,
+}
+
+/// Archive or media creation request body
+///
+/// Used to create a ZIP archive (optionally containing a DICOMDIR) out of an arbitrary set of
+/// entities. Each ID in `resources` can signify either of
+/// [`Patient`](crate::entity::Patient), [`Study`](crate::entity::Study),
+/// [`Series`](crate::entity::Series) or [`Instance`](crate::entity::Instance).
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Archive {
+    pub resources: Vec<String>,
+    pub synchronous: Option<bool>,
+    /// Transfer syntax to transcode the DICOM files to
+    pub transcode: Option<TransferSyntax>,
+    /// Name of the resulting archive file, as reported by Orthanc in `Content-Disposition`
+    pub filename: Option<String>,
+    pub priority: Option<i32>,
+}
+
+/// Study split request body
+///
+/// Moves the given series and/or instances out of a study into a newly created study. The
+/// main DICOM tags of the new study can be changed with `replace` and `remove`.
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct StudySplit {
+    pub series: Option<Vec<SeriesId>>,
+    pub instances: Option<Vec<InstanceId>>,
+    pub replace: Option<HashMap<String, String>>,
+    pub remove: Option<Vec<String>>,
+    /// Keep the moved series and instances in the original study
+    pub keep_source: Option<bool>,
+    pub priority: Option<i32>,
+    /// Set by the client, depending on whether a synchronous or an asynchronous method is called
+    pub asynchronous: Option<bool>,
+}
+
+/// Study merge request body
+///
+/// Moves the given entities into a study. Each ID in `resources` can signify either of
+/// [`Study`](crate::entity::Study), [`Series`](crate::entity::Series) or
+/// [`Instance`](crate::entity::Instance).
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct StudyMerge {
+    pub resources: Vec<String>,
+    /// Keep the moved entities in their original studies
+    pub keep_source: Option<bool>,
+    pub priority: Option<i32>,
+    /// Set by the client, depending on whether a synchronous or an asynchronous method is called
+    pub asynchronous: Option<bool>,
+}
+
+/// Asynchronous job, created as a result of a request
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Job {
     #[serde(rename = "ID")]
     pub id: String,
-    pub status: String,
     pub path: String,
-    pub parent_patient: String,
-    pub parent_study: String,
-    pub parent_series: String,
+}
+
+/// Information about a job
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct JobInfo {
+    #[serde(rename = "ID")]
+    pub id: String,
+    #[serde(rename = "Type")]
+    pub job_type: String,
+    pub state: String,
+    pub progress: u8,
+    pub priority: i32,
+    pub creation_time: String,
+    pub completion_time: Option<String>,
+    pub error_code: i32,
+    pub error_description: String,
+    pub content: serde_json::Value,
+}
+
+/// Status of an uploaded DICOM instance
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+pub enum UploadStatus {
+    /// The instance has been stored
+    Success,
+    /// The instance had already been stored before
+    AlreadyStored,
+    /// The instance could not be stored
+    Failure,
+    /// The instance has been rejected by a filter (e.g. a Lua callback)
+    FilteredOut,
+}
+
+/// Result of a DICOM upload request
+///
+/// Orthanc omits the IDs and the path of an instance, that could not be stored
+/// ([`UploadStatus::Failure`]) or that has been filtered out ([`UploadStatus::FilteredOut`]).
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct UploadResult {
+    #[serde(rename = "ID")]
+    pub id: Option<InstanceId>,
+    pub status: UploadStatus,
+    pub path: Option<String>,
+    pub parent_patient: Option<PatientId>,
+    pub parent_study: Option<StudyId>,
+    pub parent_series: Option<SeriesId>,
 }
 
 /// Result of a C-STORE DICOM request (sending entities to a modality)
@@ -192,6 +1007,17 @@ pub struct ModalityFindResult {
     pub path: String,
 }
 
+/// Peer store request body
+#[skip_serializing_none]
+#[derive(Serialize, Deserialize, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct PeerStore {
+    pub resources: Vec<String>,
+    /// Transfer syntax to transcode the DICOM files to before sending them
+    pub transcode: Option<TransferSyntax>,
+    pub synchronous: Option<bool>,
+}
+
 /// Result of a peer store request (sending entities to a peer)
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
 #[serde(rename_all = "PascalCase")]
@@ -203,6 +1029,24 @@ pub struct PeerStoreResult {
     pub failed_instances_count: u64,
 }
 
+/// Result of a study split request
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct StudySplitResult {
+    /// ID of the newly created study
+    pub target_study: StudyId,
+    #[serde(rename = "TargetStudyUID")]
+    pub target_study_uid: String,
+}
+
+/// Result of a study merge request
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct StudyMergeResult {
+    /// ID of the study, the entities were merged into
+    pub target_study: StudyId,
+}
+
 /// Result of a modification or anonymization request
 #[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
 #[serde(rename_all = "PascalCase")]
@@ -216,6 +1060,33 @@ pub struct ModificationResult {
     pub entity: EntityKind,
 }
 
+/// An Orthanc entity, found by a DICOM identifier lookup
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupResult {
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub path: String,
+    #[serde(rename = "Type")]
+    pub entity: EntityKind,
+}
+
+/// A study that could not be reconstructed
+#[derive(Debug, Eq, PartialEq)]
+pub struct ReconstructFailure {
+    pub id: StudyId,
+    pub reason: String,
+}
+
+/// Report of a reconstruction of all studies
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct ReconstructReport {
+    /// IDs of the studies that have been reconstructed
+    pub reconstructed: Vec<StudyId>,
+    /// Studies that could not be reconstructed, along with the reasons
+    pub failures: Vec<ReconstructFailure>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -238,10 +1109,11 @@ mod tests {
             m1,
             Modification {
                 replace: Some(
-                    hashmap! {"Foo".to_string() => "42".to_string(), "Bar".to_string() => "17".to_string()}
+                    hashmap! {"Foo".to_string() => "42".into(), "Bar".to_string() => "17".into()}
                 ),
                 remove: Some(vec!["Baz".to_string(), "Qux".to_string()]),
-                force: Some(true)
+                force: Some(true),
+                ..Default::default()
             }
         );
 
@@ -251,9 +1123,117 @@ mod tests {
             Modification {
                 replace: None,
                 remove: None,
-                force: None
+                force: None,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_modification_serialize() {
+        let m = Modification {
+            replace: Some(hashmap! {
+                "ProcedureCodeSequence".to_string() => serde_json::json!([{"CodeValue": "12345"}])
+            }),
+            keep: Some(vec!["SOPInstanceUID".to_string()]),
+            keep_source: Some(false),
+            transcode: Some(TransferSyntax::JpegLosslessFirstOrder),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(m).unwrap(),
+            serde_json::json!({
+                "Replace": {"ProcedureCodeSequence": [{"CodeValue": "12345"}]},
+                "Keep": ["SOPInstanceUID"],
+                "KeepSource": false,
+                "Transcode": "1.2.840.10008.1.2.4.70"
+            })
+        );
+    }
+
+    #[test]
+    fn test_modification_builder() {
+        let m = ModificationBuilder::new()
+            .replace("PatientName", "Rick Sanchez")
+            .replace("StudyInstanceUID", "1.2.3.4")
+            .remove("InstitutionName")
+            .keep("SeriesInstanceUID")
+            .keep_source(true)
+            .remove_private_tags(true)
+            .private_creator("Foo")
+            .transcode("1.2.840.10008.1.2.1")
+            .permissive(true)
+            .priority(5)
+            .force(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            m,
+            Modification {
+                replace: Some(hashmap! {
+                    "PatientName".to_string() => "Rick Sanchez".into(),
+                    "StudyInstanceUID".to_string() => "1.2.3.4".into(),
+                }),
+                remove: Some(vec!["InstitutionName".to_string()]),
+                keep: Some(vec!["SeriesInstanceUID".to_string()]),
+                keep_source: Some(true),
+                remove_private_tags: Some(true),
+                private_creator: Some("Foo".to_string()),
+                transcode: Some(TransferSyntax::ExplicitVrLittleEndian),
+                permissive: Some(true),
+                priority: Some(5),
+                asynchronous: None,
+                force: Some(true),
             }
         );
+        assert_eq!(
+            ModificationBuilder::new().build().unwrap(),
+            Modification::default()
+        );
+    }
+
+    #[test]
+    fn test_modification_builder_errors() {
+        assert_eq!(
+            ModificationBuilder::new()
+                .replace("StudyInstanceUID", "1.2.3.4")
+                .build()
+                .unwrap_err(),
+            Error::new("Modifying StudyInstanceUID requires Force", None)
+        );
+        assert_eq!(
+            ModificationBuilder::new()
+                .remove("PatientID")
+                .force(false)
+                .build()
+                .unwrap_err(),
+            Error::new("Modifying PatientID requires Force", None)
+        );
+        assert_eq!(
+            ModificationBuilder::new()
+                .replace("PatientName", "Rick Sanchez")
+                .keep("SOPInstanceUID")
+                .build()
+                .unwrap_err(),
+            Error::new("Keeping SOPInstanceUID requires Force", None)
+        );
+        assert_eq!(
+            ModificationBuilder::new()
+                .replace("PatientName", "Rick Sanchez")
+                .remove("PatientName")
+                .build()
+                .unwrap_err(),
+            Error::new("Tag PatientName is both replaced and removed", None)
+        );
+        assert_eq!(
+            ModificationBuilder::new()
+                .remove("SeriesInstanceUID")
+                .keep("SeriesInstanceUID")
+                .force(true)
+                .build()
+                .unwrap_err(),
+            Error::new("Tag SeriesInstanceUID is both kept and modified", None)
+        );
     }
 
     #[test]
@@ -275,12 +1255,13 @@ mod tests {
             a1,
             Anonymization {
                 replace: Some(
-                    hashmap! {"Foo".to_string() => "42".to_string(), "Bar".to_string() => "17".to_string()}
+                    hashmap! {"Foo".to_string() => "42".into(), "Bar".to_string() => "17".into()}
                 ),
                 keep: Some(vec!["Baz".to_string(), "Qux".to_string()]),
                 keep_private_tags: Some(true),
                 dicom_version: Some("42.17".to_string()),
-                force: Some(true)
+                force: Some(true),
+                ..Default::default()
             }
         );
         let a2: Anonymization = serde_json::from_str("{}").unwrap();
@@ -291,11 +1272,133 @@ mod tests {
                 keep: None,
                 keep_private_tags: None,
                 dicom_version: None,
-                force: None
+                force: None,
+                ..Default::default()
             }
         );
     }
 
+    #[test]
+    fn test_anonymization_serialize_default() {
+        assert_eq!(
+            serde_json::to_value(Anonymization::default()).unwrap(),
+            serde_json::json!({})
+        );
+    }
+
+    #[test]
+    fn test_anonymization_builder() {
+        let a = AnonymizationBuilder::new()
+            .profile(AnonymizationProfile::Basic)
+            .profile(AnonymizationProfile::RetainDeviceIdentity)
+            .replace("InstitutionName", "Citadel")
+            .keep("Manufacturer")
+            .remove("StationName")
+            .keep_private_tags(false)
+            .keep_source(true)
+            .private_creator("Foo")
+            .transcode("1.2.840.10008.1.2.1")
+            .priority(5)
+            .dicom_version("2021b")
+            .build()
+            .unwrap();
+        assert_eq!(
+            a,
+            Anonymization {
+                replace: Some(hashmap! {"InstitutionName".to_string() => "Citadel".into()}),
+                keep: Some(vec![
+                    "Manufacturer".to_string(),
+                    "DeviceSerialNumber".to_string(),
+                    "DeviceUID".to_string(),
+                    "DetectorID".to_string(),
+                    "GantryID".to_string(),
+                    "PlateID".to_string(),
+                    "CassetteID".to_string(),
+                    "GeneratorID".to_string(),
+                ]),
+                remove: Some(vec!["StationName".to_string()]),
+                keep_private_tags: Some(false),
+                keep_source: Some(true),
+                private_creator: Some("Foo".to_string()),
+                transcode: Some(TransferSyntax::ExplicitVrLittleEndian),
+                priority: Some(5),
+                asynchronous: None,
+                dicom_version: Some("2021b".to_string()),
+                force: None,
+            }
+        );
+        assert_eq!(
+            AnonymizationBuilder::new().build().unwrap(),
+            Anonymization::default()
+        );
+    }
+
+    #[test]
+    fn test_anonymization_builder_research_pseudonymization() {
+        let a = AnonymizationBuilder::new()
+            .profile(AnonymizationProfile::ResearchPseudonymization)
+            .profile(AnonymizationProfile::RetainDates)
+            .replace("PatientID", "SUBJ-0042")
+            .replace("PatientName", "SUBJ-0042")
+            .replace("StudyDate", "20000101")
+            .build()
+            .unwrap();
+
+        let keep = a.keep.unwrap();
+        assert!(!keep.contains(&"StudyDate".to_string()));
+        assert!(keep.contains(&"SeriesDate".to_string()));
+        assert!(keep.contains(&"PatientSex".to_string()));
+        assert_eq!(keep.iter().filter(|t| *t == "SeriesDate").count(), 1);
+        assert_eq!(
+            a.remove.unwrap(),
+            vec!["PatientBirthDate", "OtherPatientIDs", "OtherPatientNames"]
+        );
+
+        assert_eq!(
+            AnonymizationBuilder::new()
+                .profile(AnonymizationProfile::ResearchPseudonymization)
+                .replace("PatientName", "SUBJ-0042")
+                .build()
+                .unwrap_err(),
+            Error::new(
+                "Profile ResearchPseudonymization requires PatientID to be replaced",
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn test_anonymization_builder_errors() {
+        assert_eq!(
+            AnonymizationBuilder::new()
+                .keep("StudyInstanceUID")
+                .build()
+                .unwrap_err(),
+            Error::new("Keeping or replacing StudyInstanceUID requires Force", None)
+        );
+        assert!(AnonymizationBuilder::new()
+            .keep("StudyInstanceUID")
+            .force(true)
+            .build()
+            .is_ok());
+        assert_eq!(
+            AnonymizationBuilder::new()
+                .keep("PatientName")
+                .replace("PatientName", "Foo")
+                .build()
+                .unwrap_err(),
+            Error::new("Tag PatientName is both kept and modified", None)
+        );
+        assert_eq!(
+            AnonymizationBuilder::new()
+                .remove("PatientName")
+                .replace("PatientName", "Foo")
+                .build()
+                .unwrap_err(),
+            Error::new("Tag PatientName is both replaced and removed", None)
+        );
+    }
+
     #[test]
     fn test_peer_deserialize() {
         let json = r#"
@@ -325,4 +1428,75 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn test_transfer_syntax() {
+        assert_eq!(
+            TransferSyntax::ExplicitVrLittleEndian.uid(),
+            "1.2.840.10008.1.2.1"
+        );
+        assert_eq!(
+            TransferSyntax::from("1.2.840.10008.1.2.4.90"),
+            TransferSyntax::Jpeg2000Lossless
+        );
+        assert_eq!(
+            TransferSyntax::from("1.2.3.4"),
+            TransferSyntax::Other("1.2.3.4".to_string())
+        );
+        assert_eq!(
+            TransferSyntax::Other("1.2.3.4".to_string()).uid(),
+            "1.2.3.4"
+        );
+        assert_eq!(
+            TransferSyntax::RleLossless.to_string(),
+            "1.2.840.10008.1.2.5"
+        );
+    }
+
+    #[test]
+    fn test_transfer_syntax_serde() {
+        assert_eq!(
+            serde_json::to_value(TransferSyntax::JpegBaseline).unwrap(),
+            serde_json::json!("1.2.840.10008.1.2.4.50")
+        );
+        let t: TransferSyntax = serde_json::from_str(r#""1.2.840.10008.1.2""#).unwrap();
+        assert_eq!(t, TransferSyntax::ImplicitVrLittleEndian);
+    }
+
+    #[test]
+    fn test_ordered_slices_instance_ids() {
+        let slices: OrderedSlices = serde_json::from_str(
+            r#"
+                {
+                    "Dicom": ["/instances/foo/file", "/instances/bar/file"],
+                    "Slices": [
+                        "/instances/foo/frames/0",
+                        "/instances/bar/frames/0",
+                        "/instances/bar/frames/1",
+                        "/instances/foo/frames/1"
+                    ],
+                    "SlicesShort": [["foo", 0, 1], ["bar", 0, 2], ["foo", 1, 1]],
+                    "Type": "Volume"
+                }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            slices.slices_short[1],
+            Slice {
+                instance: "bar".into(),
+                first_frame: 0,
+                frame_count: 2
+            }
+        );
+        assert_eq!(slices.sorting, SliceSorting::Volume);
+        assert_eq!(
+            slices.instance_ids(),
+            vec![InstanceId::from("foo"), InstanceId::from("bar")]
+        );
+        assert_eq!(
+            serde_json::to_value(&slices.slices_short[0]).unwrap(),
+            serde_json::json!(["foo", 0, 1])
+        );
+    }
 }
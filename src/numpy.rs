@@ -0,0 +1,408 @@
+//! Parsing of NumPy arrays, exported by Orthanc
+//!
+//! Orthanc can export the pixel data of an instance or a series as a NumPy `.npy` file (or a
+//! `.npz` archive of it, if compression is requested). The functions of this module turn the
+//! exported data into a typed buffer, along with its shape:
+//!
+//! ```
+//! let mut data = Vec::new();
+//! client
+//!     .instance_numpy("29fa4d9d-51a69d1d-70e2b29a-fd824316-50850d0c", NumpyOptions::default(), &mut data)
+//!     .unwrap();
+//! let array = numpy::read_array(data.as_slice()).unwrap();
+//! if let NpyData::U16(pixels) = array.data {
+//!     println!("{:?}: {} pixels", array.header.shape, pixels.len());
+//! }
+//! ```
+use crate::{Error, Result};
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::io::prelude::*;
+use std::io::Cursor;
+use std::mem::size_of;
+
+const MAGIC: &[u8] = b"\x93NUMPY";
+
+/// Type of the elements of a NumPy array
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Dtype {
+    Bool,
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+    U64,
+    I64,
+    F32,
+    F64,
+}
+
+impl Dtype {
+    /// Size of a single element in bytes
+    pub fn size(&self) -> usize {
+        match self {
+            Dtype::Bool | Dtype::U8 | Dtype::I8 => 1,
+            Dtype::U16 | Dtype::I16 => 2,
+            Dtype::U32 | Dtype::I32 | Dtype::F32 => 4,
+            Dtype::U64 | Dtype::I64 | Dtype::F64 => 8,
+        }
+    }
+}
+
+/// Header of a `.npy` file
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NpyHeader {
+    pub dtype: Dtype,
+    pub little_endian: bool,
+    /// Whether the data is in column-major (Fortran) order, i.e. the first index varies fastest
+    pub fortran_order: bool,
+    /// Dimensions of the array, e.g. `[frames, height, width, channels]` for a series
+    pub shape: Vec<usize>,
+}
+
+impl NpyHeader {
+    /// Total number of elements in the array, or `None`, if it does not fit into a `usize`
+    pub fn element_count(&self) -> Option<usize> {
+        self.shape.iter().try_fold(1usize, |n, d| n.checked_mul(*d))
+    }
+}
+
+/// Elements of a NumPy array, converted to the native byte order
+#[derive(Debug, PartialEq)]
+pub enum NpyData {
+    Bool(Vec<bool>),
+    U8(Vec<u8>),
+    I8(Vec<i8>),
+    U16(Vec<u16>),
+    I16(Vec<i16>),
+    U32(Vec<u32>),
+    I32(Vec<i32>),
+    U64(Vec<u64>),
+    I64(Vec<i64>),
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+}
+
+/// A NumPy array, read from a `.npy` file
+///
+/// The elements are in the order of the file. If [`NpyHeader::fortran_order`] is set, they are
+/// in column-major order, and are not transposed to the row-major order of the other arrays.
+#[derive(Debug, PartialEq)]
+pub struct NpyArray {
+    pub header: NpyHeader,
+    pub data: NpyData,
+}
+
+/// Read the header of a `.npy` file, leaving `reader` at the start of the array data
+pub fn read_header<R: Read>(reader: &mut R) -> Result<NpyHeader> {
+    let mut preamble = [0; 8];
+    reader.read_exact(&mut preamble)?;
+    if &preamble[..6] != MAGIC {
+        return Err(Error::new("Not a NumPy file", None));
+    }
+    let header_len = match preamble[6] {
+        1 => {
+            let mut len = [0; 2];
+            reader.read_exact(&mut len)?;
+            u16::from_le_bytes(len) as usize
+        }
+        2 | 3 => {
+            let mut len = [0; 4];
+            reader.read_exact(&mut len)?;
+            u32::from_le_bytes(len) as usize
+        }
+        v => {
+            return Err(Error::new(
+                &format!("Unsupported NumPy format version: {}", v),
+                None,
+            ))
+        }
+    };
+    let mut header = vec![0; header_len];
+    reader.read_exact(&mut header)?;
+    parse_header(std::str::from_utf8(&header)?)
+}
+
+/// Read a `.npy` file
+///
+/// Fails if the size of the data, given by the header, overflows, or if there is less data.
+pub fn read_array<R: Read>(mut reader: R) -> Result<NpyArray> {
+    let header = read_header(&mut reader)?;
+    let len = header
+        .element_count()
+        .and_then(|n| n.checked_mul(header.dtype.size()))
+        .ok_or_else(|| invalid_header(&format!("shape {:?} is too large", header.shape)))?;
+    // The buffer grows with the data actually read, so a bogus shape does not allocate it upfront
+    let mut bytes = vec![];
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < len {
+        return Err(Error::new(
+            &format!(
+                "NumPy data is truncated: expected {} bytes, got {}",
+                len,
+                bytes.len()
+            ),
+            None,
+        ));
+    }
+    let data = convert(&bytes, header.dtype, header.little_endian);
+    Ok(NpyArray { header, data })
+}
+
+/// Read all arrays of a `.npz` archive (as exported by Orthanc, when compression is requested),
+/// keyed by their names (e.g. `arr_0`)
+pub fn read_npz(data: &[u8]) -> Result<HashMap<String, NpyArray>> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(data))?;
+    let mut arrays = HashMap::new();
+    for i in 0..archive.len() {
+        let file = archive.by_index(i)?;
+        let name = file.name().trim_end_matches(".npy").to_string();
+        arrays.insert(name, read_array(file)?);
+    }
+    Ok(arrays)
+}
+
+fn parse_header(header: &str) -> Result<NpyHeader> {
+    let descr = field(header, "descr")?.trim_matches(|c| c == '\'' || c == '"');
+    let (little_endian, dtype) = parse_descr(descr)?;
+    let fortran_order = match field(header, "fortran_order")? {
+        "True" => true,
+        "False" => false,
+        v => return Err(invalid_header(&format!("fortran_order {}", v))),
+    };
+    let shape = field(header, "shape")?
+        .trim_matches(|c| c == '(' || c == ')')
+        .split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .map(|d| {
+            d.parse()
+                .map_err(|_| invalid_header(&format!("shape {}", d)))
+        })
+        .collect::<Result<Vec<usize>>>()?;
+    Ok(NpyHeader {
+        dtype,
+        little_endian,
+        fortran_order,
+        shape,
+    })
+}
+
+/// Raw value of a key of the Python dictionary literal, that makes up the header
+fn field<'a>(header: &'a str, key: &str) -> Result<&'a str> {
+    let pattern = format!("'{}':", key);
+    let start = header
+        .find(&pattern)
+        .ok_or_else(|| invalid_header(&format!("missing {}", key)))?
+        + pattern.len();
+    let value = header[start..].trim_start();
+    let end = if value.starts_with('(') {
+        value.find(')').map(|i| i + 1)
+    } else {
+        value.find([',', '}'])
+    };
+    end.map(|e| value[..e].trim())
+        .ok_or_else(|| invalid_header(&format!("unterminated {}", key)))
+}
+
+fn parse_descr(descr: &str) -> Result<(bool, Dtype)> {
+    let little_endian = !descr.starts_with('>');
+    let dtype = match descr.trim_start_matches(['<', '>', '|', '=']) {
+        "b1" => Dtype::Bool,
+        "u1" => Dtype::U8,
+        "i1" => Dtype::I8,
+        "u2" => Dtype::U16,
+        "i2" => Dtype::I16,
+        "u4" => Dtype::U32,
+        "i4" => Dtype::I32,
+        "u8" => Dtype::U64,
+        "i8" => Dtype::I64,
+        "f4" => Dtype::F32,
+        "f8" => Dtype::F64,
+        _ => {
+            return Err(Error::new(
+                &format!("Unsupported NumPy dtype: {}", descr),
+                None,
+            ))
+        }
+    };
+    Ok((little_endian, dtype))
+}
+
+fn invalid_header(reason: &str) -> Error {
+    Error::new(&format!("Invalid NumPy header: {}", reason), None)
+}
+
+macro_rules! elements {
+    ($bytes:expr, $t:ty, $little_endian:expr) => {
+        $bytes
+            .chunks_exact(size_of::<$t>())
+            .map(|c| {
+                let c = c.try_into().unwrap();
+                if $little_endian {
+                    <$t>::from_le_bytes(c)
+                } else {
+                    <$t>::from_be_bytes(c)
+                }
+            })
+            .collect()
+    };
+}
+
+fn convert(bytes: &[u8], dtype: Dtype, little_endian: bool) -> NpyData {
+    match dtype {
+        Dtype::Bool => NpyData::Bool(bytes.iter().map(|b| *b != 0).collect()),
+        Dtype::U8 => NpyData::U8(bytes.to_vec()),
+        Dtype::I8 => NpyData::I8(bytes.iter().map(|b| *b as i8).collect()),
+        Dtype::U16 => NpyData::U16(elements!(bytes, u16, little_endian)),
+        Dtype::I16 => NpyData::I16(elements!(bytes, i16, little_endian)),
+        Dtype::U32 => NpyData::U32(elements!(bytes, u32, little_endian)),
+        Dtype::I32 => NpyData::I32(elements!(bytes, i32, little_endian)),
+        Dtype::U64 => NpyData::U64(elements!(bytes, u64, little_endian)),
+        Dtype::I64 => NpyData::I64(elements!(bytes, i64, little_endian)),
+        Dtype::F32 => NpyData::F32(elements!(bytes, f32, little_endian)),
+        Dtype::F64 => NpyData::F64(elements!(bytes, f64, little_endian)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn npy(header: &str, data: &[u8]) -> Vec<u8> {
+        let mut npy = MAGIC.to_vec();
+        npy.extend_from_slice(&[1, 0]);
+        npy.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        npy.extend_from_slice(header.as_bytes());
+        npy.extend_from_slice(data);
+        npy
+    }
+
+    #[test]
+    fn test_read_array() {
+        let data = npy(
+            "{'descr': '<u2', 'fortran_order': False, 'shape': (2, 3, 1), }",
+            &[1, 0, 2, 0, 3, 0, 4, 0, 5, 0, 0, 1],
+        );
+        let array = read_array(data.as_slice()).unwrap();
+        assert_eq!(
+            array.header,
+            NpyHeader {
+                dtype: Dtype::U16,
+                little_endian: true,
+                fortran_order: false,
+                shape: vec![2, 3, 1],
+            }
+        );
+        assert_eq!(array.header.element_count(), Some(6));
+        assert_eq!(array.data, NpyData::U16(vec![1, 2, 3, 4, 5, 256]));
+    }
+
+    #[test]
+    fn test_read_array_big_endian_float() {
+        let mut bytes = vec![];
+        bytes.extend_from_slice(&1.5f32.to_be_bytes());
+        bytes.extend_from_slice(&(-2.0f32).to_be_bytes());
+        let data = npy(
+            "{'descr': '>f4', 'fortran_order': True, 'shape': (2,), }",
+            &bytes,
+        );
+        let array = read_array(data.as_slice()).unwrap();
+        assert!(!array.header.little_endian);
+        assert!(array.header.fortran_order);
+        assert_eq!(array.header.shape, vec![2]);
+        assert_eq!(array.data, NpyData::F32(vec![1.5, -2.0]));
+    }
+
+    #[test]
+    fn test_read_header_leaves_data() {
+        let data = npy(
+            "{'descr': '|u1', 'fortran_order': False, 'shape': (), }",
+            &[42],
+        );
+        let mut reader = data.as_slice();
+        let header = read_header(&mut reader).unwrap();
+        assert_eq!(header.dtype, Dtype::U8);
+        assert_eq!(header.shape, Vec::<usize>::new());
+        assert_eq!(header.element_count(), Some(1));
+        assert_eq!(reader, &[42]);
+    }
+
+    #[test]
+    fn test_read_npz() {
+        let data = npy(
+            "{'descr': '|i1', 'fortran_order': False, 'shape': (2,), }",
+            &[1, 255],
+        );
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("arr_0.npy", zip::write::FileOptions::default())
+            .unwrap();
+        zip.write_all(&data).unwrap();
+        let npz = zip.finish().unwrap().into_inner();
+
+        let arrays = read_npz(&npz).unwrap();
+        assert_eq!(arrays.len(), 1);
+        assert_eq!(arrays["arr_0"].data, NpyData::I8(vec![1, -1]));
+    }
+
+    #[test]
+    fn test_read_array_errors() {
+        assert_eq!(
+            read_array(&b"foobarbaz!"[..]).unwrap_err(),
+            Error::new("Not a NumPy file", None)
+        );
+        let data = npy(
+            "{'descr': '<c8', 'fortran_order': False, 'shape': (1,), }",
+            &[],
+        );
+        assert_eq!(
+            read_array(data.as_slice()).unwrap_err(),
+            Error::new("Unsupported NumPy dtype: <c8", None)
+        );
+        let data = npy("{'descr': '<u2', 'shape': (1,), }", &[]);
+        assert_eq!(
+            read_array(data.as_slice()).unwrap_err(),
+            Error::new("Invalid NumPy header: missing fortran_order", None)
+        );
+        let data = npy(
+            "{'descr': '<u2', 'fortran_order': False, 'shape': (4,), }",
+            &[0, 0],
+        );
+        assert_eq!(
+            read_array(data.as_slice()).unwrap_err(),
+            Error::new("NumPy data is truncated: expected 8 bytes, got 2", None)
+        );
+        let data = npy(
+            &format!(
+                "{{'descr': '<u2', 'fortran_order': False, 'shape': ({}, 2), }}",
+                usize::MAX
+            ),
+            &[],
+        );
+        assert_eq!(
+            read_array(data.as_slice()).unwrap_err(),
+            Error::new(
+                &format!(
+                    "Invalid NumPy header: shape [{}, 2] is too large",
+                    usize::MAX
+                ),
+                None
+            )
+        );
+        // A huge shape fails on the missing data, without allocating a buffer of its size
+        let data = npy(
+            "{'descr': '|u1', 'fortran_order': False, 'shape': (2147483648,), }",
+            &[42],
+        );
+        assert_eq!(
+            read_array(data.as_slice()).unwrap_err(),
+            Error::new(
+                "NumPy data is truncated: expected 2147483648 bytes, got 1",
+                None
+            )
+        );
+    }
+}
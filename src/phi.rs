@@ -0,0 +1,482 @@
+//! Verification of anonymized data for leftover PHI (protected health information)
+//!
+//! A [`PhiChecker`] inspects the tags of an anonymized instance (in the format of
+//! [`Client::instance_tags_expanded`]) or the raw bytes of an anonymized DICOM file, and reports
+//! every piece of identifying data it finds:
+//!
+//! * non-empty values of PHI tags (e.g. `PatientBirthDate` or `InstitutionName`)
+//! * non-empty private tags
+//! * values, that contain any of the original identifying values of the instance (ignoring the
+//!   case)
+//! * values, that cannot be inspected, because Orthanc does not return them as strings (values,
+//!   that are too long, as well as binary and null values of PHI and private tags)
+//!
+//! Sequences are inspected recursively.
+//!
+//! ```
+//! let checker = PhiChecker::new()
+//!     .originals_from_tags(&client.instance_tags_expanded(original_id).unwrap());
+//! let mut data = vec![];
+//! client.anonymize_instance(original_id, None, &mut data).unwrap();
+//! let report = checker.check_file(&data);
+//! assert!(report.is_clean(), "{:#?}", report.findings);
+//! ```
+use crate::{Client, Result};
+use serde_json::Value;
+
+/// Tags, that are checked by default. Orthanc replaces `PatientID` and `PatientName` with new
+/// values during anonymization, so they are only checked against the original values.
+pub const DEFAULT_PHI_TAGS: [&str; 26] = [
+    "PatientBirthDate",
+    "PatientBirthTime",
+    "PatientAddress",
+    "PatientTelephoneNumbers",
+    "PatientMotherBirthName",
+    "PatientComments",
+    "OtherPatientIDs",
+    "OtherPatientNames",
+    "OtherPatientIDsSequence",
+    "IssuerOfPatientID",
+    "MilitaryRank",
+    "MedicalRecordLocator",
+    "AdditionalPatientHistory",
+    "AccessionNumber",
+    "ReferringPhysicianName",
+    "ReferringPhysicianAddress",
+    "ReferringPhysicianTelephoneNumbers",
+    "PerformingPhysicianName",
+    "NameOfPhysiciansReadingStudy",
+    "RequestingPhysician",
+    "OperatorsName",
+    "InstitutionName",
+    "InstitutionAddress",
+    "InstitutionalDepartmentName",
+    "StationName",
+    "DeviceSerialNumber",
+];
+
+/// Tags, the original values of which are looked for in the anonymized data, in addition to the
+/// PHI tags
+const IDENTIFYING_TAGS: [&str; 2] = ["PatientID", "PatientName"];
+
+/// Original values shorter than this are ignored, as they would match too much unrelated data
+const MIN_ORIGINAL_LEN: usize = 3;
+
+/// Reason of a finding
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PhiReason {
+    /// A PHI tag has a non-empty value
+    PhiTag,
+    /// A private tag has a non-empty value
+    PrivateTag,
+    /// The value contains an original identifying value
+    OriginalValue(String),
+    /// The value is not available as a string (its Orthanc type is `TooLong`, `Binary` or
+    /// `Null`), so it cannot be verified. Such a value should be checked with
+    /// [`PhiChecker::check_file`]
+    Unverifiable(String),
+}
+
+/// A piece of identifying data, found in the anonymized data
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PhiFinding {
+    /// Location of the data: a path of tags (e.g. `0008,1110[0].0008,0090`), or a byte offset in
+    /// a file (e.g. `@1042`)
+    pub location: String,
+    /// Name of the tag, if known
+    pub tag: Option<String>,
+    /// The offending value
+    pub value: String,
+    pub reason: PhiReason,
+}
+
+/// Result of a PHI check
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct PhiReport {
+    pub findings: Vec<PhiFinding>,
+}
+
+impl PhiReport {
+    /// Whether no identifying data has been found
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+}
+
+/// Checker of anonymized data
+#[derive(Debug, Clone)]
+pub struct PhiChecker {
+    tags: Vec<String>,
+    originals: Vec<String>,
+    allow_private_tags: bool,
+}
+
+impl Default for PhiChecker {
+    fn default() -> Self {
+        PhiChecker {
+            tags: DEFAULT_PHI_TAGS.iter().map(|t| t.to_string()).collect(),
+            originals: vec![],
+            allow_private_tags: false,
+        }
+    }
+}
+
+impl PhiChecker {
+    /// Creates a new checker with the [`DEFAULT_PHI_TAGS`]
+    pub fn new() -> PhiChecker {
+        PhiChecker::default()
+    }
+
+    /// Replace the list of PHI tags
+    pub fn tags<T: Into<String>>(
+        mut self,
+        tags: impl IntoIterator<Item = T>,
+    ) -> PhiChecker {
+        self.tags = tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Add a tag to the list of PHI tags
+    pub fn tag(mut self, tag: impl Into<String>) -> PhiChecker {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Add an original identifying value to look for
+    pub fn original(mut self, value: impl Into<String>) -> PhiChecker {
+        let value = value.into().trim().to_string();
+        if value.len() >= MIN_ORIGINAL_LEN && !self.originals.contains(&value) {
+            self.originals.push(value);
+        }
+        self
+    }
+
+    /// Collect the original identifying values from the tags of the original instance (in the
+    /// format of [`Client::instance_tags_expanded`])
+    pub fn originals_from_tags(mut self, tags: &Value) -> PhiChecker {
+        let mut values = vec![];
+        let names: Vec<&str> = self
+            .tags
+            .iter()
+            .map(AsRef::as_ref)
+            .chain(IDENTIFYING_TAGS.iter().copied())
+            .collect();
+        collect_values(tags, &names, &mut values);
+        for v in values {
+            self = self.original(v);
+        }
+        self
+    }
+
+    /// Do not report private tags
+    pub fn allow_private_tags(mut self, allow: bool) -> PhiChecker {
+        self.allow_private_tags = allow;
+        self
+    }
+
+    /// Check the tags of an anonymized instance (in the format of
+    /// [`Client::instance_tags_expanded`])
+    pub fn check_tags(&self, tags: &Value) -> PhiReport {
+        let mut report = PhiReport::default();
+        self.check_dataset(tags, "", &mut report);
+        report
+    }
+
+    /// Check an anonymized instance, stored in Orthanc
+    pub fn check_instance(&self, client: &Client, id: &str) -> Result<PhiReport> {
+        Ok(self.check_tags(&client.instance_tags_expanded(id)?))
+    }
+
+    /// Search the raw bytes of an anonymized DICOM file for the original values
+    ///
+    /// Unlike [`PhiChecker::check_tags`], this also finds values in places, that are not exposed
+    /// as tags, e.g. in binary data, but it cannot check PHI or private tags.
+    ///
+    /// Like [`PhiChecker::check_tags`], the search ignores the case. The encoding of the file is
+    /// unknown though, so only the case of ASCII letters is ignored, while `check_tags` compares
+    /// the decoded values, ignoring the case of all letters.
+    pub fn check_file(&self, data: &[u8]) -> PhiReport {
+        let mut report = PhiReport::default();
+        for original in &self.originals {
+            let needle = original.as_bytes();
+            let mut start = 0;
+            while let Some(pos) = find(&data[start..], needle) {
+                report.findings.push(PhiFinding {
+                    location: format!("@{}", start + pos),
+                    tag: None,
+                    value: original.clone(),
+                    reason: PhiReason::OriginalValue(original.clone()),
+                });
+                start += pos + needle.len();
+            }
+        }
+        report
+    }
+
+    fn check_dataset(&self, dataset: &Value, prefix: &str, report: &mut PhiReport) {
+        let elements = match dataset.as_object() {
+            Some(e) => e,
+            None => return,
+        };
+        for (tag, element) in elements {
+            let location = format!("{}{}", prefix, tag);
+            let name = element["Name"].as_str().map(|n| n.to_string());
+            let unverifiable = |kind: &str| PhiFinding {
+                location: location.clone(),
+                tag: name.clone(),
+                value: String::new(),
+                reason: PhiReason::Unverifiable(kind.to_string()),
+            };
+            match (element["Type"].as_str(), &element["Value"]) {
+                (_, Value::Array(items)) => {
+                    for (i, item) in items.iter().enumerate() {
+                        self.check_dataset(item, &format!("{}[{}].", location, i), report);
+                    }
+                }
+                // A value, that is too long, may contain any of the original values
+                (Some(kind @ "TooLong"), _) => report.findings.push(unverifiable(kind)),
+                // Most of the binary values are not PHI (e.g. lookup tables), so they are only
+                // reported for the tags, that must not have a value at all
+                (Some(kind @ "Binary"), _) | (Some(kind @ "Null"), _)
+                    if self.is_phi_tag(&name)
+                        || (!self.allow_private_tags && is_private(tag)) =>
+                {
+                    report.findings.push(unverifiable(kind));
+                }
+                (Some("Binary"), _) | (Some("Null"), _) => (),
+                (_, Value::String(value)) if !value.trim().is_empty() => {
+                    let finding = |reason| PhiFinding {
+                        location: location.clone(),
+                        tag: name.clone(),
+                        value: value.clone(),
+                        reason,
+                    };
+                    if let Some(original) =
+                        self.originals.iter().find(|o| contains(value, o))
+                    {
+                        report
+                            .findings
+                            .push(finding(PhiReason::OriginalValue(original.clone())));
+                    } else if self.is_phi_tag(&name) {
+                        report.findings.push(finding(PhiReason::PhiTag));
+                    } else if !self.allow_private_tags && is_private(tag) {
+                        report.findings.push(finding(PhiReason::PrivateTag));
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn is_phi_tag(&self, name: &Option<String>) -> bool {
+        name.as_ref()
+            .map(|n| self.tags.contains(n))
+            .unwrap_or(false)
+    }
+}
+
+/// Whether the tag (`gggg,eeee`) is private, i.e. its group number is odd
+fn is_private(tag: &str) -> bool {
+    u16::from_str_radix(tag.split(',').next().unwrap_or_default(), 16)
+        .map(|group| group % 2 == 1)
+        .unwrap_or(false)
+}
+
+fn contains(value: &str, original: &str) -> bool {
+    value.to_lowercase().contains(&original.to_lowercase())
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|w| w.eq_ignore_ascii_case(needle))
+}
+
+fn collect_values(dataset: &Value, names: &[&str], values: &mut Vec<String>) {
+    if let Some(elements) = dataset.as_object() {
+        for element in elements.values() {
+            match &element["Value"] {
+                Value::Array(items) => {
+                    for item in items {
+                        collect_values(item, names, values);
+                    }
+                }
+                Value::String(value)
+                    if element["Name"]
+                        .as_str()
+                        .map(|n| names.contains(&n))
+                        .unwrap_or(false) =>
+                {
+                    values.push(value.clone());
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn original() -> Value {
+        json!({
+            "0010,0010": {"Name": "PatientName", "Type": "String", "Value": "Sanchez^Rick"},
+            "0010,0020": {"Name": "PatientID", "Type": "String", "Value": "c137"},
+            "0010,0040": {"Name": "PatientSex", "Type": "String", "Value": "M"},
+            "0008,0080": {"Name": "InstitutionName", "Type": "String", "Value": "Citadel"},
+        })
+    }
+
+    #[test]
+    fn test_check_tags_clean() {
+        let checker = PhiChecker::new().originals_from_tags(&original());
+        let anonymized = json!({
+            "0010,0010": {"Name": "PatientName", "Type": "String", "Value": "Anonymized1"},
+            "0010,0020": {"Name": "PatientID", "Type": "String", "Value": "ANON-1"},
+            "0010,0040": {"Name": "PatientSex", "Type": "String", "Value": "M"},
+            "0008,0080": {"Name": "InstitutionName", "Type": "String", "Value": ""},
+            "0010,0030": {"Name": "PatientBirthDate", "Type": "String", "Value": ""},
+            "0028,1201": {"Name": "RedPaletteColorLookupTableData", "Type": "Null", "Value": null},
+            "0028,3006": {"Name": "LUTData", "Type": "Binary", "Value": "data:application/octet-stream;base64,AAA="},
+        });
+        assert!(checker.check_tags(&anonymized).is_clean());
+    }
+
+    #[test]
+    fn test_check_tags_unverifiable() {
+        let checker = PhiChecker::new().originals_from_tags(&original());
+        let anonymized = json!({
+            "0008,1030": {"Name": "StudyDescription", "Type": "TooLong", "Value": null},
+            "0009,1001": {"Name": "Unknown Tag & Data", "Type": "Binary", "Value": null},
+            "0010,0030": {"Name": "PatientBirthDate", "Type": "Null", "Value": null},
+            "0028,1201": {"Name": "RedPaletteColorLookupTableData", "Type": "Null", "Value": null},
+        });
+        let unverifiable = |location: &str, tag: &str, kind: &str| PhiFinding {
+            location: location.to_string(),
+            tag: Some(tag.to_string()),
+            value: "".to_string(),
+            reason: PhiReason::Unverifiable(kind.to_string()),
+        };
+
+        assert_eq!(
+            checker.check_tags(&anonymized).findings,
+            vec![
+                unverifiable("0008,1030", "StudyDescription", "TooLong"),
+                unverifiable("0009,1001", "Unknown Tag & Data", "Binary"),
+                unverifiable("0010,0030", "PatientBirthDate", "Null"),
+            ]
+        );
+        assert_eq!(
+            checker
+                .allow_private_tags(true)
+                .check_tags(&anonymized)
+                .findings
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_check_tags() {
+        let checker = PhiChecker::new().originals_from_tags(&original());
+        let anonymized = json!({
+            "0010,0010": {"Name": "PatientName", "Type": "String", "Value": "Anonymized1"},
+            "0010,0030": {"Name": "PatientBirthDate", "Type": "String", "Value": "19440101"},
+            "0008,1110": {
+                "Name": "ReferencedStudySequence",
+                "Type": "Sequence",
+                "Value": [
+                    {},
+                    {
+                        "0008,1030": {
+                            "Name": "StudyDescription",
+                            "Type": "String",
+                            "Value": "Brain of RICK SANCHEZ^RICK"
+                        }
+                    }
+                ]
+            },
+            "0009,1001": {"Name": "Unknown Tag & Data", "Type": "String", "Value": "foo"},
+        });
+
+        assert_eq!(
+            checker.check_tags(&anonymized).findings,
+            vec![
+                PhiFinding {
+                    location: "0008,1110[1].0008,1030".to_string(),
+                    tag: Some("StudyDescription".to_string()),
+                    value: "Brain of RICK SANCHEZ^RICK".to_string(),
+                    reason: PhiReason::OriginalValue("Sanchez^Rick".to_string()),
+                },
+                PhiFinding {
+                    location: "0009,1001".to_string(),
+                    tag: Some("Unknown Tag & Data".to_string()),
+                    value: "foo".to_string(),
+                    reason: PhiReason::PrivateTag,
+                },
+                PhiFinding {
+                    location: "0010,0030".to_string(),
+                    tag: Some("PatientBirthDate".to_string()),
+                    value: "19440101".to_string(),
+                    reason: PhiReason::PhiTag,
+                },
+            ]
+        );
+        assert_eq!(
+            checker
+                .allow_private_tags(true)
+                .check_tags(&anonymized)
+                .findings
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_custom_tags() {
+        let checker = PhiChecker::new().tags(vec!["StudyDescription"]).tag("Foo");
+        let anonymized = json!({
+            "0008,1030": {"Name": "StudyDescription", "Type": "String", "Value": "Brain"},
+            "0010,0030": {"Name": "PatientBirthDate", "Type": "String", "Value": "19440101"},
+        });
+        let findings = checker.check_tags(&anonymized).findings;
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].tag, Some("StudyDescription".to_string()));
+    }
+
+    #[test]
+    fn test_check_file() {
+        let checker = PhiChecker::new()
+            .original("c137")
+            .original("M")
+            .original("c137");
+        let report = checker.check_file(b"foo c137 bar C137");
+        assert_eq!(
+            report.findings,
+            vec![
+                PhiFinding {
+                    location: "@4".to_string(),
+                    tag: None,
+                    value: "c137".to_string(),
+                    reason: PhiReason::OriginalValue("c137".to_string()),
+                },
+                PhiFinding {
+                    location: "@13".to_string(),
+                    tag: None,
+                    value: "c137".to_string(),
+                    reason: PhiReason::OriginalValue("c137".to_string()),
+                },
+            ]
+        );
+        assert!(checker.check_file(b"foobar").is_clean());
+    }
+
+    #[test]
+    fn test_is_private() {
+        assert!(is_private("0009,1001"));
+        assert!(!is_private("0010,0010"));
+        assert!(!is_private("foo"));
+    }
+}
@@ -0,0 +1,235 @@
+//! Progress reporting for uploads and downloads
+//!
+//! A progress observer can be attached to a client with
+//! [`Client::progress`](crate::Client::progress). It is then notified about every chunk of data,
+//! sent to or received from Orthanc by the streaming operations (`*_dicom`, `*_media`,
+//! `anonymize_instance`, `modify_instance`, archive creation and extraction, uploads).
+//!
+//! Every notification carries the [`Transfer`] it belongs to, so that transfers running
+//! concurrently (e.g. the uploads of
+//! [`Client::import_directory`](crate::Client::import_directory)) can be told apart:
+//!
+//! ```
+//! let client = Client::new("http://localhost:8042").progress(|t: &Transfer| {
+//!     match t.total {
+//!         Some(total) => println!("#{} {}: {}/{} bytes", t.id, t.path, t.transferred, total),
+//!         None => println!("#{} {}: {} bytes", t.id, t.path, t.transferred),
+//!     }
+//! });
+//! ```
+use std::fmt;
+use std::io::prelude::*;
+use std::io::Result;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+static NEXT_TRANSFER_ID: AtomicU64 = AtomicU64::new(1);
+
+/// State of a single upload or download
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Transfer {
+    /// Identifier of the transfer, unique within the process
+    pub id: u64,
+    /// Path of the Orthanc REST API resource, e.g. `instances/{id}/file`
+    pub path: String,
+    /// Local file, that is being uploaded, if any
+    pub file: Option<PathBuf>,
+    /// Number of bytes transferred so far
+    pub transferred: u64,
+    /// Size of the transferred data, if known (e.g. when Orthanc responds with a
+    /// `Content-Length` header)
+    pub total: Option<u64>,
+}
+
+impl Transfer {
+    pub(crate) fn new(path: &str, total: Option<u64>) -> Transfer {
+        Transfer {
+            id: NEXT_TRANSFER_ID.fetch_add(1, Ordering::Relaxed),
+            path: path.to_string(),
+            file: None,
+            transferred: 0,
+            total,
+        }
+    }
+
+    pub(crate) fn file(mut self, file: impl Into<PathBuf>) -> Transfer {
+        self.file = Some(file.into());
+        self
+    }
+}
+
+/// Observer of the progress of transfers
+///
+/// Implemented for any `Fn(&Transfer)` closure, that can be shared between threads.
+pub trait Progress: Send + Sync {
+    /// Called when a transfer starts and every time a chunk of data has been transferred
+    fn update(&self, transfer: &Transfer);
+}
+
+impl<F: Fn(&Transfer) + Send + Sync> Progress for F {
+    fn update(&self, transfer: &Transfer) {
+        self(transfer)
+    }
+}
+
+/// Shared progress observer of a client
+#[derive(Clone)]
+pub(crate) struct Observer(pub(crate) Arc<dyn Progress>);
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Observer")
+    }
+}
+
+/// A writer that reports the number of bytes written through it
+pub(crate) struct ProgressWriter<W: Write> {
+    writer: W,
+    observer: Option<Observer>,
+    transfer: Transfer,
+}
+
+impl<W: Write> ProgressWriter<W> {
+    pub(crate) fn new(
+        writer: W,
+        observer: Option<Observer>,
+        transfer: Transfer,
+    ) -> ProgressWriter<W> {
+        if let Some(o) = &observer {
+            o.0.update(&transfer);
+        }
+        ProgressWriter {
+            writer,
+            observer,
+            transfer,
+        }
+    }
+}
+
+impl<W: Write> Write for ProgressWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let n = self.writer.write(buf)?;
+        self.transfer.transferred += n as u64;
+        if let Some(o) = &self.observer {
+            o.0.update(&self.transfer);
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// A reader that reports the number of bytes read through it
+pub(crate) struct ProgressReader<R: Read> {
+    reader: R,
+    observer: Option<Observer>,
+    transfer: Transfer,
+}
+
+impl<R: Read> ProgressReader<R> {
+    pub(crate) fn new(
+        reader: R,
+        observer: Option<Observer>,
+        transfer: Transfer,
+    ) -> ProgressReader<R> {
+        if let Some(o) = &observer {
+            o.0.update(&transfer);
+        }
+        ProgressReader {
+            reader,
+            observer,
+            transfer,
+        }
+    }
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let n = self.reader.read(buf)?;
+        if n > 0 {
+            self.transfer.transferred += n as u64;
+            if let Some(o) = &self.observer {
+                o.0.update(&self.transfer);
+            }
+        }
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    type Updates = Arc<Mutex<Vec<(u64, Option<u64>)>>>;
+
+    fn observer() -> (Observer, Updates) {
+        let updates = Arc::new(Mutex::new(vec![]));
+        let u = updates.clone();
+        let observer = Observer(Arc::new(move |t: &Transfer| {
+            u.lock().unwrap().push((t.transferred, t.total))
+        }));
+        (observer, updates)
+    }
+
+    #[test]
+    fn test_transfer() {
+        let first = Transfer::new("instances/foo/file", Some(6));
+        let second = Transfer::new("instances", None).file("/tmp/foo.dcm");
+        assert_ne!(first.id, second.id);
+        assert_eq!(first.path, "instances/foo/file");
+        assert_eq!(first.file, None);
+        assert_eq!(first.transferred, 0);
+        assert_eq!(second.file, Some(PathBuf::from("/tmp/foo.dcm")));
+    }
+
+    #[test]
+    fn test_progress_writer() {
+        let (observer, updates) = observer();
+        let mut data = vec![];
+        let mut writer =
+            ProgressWriter::new(&mut data, Some(observer), Transfer::new("foo", Some(6)));
+        writer.write_all(b"foo").unwrap();
+        writer.write_all(b"bar").unwrap();
+
+        assert_eq!(data, b"foobar");
+        assert_eq!(
+            *updates.lock().unwrap(),
+            vec![(0, Some(6)), (3, Some(6)), (6, Some(6))]
+        );
+    }
+
+    #[test]
+    fn test_progress_reader() {
+        let (observer, updates) = observer();
+        let mut reader =
+            ProgressReader::new(&b"foobar"[..], Some(observer), Transfer::new("foo", None));
+        let mut buf = [0; 4];
+        reader.read_exact(&mut buf).unwrap();
+        let mut rest = vec![];
+        reader.read_to_end(&mut rest).unwrap();
+
+        assert_eq!(rest, b"ar");
+        assert_eq!(
+            *updates.lock().unwrap(),
+            vec![(0, None), (4, None), (6, None)]
+        );
+    }
+
+    #[test]
+    fn test_no_observer() {
+        let mut data = vec![];
+        let mut writer = ProgressWriter::new(&mut data, None, Transfer::new("foo", None));
+        writer.write_all(b"foo").unwrap();
+        assert_eq!(data, b"foo");
+    }
+
+    #[test]
+    fn test_observer_debug() {
+        let (observer, _) = observer();
+        assert_eq!(format!("{:?}", observer), "Observer");
+    }
+}
@@ -0,0 +1,706 @@
+//! Consistent pseudonymization of patients and studies
+//!
+//! By default every anonymization generates new random identifiers, so anonymizing two studies of
+//! the same patient results in two different anonymized patients. A [`Pseudonymizer`] maintains a
+//! mapping from the original identifiers (`PatientID`, `StudyInstanceUID`, `SeriesInstanceUID`)
+//! to pseudonyms in a [`PseudonymStore`], and injects the pseudonyms into every anonymization
+//! request, so the same original value is always replaced with the same pseudonym.
+//!
+//! Orthanc can only replace a tag with a single value for the whole anonymized entity, so patients
+//! and studies are anonymized series by series, each series with the pseudonyms of its own UIDs:
+//!
+//! ```
+//! let client = Client::new("http://localhost:8042");
+//! let store = JsonFileStore::open("/srv/pseudonyms.jsonl").unwrap();
+//! let mut pseudonymizer = Pseudonymizer::new(store);
+//! for id in client.studies().unwrap() {
+//!     pseudonymizer.anonymize_study(&client, &id, None).unwrap();
+//! }
+//! pseudonymizer.export_csv(fs::File::create("/srv/pseudonyms.csv").unwrap()).unwrap();
+//! ```
+use crate::entity::{Entity, EntityKind, Series};
+use crate::ids;
+use crate::models::{Anonymization, ModificationResult};
+use crate::{Client, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::prelude::*;
+use std::path::Path;
+use uuid::Uuid;
+
+/// A single mapping of an original value of a tag to its pseudonym
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct PseudonymEntry {
+    pub tag: String,
+    pub original: String,
+    pub pseudonym: String,
+}
+
+/// Storage of pseudonyms
+pub trait PseudonymStore {
+    /// Get the pseudonym of the `original` value of `tag`, if any
+    fn get(&self, tag: &str, original: &str) -> Result<Option<String>>;
+    /// Store a new pseudonym
+    fn insert(&mut self, entry: PseudonymEntry) -> Result<()>;
+    /// All stored pseudonyms, sorted by tag and original value
+    fn entries(&self) -> Result<Vec<PseudonymEntry>>;
+    /// Whether `pseudonym` is already assigned to an original value of any tag
+    ///
+    /// The default implementation scans all the entries. Stores should override it with a faster
+    /// lookup.
+    fn contains_pseudonym(&self, pseudonym: &str) -> Result<bool> {
+        Ok(self.entries()?.iter().any(|e| e.pseudonym == pseudonym))
+    }
+}
+
+/// In-memory pseudonym store. The mapping is lost, when the store is dropped
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    pseudonyms: BTreeMap<(String, String), String>,
+    assigned: HashSet<String>,
+}
+
+impl MemoryStore {
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+}
+
+impl PseudonymStore for MemoryStore {
+    fn get(&self, tag: &str, original: &str) -> Result<Option<String>> {
+        Ok(self
+            .pseudonyms
+            .get(&(tag.to_string(), original.to_string()))
+            .cloned())
+    }
+
+    fn insert(&mut self, entry: PseudonymEntry) -> Result<()> {
+        self.assigned.insert(entry.pseudonym.clone());
+        let pseudonym = entry.pseudonym.clone();
+        if let Some(previous) = self
+            .pseudonyms
+            .insert((entry.tag, entry.original), entry.pseudonym)
+        {
+            if previous != pseudonym {
+                self.assigned.remove(&previous);
+            }
+        }
+        Ok(())
+    }
+
+    fn entries(&self) -> Result<Vec<PseudonymEntry>> {
+        Ok(self
+            .pseudonyms
+            .iter()
+            .map(|((tag, original), pseudonym)| PseudonymEntry {
+                tag: tag.clone(),
+                original: original.clone(),
+                pseudonym: pseudonym.clone(),
+            })
+            .collect())
+    }
+
+    fn contains_pseudonym(&self, pseudonym: &str) -> Result<bool> {
+        Ok(self.assigned.contains(pseudonym))
+    }
+}
+
+/// Pseudonym store, persisted in a JSON Lines file
+///
+/// Every stored pseudonym is appended to the file as a line with a JSON object, so storing a
+/// pseudonym does not rewrite the whole file. If an original value is stored several times, the
+/// last line wins.
+///
+/// Every line is terminated with a newline. A last line without one has been cut off by a crash
+/// while it was being written, before its pseudonym could be used, so it is dropped when the
+/// store is opened.
+#[derive(Debug)]
+pub struct JsonFileStore {
+    file: fs::File,
+    store: MemoryStore,
+}
+
+impl JsonFileStore {
+    /// Open the store, loading the existing pseudonyms from `path`, if the file exists
+    ///
+    /// Fails if any of the complete lines is not a valid entry.
+    pub fn open(path: impl AsRef<Path>) -> Result<JsonFileStore> {
+        let path = path.as_ref();
+        let mut store = MemoryStore::new();
+        let data = if path.exists() {
+            fs::read(path)?
+        } else {
+            vec![]
+        };
+        let complete = data.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+        for line in std::str::from_utf8(&data[..complete])?.lines() {
+            if !line.trim().is_empty() {
+                store.insert(serde_json::from_str(line)?)?;
+            }
+        }
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        if complete < data.len() {
+            // Drop the cut off line, so that the next entry is not appended to it
+            file.set_len(complete as u64)?;
+        }
+        Ok(JsonFileStore { file, store })
+    }
+}
+
+impl PseudonymStore for JsonFileStore {
+    fn get(&self, tag: &str, original: &str) -> Result<Option<String>> {
+        self.store.get(tag, original)
+    }
+
+    fn insert(&mut self, entry: PseudonymEntry) -> Result<()> {
+        // Persist first, so that the memory never holds a pseudonym, that is not in the file
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.store.insert(entry)
+    }
+
+    fn entries(&self) -> Result<Vec<PseudonymEntry>> {
+        self.store.entries()
+    }
+
+    fn contains_pseudonym(&self, pseudonym: &str) -> Result<bool> {
+        self.store.contains_pseudonym(pseudonym)
+    }
+}
+
+/// Tags, that are set by the [`Pseudonymizer`] and must not be part of a passed anonymization
+const PSEUDONYMIZED_TAGS: [&str; 4] = [
+    "PatientID",
+    "PatientName",
+    "StudyInstanceUID",
+    "SeriesInstanceUID",
+];
+
+/// Number of random pseudonyms, that are tried, before giving up on finding a unique one
+const MAX_ATTEMPTS: usize = 16;
+
+/// Anonymizes entities, replacing their identifiers with consistent pseudonyms
+///
+/// `PatientID` (as well as `PatientName`) is replaced with a pseudonym, built of a prefix and a
+/// random part, e.g. `ANON-3F2504E04F8911D3`. `StudyInstanceUID` and `SeriesInstanceUID` are
+/// replaced with random UUID-derived UIDs (`2.25.<uuid>`). `SOPInstanceUID`s are regenerated by
+/// Orthanc as usual. A newly generated pseudonym is never one, that is already in the store.
+///
+/// The anonymization, passed to the `anonymize_*` methods, must not replace, keep or remove the
+/// pseudonymized tags (`PatientID`, `PatientName`, `StudyInstanceUID`, `SeriesInstanceUID`), nor
+/// disable `Force`, which Orthanc requires to replace the UIDs. Such an anonymization is rejected
+/// with an error.
+#[derive(Debug)]
+pub struct Pseudonymizer<S: PseudonymStore> {
+    store: S,
+    prefix: String,
+}
+
+impl<S: PseudonymStore> Pseudonymizer<S> {
+    /// Creates a new pseudonymizer with the `ANON-` prefix of patient pseudonyms
+    pub fn new(store: S) -> Pseudonymizer<S> {
+        Pseudonymizer {
+            store,
+            prefix: "ANON-".to_string(),
+        }
+    }
+
+    /// Set the prefix of patient pseudonyms
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Pseudonymizer<S> {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// The underlying store
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Get the pseudonym of the `original` value of `tag`, generating and storing a new one, if
+    /// there is none yet
+    pub fn pseudonym(&mut self, tag: &str, original: &str) -> Result<String> {
+        if let Some(p) = self.store.get(tag, original)? {
+            return Ok(p);
+        }
+        let pseudonym = self.unique_pseudonym(
+            tag,
+            std::iter::repeat_with(Uuid::new_v4).take(MAX_ATTEMPTS),
+        )?;
+        self.store.insert(PseudonymEntry {
+            tag: tag.to_string(),
+            original: original.to_string(),
+            pseudonym: pseudonym.clone(),
+        })?;
+        Ok(pseudonym)
+    }
+
+    /// Anonymize a patient, replacing its `PatientID` and `PatientName` with a pseudonym
+    ///
+    /// The studies of the patient are anonymized one by one with
+    /// [`anonymize_study`](Pseudonymizer::anonymize_study), so their UIDs are replaced with the
+    /// same pseudonyms as when they are anonymized on their own.
+    pub fn anonymize_patient(
+        &mut self,
+        client: &Client,
+        id: &str,
+        anonymization: Option<Anonymization>,
+    ) -> Result<ModificationResult> {
+        let patient = client.patient(id)?;
+        let patient_id =
+            self.pseudonym("PatientID", &required_tag(&patient, "PatientID")?)?;
+        for study in &patient.studies {
+            self.anonymize_study(client, study, anonymization.clone())?;
+        }
+        let id = ids::patient_id(&patient_id);
+        Ok(ModificationResult {
+            path: format!("/patients/{}", id),
+            id: id.to_string(),
+            patient_id: id.to_string(),
+            entity: EntityKind::Patient,
+        })
+    }
+
+    /// Anonymize a study, replacing its `PatientID`, `PatientName` and `StudyInstanceUID` with
+    /// pseudonyms
+    ///
+    /// The series of the study are anonymized one by one, so their `SeriesInstanceUID`s are
+    /// replaced with the same pseudonyms as when they are anonymized on their own.
+    pub fn anonymize_study(
+        &mut self,
+        client: &Client,
+        id: &str,
+        anonymization: Option<Anonymization>,
+    ) -> Result<ModificationResult> {
+        let study = client.study(id)?;
+        let patient_id = required_tag(&study, "PatientID")?;
+        let study_uid = required_tag(&study, "StudyInstanceUID")?;
+        for series in client.study_series(id)? {
+            self.anonymize_series_of(
+                client,
+                &series,
+                &patient_id,
+                &study_uid,
+                anonymization.clone(),
+            )?;
+        }
+        let patient_id = self.pseudonym("PatientID", &patient_id)?;
+        let id = ids::study_id(
+            &patient_id,
+            &self.pseudonym("StudyInstanceUID", &study_uid)?,
+        );
+        Ok(ModificationResult {
+            path: format!("/studies/{}", id),
+            id: id.to_string(),
+            patient_id: ids::patient_id(&patient_id).to_string(),
+            entity: EntityKind::Study,
+        })
+    }
+
+    /// Anonymize a series, replacing its `PatientID`, `PatientName`, `StudyInstanceUID` and
+    /// `SeriesInstanceUID` with pseudonyms
+    pub fn anonymize_series(
+        &mut self,
+        client: &Client,
+        id: &str,
+        anonymization: Option<Anonymization>,
+    ) -> Result<ModificationResult> {
+        let series = client.series(id)?;
+        let study = client.study(&series.parent_study)?;
+        self.anonymize_series_of(
+            client,
+            &series,
+            &required_tag(&study, "PatientID")?,
+            &required_tag(&study, "StudyInstanceUID")?,
+            anonymization,
+        )
+    }
+
+    /// Export the mapping table as CSV with `Tag,Original,Pseudonym` columns
+    pub fn export_csv<W: Write>(&self, mut writer: W) -> Result<()> {
+        writeln!(writer, "Tag,Original,Pseudonym")?;
+        for entry in self.store.entries()? {
+            writeln!(
+                writer,
+                "{},{},{}",
+                csv_field(&entry.tag),
+                csv_field(&entry.original),
+                csv_field(&entry.pseudonym)
+            )?;
+        }
+        Ok(())
+    }
+
+    fn anonymize_series_of(
+        &mut self,
+        client: &Client,
+        series: &Series,
+        patient_id: &str,
+        study_uid: &str,
+        anonymization: Option<Anonymization>,
+    ) -> Result<ModificationResult> {
+        let anonymization = self.inject(
+            anonymization,
+            &[
+                ("PatientID", patient_id.to_string()),
+                ("StudyInstanceUID", study_uid.to_string()),
+                (
+                    "SeriesInstanceUID",
+                    required_tag(series, "SeriesInstanceUID")?,
+                ),
+            ],
+        )?;
+        client.anonymize_series(&series.id, Some(anonymization))
+    }
+
+    /// Generate a pseudonym of `tag` out of the first of `uuids`, that gives a pseudonym, which
+    /// is not in the store yet
+    fn unique_pseudonym(
+        &self,
+        tag: &str,
+        uuids: impl IntoIterator<Item = Uuid>,
+    ) -> Result<String> {
+        for uuid in uuids {
+            let pseudonym = match tag {
+                "PatientID" => format!(
+                    "{}{}",
+                    self.prefix,
+                    &uuid.to_simple().to_string().to_uppercase()[..16]
+                ),
+                _ => format!("2.25.{}", uuid.as_u128()),
+            };
+            if !self.store.contains_pseudonym(&pseudonym)? {
+                return Ok(pseudonym);
+            }
+        }
+        Err(Error::new(
+            &format!("Could not generate a unique pseudonym of {}", tag),
+            None,
+        ))
+    }
+
+    fn inject(
+        &mut self,
+        anonymization: Option<Anonymization>,
+        tags: &[(&str, String)],
+    ) -> Result<Anonymization> {
+        let mut anonymization = anonymization.unwrap_or_default();
+        if let Some(tag) = anonymization
+            .replace
+            .iter()
+            .flat_map(|r| r.keys())
+            .chain(anonymization.keep.iter().flatten())
+            .chain(anonymization.remove.iter().flatten())
+            .find(|t| PSEUDONYMIZED_TAGS.contains(&t.as_str()))
+        {
+            return Err(Error::new(
+                &format!("Tag {} is set by the pseudonymizer", tag),
+                None,
+            ));
+        }
+        if anonymization.force == Some(false) && tags.iter().any(|(t, _)| *t != "PatientID")
+        {
+            return Err(Error::new(
+                "Replacing UIDs with pseudonyms requires Force",
+                None,
+            ));
+        }
+        for (tag, original) in tags {
+            let pseudonym = self.pseudonym(tag, original)?;
+            let replace = anonymization.replace.get_or_insert_with(Default::default);
+            if *tag == "PatientID" {
+                replace.insert("PatientName".to_string(), pseudonym.clone().into());
+            } else {
+                // Orthanc only allows replacing UIDs with `Force`
+                anonymization.force = Some(true);
+            }
+            replace.insert(tag.to_string(), pseudonym.into());
+        }
+        Ok(anonymization)
+    }
+}
+
+fn required_tag<E: Entity>(entity: &E, tag: &str) -> Result<String> {
+    match entity.main_dicom_tag(tag) {
+        Some(v) => Ok(v.to_string()),
+        None => Err(Error::new(
+            &format!("{:?} {} has no {}", E::kind(), entity.id(), tag),
+            None,
+        )),
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pseudonym_consistent() {
+        let mut p = Pseudonymizer::new(MemoryStore::new()).prefix("SUBJ-");
+        let patient = p.pseudonym("PatientID", "patient_1").unwrap();
+        let study = p.pseudonym("StudyInstanceUID", "1.2.3").unwrap();
+
+        assert!(patient.starts_with("SUBJ-"));
+        assert_eq!(patient.len(), 21);
+        assert!(study.starts_with("2.25."));
+        assert!(study.len() <= 64);
+        assert_eq!(p.pseudonym("PatientID", "patient_1").unwrap(), patient);
+        assert_eq!(p.pseudonym("StudyInstanceUID", "1.2.3").unwrap(), study);
+        assert_ne!(p.pseudonym("PatientID", "patient_2").unwrap(), patient);
+        assert_eq!(p.store().entries().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_inject() {
+        let mut p = Pseudonymizer::new(MemoryStore::new());
+        let a = p
+            .inject(
+                Some(Anonymization {
+                    keep: Some(vec!["StudyDate".to_string()]),
+                    ..Default::default()
+                }),
+                &[
+                    ("PatientID", "patient_1".to_string()),
+                    ("StudyInstanceUID", "1.2.3".to_string()),
+                ],
+            )
+            .unwrap();
+        let replace = a.replace.unwrap();
+
+        assert_eq!(
+            replace["PatientID"],
+            p.pseudonym("PatientID", "patient_1").unwrap()
+        );
+        assert_eq!(replace["PatientName"], replace["PatientID"]);
+        assert_eq!(
+            replace["StudyInstanceUID"],
+            p.pseudonym("StudyInstanceUID", "1.2.3").unwrap()
+        );
+        assert_eq!(a.keep, Some(vec!["StudyDate".to_string()]));
+        assert_eq!(a.force, Some(true));
+    }
+
+    #[test]
+    fn test_inject_conflicts() {
+        let mut p = Pseudonymizer::new(MemoryStore::new());
+        let tags = [
+            ("PatientID", "patient_1".to_string()),
+            ("StudyInstanceUID", "1.2.3".to_string()),
+        ];
+        assert_eq!(
+            p.inject(
+                Some(Anonymization {
+                    replace: Some(
+                        vec![("PatientName".to_string(), "Rick Sanchez".into())]
+                            .into_iter()
+                            .collect()
+                    ),
+                    ..Default::default()
+                }),
+                &tags,
+            )
+            .unwrap_err(),
+            Error::new("Tag PatientName is set by the pseudonymizer", None)
+        );
+        assert_eq!(
+            p.inject(
+                Some(Anonymization {
+                    keep: Some(vec!["StudyInstanceUID".to_string()]),
+                    ..Default::default()
+                }),
+                &tags,
+            )
+            .unwrap_err(),
+            Error::new("Tag StudyInstanceUID is set by the pseudonymizer", None)
+        );
+        assert_eq!(
+            p.inject(
+                Some(Anonymization {
+                    force: Some(false),
+                    ..Default::default()
+                }),
+                &tags,
+            )
+            .unwrap_err(),
+            Error::new("Replacing UIDs with pseudonyms requires Force", None)
+        );
+        // Nothing is stored for a rejected anonymization
+        assert!(p.store().entries().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unique_pseudonym() {
+        let taken = Uuid::from_u128(42);
+        let free = Uuid::from_u128(17);
+        let mut p = Pseudonymizer::new(MemoryStore::new());
+        p.store
+            .insert(PseudonymEntry {
+                tag: "SeriesInstanceUID".to_string(),
+                original: "1.2.3".to_string(),
+                pseudonym: "2.25.42".to_string(),
+            })
+            .unwrap();
+
+        assert_eq!(
+            p.unique_pseudonym("StudyInstanceUID", vec![taken, free])
+                .unwrap(),
+            "2.25.17"
+        );
+        assert_eq!(
+            p.unique_pseudonym("StudyInstanceUID", vec![taken, taken])
+                .unwrap_err(),
+            Error::new(
+                "Could not generate a unique pseudonym of StudyInstanceUID",
+                None
+            )
+        );
+    }
+
+    #[test]
+    fn test_memory_store_contains_pseudonym() {
+        let mut store = MemoryStore::new();
+        for pseudonym in &["ANON-1", "ANON-2"] {
+            store
+                .insert(PseudonymEntry {
+                    tag: "PatientID".to_string(),
+                    original: "patient_1".to_string(),
+                    pseudonym: pseudonym.to_string(),
+                })
+                .unwrap();
+        }
+
+        assert!(store.contains_pseudonym("ANON-2").unwrap());
+        assert!(!store.contains_pseudonym("ANON-1").unwrap());
+
+        // Storing the same mapping again keeps its pseudonym assigned
+        store
+            .insert(PseudonymEntry {
+                tag: "PatientID".to_string(),
+                original: "patient_1".to_string(),
+                pseudonym: "ANON-2".to_string(),
+            })
+            .unwrap();
+        assert!(store.contains_pseudonym("ANON-2").unwrap());
+    }
+
+    #[test]
+    fn test_json_file_store() {
+        let path = std::env::temp_dir().join("orthanc_test_json_file_store.jsonl");
+        let _ = fs::remove_file(&path);
+
+        let mut store = JsonFileStore::open(&path).unwrap();
+        store
+            .insert(PseudonymEntry {
+                tag: "PatientID".to_string(),
+                original: "patient_1".to_string(),
+                pseudonym: "ANON-1".to_string(),
+            })
+            .unwrap();
+        drop(store);
+
+        let mut store = JsonFileStore::open(&path).unwrap();
+        assert_eq!(
+            store.get("PatientID", "patient_1").unwrap(),
+            Some("ANON-1".to_string())
+        );
+        assert_eq!(store.get("PatientID", "patient_2").unwrap(), None);
+        store
+            .insert(PseudonymEntry {
+                tag: "StudyInstanceUID".to_string(),
+                original: "1.2.3".to_string(),
+                pseudonym: "2.25.42".to_string(),
+            })
+            .unwrap();
+
+        // Every pseudonym is appended as a line
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            concat!(
+                r#"{"tag":"PatientID","original":"patient_1","pseudonym":"ANON-1"}"#,
+                "\n",
+                r#"{"tag":"StudyInstanceUID","original":"1.2.3","pseudonym":"2.25.42"}"#,
+                "\n",
+            )
+        );
+        let store = JsonFileStore::open(&path).unwrap();
+        assert_eq!(store.entries().unwrap().len(), 2);
+        assert!(store.contains_pseudonym("2.25.42").unwrap());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_json_file_store_cut_off_line() {
+        let path =
+            std::env::temp_dir().join("orthanc_test_json_file_store_cut_off_line.jsonl");
+        let line = r#"{"tag":"PatientID","original":"patient_1","pseudonym":"ANON-1"}"#;
+        fs::write(&path, format!("{}\n{}", line, &line[..20])).unwrap();
+
+        let mut store = JsonFileStore::open(&path).unwrap();
+        assert_eq!(store.entries().unwrap().len(), 1);
+        assert_eq!(fs::read_to_string(&path).unwrap(), format!("{}\n", line));
+        store
+            .insert(PseudonymEntry {
+                tag: "PatientID".to_string(),
+                original: "patient_2".to_string(),
+                pseudonym: "ANON-2".to_string(),
+            })
+            .unwrap();
+        drop(store);
+
+        let store = JsonFileStore::open(&path).unwrap();
+        assert_eq!(store.entries().unwrap().len(), 2);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_json_file_store_invalid() {
+        let path = std::env::temp_dir().join("orthanc_test_json_file_store_invalid.jsonl");
+        // A complete line is corrupt, so the store can not be trusted
+        let line = r#"{"tag":"PatientID","original":"patient_1","pseudonym":"ANON-1"}"#;
+        fs::write(&path, format!("foobar\n{}\n", line)).unwrap();
+        assert!(JsonFileStore::open(&path).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let mut store = MemoryStore::new();
+        for (tag, original, pseudonym) in &[
+            ("StudyInstanceUID", "1.2.3", "2.25.42"),
+            ("PatientID", "Smith, \"Rick\"", "ANON-2"),
+            ("PatientID", "patient_1", "ANON-1"),
+        ] {
+            store
+                .insert(PseudonymEntry {
+                    tag: tag.to_string(),
+                    original: original.to_string(),
+                    pseudonym: pseudonym.to_string(),
+                })
+                .unwrap();
+        }
+        let mut csv = vec![];
+        Pseudonymizer::new(store).export_csv(&mut csv).unwrap();
+
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            concat!(
+                "Tag,Original,Pseudonym\n",
+                "PatientID,\"Smith, \"\"Rick\"\"\",ANON-2\n",
+                "PatientID,patient_1,ANON-1\n",
+                "StudyInstanceUID,1.2.3,2.25.42\n",
+            )
+        );
+    }
+}
@@ -1,5 +1,10 @@
 use crate::{Error, Result};
 use bytes::Bytes;
+use std::io;
+use std::io::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
 
 pub(crate) fn check_http_error(status: reqwest::StatusCode, body: Bytes) -> Result<Bytes> {
     if status >= reqwest::StatusCode::BAD_REQUEST {
@@ -12,6 +17,89 @@ pub(crate) fn check_http_error(status: reqwest::StatusCode, body: Bytes) -> Resu
     Ok(body)
 }
 
+pub(crate) fn check_http_error_response(
+    resp: reqwest::blocking::Response,
+) -> Result<reqwest::blocking::Response> {
+    let status = resp.status();
+    if status >= reqwest::StatusCode::BAD_REQUEST {
+        let message = format!("API error: {}", status);
+        let body = resp.bytes()?;
+        if body.is_empty() {
+            return Err(Error::new(&message, None));
+        };
+        return Err(Error::new(&message, serde_json::from_slice(&body)?));
+    }
+    Ok(resp)
+}
+
+/// Apply `f` to every item using a pool of `workers` threads
+///
+/// The results are returned in the order of `items`
+pub(crate) fn parallel_map<T, R, F>(items: &[T], workers: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<R>>> = Mutex::new(items.iter().map(|_| None).collect());
+    thread::scope(|scope| {
+        for _ in 0..workers.clamp(1, items.len().max(1)) {
+            scope.spawn(|| loop {
+                let i = next.fetch_add(1, Ordering::SeqCst);
+                if i >= items.len() {
+                    break;
+                }
+                let res = f(&items[i]);
+                results.lock().unwrap()[i] = Some(res);
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|r| r.unwrap())
+        .collect()
+}
+
+/// Check whether the data starts with a DICOM file preamble (128 bytes followed by `DICM`)
+pub(crate) fn is_dicom(data: &[u8]) -> bool {
+    data.len() >= 132 && &data[128..132] == b"DICM"
+}
+
+/// A writer that computes the MD5 digest of the data written through it
+pub(crate) struct Md5Writer<W: Write> {
+    writer: W,
+    context: md5::Context,
+}
+
+impl<W: Write> Md5Writer<W> {
+    pub(crate) fn new(writer: W) -> Md5Writer<W> {
+        Md5Writer {
+            writer,
+            context: md5::Context::new(),
+        }
+    }
+
+    /// Hex-encoded MD5 digest of the data written so far
+    pub(crate) fn digest(self) -> String {
+        format!("{:x}", self.context.compute())
+    }
+}
+
+impl<W: Write> Write for Md5Writer<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.writer.write(buf)?;
+        self.context.consume(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,6 +147,7 @@ mod tests {
                     orthanc_status: 15,
                     orthanc_error: "Bad file format".to_string(),
                 },),
+                integrity: None,
             },
         );
     }
@@ -70,11 +159,46 @@ mod tests {
             res.unwrap_err(),
             Error {
                 message: "API error: 401 Unauthorized".to_string(),
-                details: None
+                details: None,
+                integrity: None,
             },
         );
     }
 
+    #[test]
+    fn test_parallel_map() {
+        let items: Vec<u64> = (0..100).collect();
+        assert_eq!(
+            parallel_map(&items, 4, |i| i * 2),
+            (0..100).map(|i| i * 2).collect::<Vec<u64>>()
+        );
+        assert_eq!(parallel_map(&items[..1], 4, |i| i + 1), vec![1]);
+        assert_eq!(parallel_map(&items, 0, |i| i + 1).len(), 100);
+        assert!(parallel_map(&items[..0], 4, |i| i + 1).is_empty());
+    }
+
+    #[test]
+    fn test_md5_writer() {
+        let mut data = vec![];
+        let mut writer = Md5Writer::new(&mut data);
+        writer.write_all(b"foo").unwrap();
+        writer.write_all(b"bar").unwrap();
+        assert_eq!(writer.digest(), "3858f62230ac3c915f300c664312c63f");
+        assert_eq!(data, b"foobar");
+    }
+
+    #[test]
+    fn test_is_dicom() {
+        let mut data = vec![0; 128];
+        data.extend_from_slice(b"DICM");
+        assert!(is_dicom(&data));
+        data.extend_from_slice(b"foobar");
+        assert!(is_dicom(&data));
+        assert!(!is_dicom(&data[..131]));
+        assert!(!is_dicom(b"DICM"));
+        assert!(!is_dicom(&[b'D'; 140]));
+    }
+
     // TODO: Firgure out how to handle this
     #[test]
     fn test_check_http_error_error_random_body() {
@@ -86,7 +210,8 @@ mod tests {
             res.unwrap_err(),
             Error {
                 message: "expected ident at line 1 column 2".to_string(),
-                details: None
+                details: None,
+                integrity: None,
             },
         );
     }
@@ -0,0 +1,280 @@
+//! Lazy traversal of the Patient → Study → Series → Instance hierarchy
+//!
+//! A [`Walker`] is an iterator, that yields the entities of the whole server (or of the subtree
+//! of a single entity) in depth-first order: every entity is followed by all its descendants,
+//! before its next sibling. Entities are fetched from Orthanc lazily, a few at a time, so only
+//! the IDs of the pending entities and a bounded number of prefetched entities are held in memory:
+//!
+//! ```
+//! let walker = Walker::new(&client)
+//!     .filter_studies(|s| s.main_dicom_tag("ModalitiesInStudy") == Some("MR"))
+//!     .max_level(EntityKind::Series)
+//!     .prefetch(8);
+//! for node in walker {
+//!     match node.unwrap() {
+//!         Node::Study(s) => println!("Study {}", s.id),
+//!         Node::Series(s) => println!("  Series {}", s.id),
+//!         _ => (),
+//!     }
+//! }
+//! ```
+use crate::entity::{Entity, EntityKind, Instance, Patient, Series, Study};
+use crate::utils::parallel_map;
+use crate::{Client, Result};
+use std::collections::VecDeque;
+use std::fmt;
+
+/// An entity, yielded by a [`Walker`]
+#[derive(Debug, Eq, PartialEq)]
+pub enum Node {
+    Patient(Patient),
+    Study(Study),
+    Series(Series),
+    Instance(Instance),
+}
+
+impl Node {
+    /// Kind of the entity
+    pub fn kind(&self) -> EntityKind {
+        match self {
+            Node::Patient(_) => EntityKind::Patient,
+            Node::Study(_) => EntityKind::Study,
+            Node::Series(_) => EntityKind::Series,
+            Node::Instance(_) => EntityKind::Instance,
+        }
+    }
+
+    /// ID of the entity
+    pub fn id(&self) -> &str {
+        match self {
+            Node::Patient(p) => p.id(),
+            Node::Study(s) => s.id(),
+            Node::Series(s) => s.id(),
+            Node::Instance(i) => i.id(),
+        }
+    }
+
+    fn children(&self) -> Vec<String> {
+        match self {
+            Node::Patient(p) => ids(p.children()),
+            Node::Study(s) => ids(s.children()),
+            Node::Series(s) => ids(s.children()),
+            Node::Instance(i) => ids(i.children()),
+        }
+    }
+}
+
+type Filter<'a, T> = Box<dyn Fn(&T) -> bool + 'a>;
+
+/// IDs of the entities of one level, that are still to be visited, along with the entities
+/// already fetched from Orthanc
+struct Level {
+    kind: EntityKind,
+    ids: VecDeque<String>,
+    fetched: VecDeque<Result<Node>>,
+}
+
+impl Level {
+    fn new(kind: EntityKind, ids: Vec<String>) -> Level {
+        Level {
+            kind,
+            ids: ids.into(),
+            fetched: VecDeque::new(),
+        }
+    }
+}
+
+/// Depth-first iterator over Orthanc entities
+///
+/// Yields a [`Result`] for every entity, so a failure to fetch a single entity does not stop the
+/// traversal. The descendants of an entity, that could not be fetched or that has been rejected
+/// by a filter, are not visited.
+pub struct Walker<'a> {
+    client: &'a Client,
+    list_patients: bool,
+    stack: Vec<Level>,
+    max_level: EntityKind,
+    prefetch: usize,
+    patient_filter: Option<Filter<'a, Patient>>,
+    study_filter: Option<Filter<'a, Study>>,
+    series_filter: Option<Filter<'a, Series>>,
+    instance_filter: Option<Filter<'a, Instance>>,
+}
+
+impl fmt::Debug for Walker<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Walker")
+            .field("max_level", &self.max_level)
+            .field("prefetch", &self.prefetch)
+            .finish()
+    }
+}
+
+impl<'a> Walker<'a> {
+    /// Walk all entities of the server, starting with the patients
+    pub fn new(client: &'a Client) -> Walker<'a> {
+        Walker {
+            client,
+            list_patients: true,
+            stack: vec![],
+            max_level: EntityKind::Instance,
+            prefetch: 1,
+            patient_filter: None,
+            study_filter: None,
+            series_filter: None,
+            instance_filter: None,
+        }
+    }
+
+    /// Walk an entity and all its descendants
+    pub fn from_entity(client: &'a Client, kind: EntityKind, id: &str) -> Walker<'a> {
+        let mut walker = Walker::new(client);
+        walker.list_patients = false;
+        walker.stack.push(Level::new(kind, vec![id.to_string()]));
+        walker
+    }
+
+    /// Do not descend below entities of the `kind` level (e.g. [`EntityKind::Series`] to skip
+    /// the instances)
+    pub fn max_level(mut self, kind: EntityKind) -> Self {
+        self.max_level = kind;
+        self
+    }
+
+    /// Number of sibling entities to fetch from Orthanc at once (in parallel). Defaults to 1
+    pub fn prefetch(mut self, prefetch: usize) -> Self {
+        self.prefetch = prefetch.max(1);
+        self
+    }
+
+    /// Only visit the patients (and their descendants), for which `filter` returns `true`
+    pub fn filter_patients(mut self, filter: impl Fn(&Patient) -> bool + 'a) -> Self {
+        self.patient_filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Only visit the studies (and their descendants), for which `filter` returns `true`
+    pub fn filter_studies(mut self, filter: impl Fn(&Study) -> bool + 'a) -> Self {
+        self.study_filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Only visit the series (and their descendants), for which `filter` returns `true`
+    pub fn filter_series(mut self, filter: impl Fn(&Series) -> bool + 'a) -> Self {
+        self.series_filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Only visit the instances, for which `filter` returns `true`
+    pub fn filter_instances(mut self, filter: impl Fn(&Instance) -> bool + 'a) -> Self {
+        self.instance_filter = Some(Box::new(filter));
+        self
+    }
+
+    fn accepts(&self, node: &Node) -> bool {
+        match node {
+            Node::Patient(p) => self.patient_filter.as_ref().map(|f| f(p)).unwrap_or(true),
+            Node::Study(s) => self.study_filter.as_ref().map(|f| f(s)).unwrap_or(true),
+            Node::Series(s) => self.series_filter.as_ref().map(|f| f(s)).unwrap_or(true),
+            Node::Instance(i) => {
+                self.instance_filter.as_ref().map(|f| f(i)).unwrap_or(true)
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Walker<'a> {
+    type Item = Result<Node>;
+
+    fn next(&mut self) -> Option<Result<Node>> {
+        if self.list_patients {
+            self.list_patients = false;
+            match self.client.patients() {
+                Ok(ids) => self
+                    .stack
+                    .push(Level::new(EntityKind::Patient, self::ids(&ids))),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        loop {
+            let level = self.stack.last_mut()?;
+            if level.fetched.is_empty() {
+                if level.ids.is_empty() {
+                    self.stack.pop();
+                    continue;
+                }
+                let n = self.prefetch.min(level.ids.len());
+                let batch: Vec<String> = level.ids.drain(..n).collect();
+                let (client, kind) = (self.client, level.kind);
+                level.fetched =
+                    parallel_map(&batch, n, |id| fetch(client, kind, id)).into();
+            }
+            let node = match level.fetched.pop_front() {
+                Some(Ok(node)) => node,
+                Some(Err(e)) => return Some(Err(e)),
+                None => continue,
+            };
+            if !self.accepts(&node) {
+                continue;
+            }
+            if let Some(child) = child_kind(node.kind()) {
+                if depth(child) <= depth(self.max_level) {
+                    self.stack
+                        .push(Level::new(child, node.children().into_iter().collect()));
+                }
+            }
+            return Some(Ok(node));
+        }
+    }
+}
+
+fn ids<T: AsRef<str>>(ids: &[T]) -> Vec<String> {
+    ids.iter().map(|id| id.as_ref().to_string()).collect()
+}
+
+fn fetch(client: &Client, kind: EntityKind, id: &str) -> Result<Node> {
+    match kind {
+        EntityKind::Patient => client.patient(id).map(Node::Patient),
+        EntityKind::Study => client.study(id).map(Node::Study),
+        EntityKind::Series => client.series(id).map(Node::Series),
+        EntityKind::Instance => client.instance(id).map(Node::Instance),
+    }
+}
+
+fn child_kind(kind: EntityKind) -> Option<EntityKind> {
+    match kind {
+        EntityKind::Patient => Some(EntityKind::Study),
+        EntityKind::Study => Some(EntityKind::Series),
+        EntityKind::Series => Some(EntityKind::Instance),
+        EntityKind::Instance => None,
+    }
+}
+
+fn depth(kind: EntityKind) -> u8 {
+    match kind {
+        EntityKind::Patient => 0,
+        EntityKind::Study => 1,
+        EntityKind::Series => 2,
+        EntityKind::Instance => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_child_kind() {
+        assert_eq!(child_kind(EntityKind::Patient), Some(EntityKind::Study));
+        assert_eq!(child_kind(EntityKind::Study), Some(EntityKind::Series));
+        assert_eq!(child_kind(EntityKind::Series), Some(EntityKind::Instance));
+        assert_eq!(child_kind(EntityKind::Instance), None);
+    }
+
+    #[test]
+    fn test_depth() {
+        assert!(depth(EntityKind::Patient) < depth(EntityKind::Study));
+        assert!(depth(EntityKind::Study) < depth(EntityKind::Series));
+        assert!(depth(EntityKind::Series) < depth(EntityKind::Instance));
+    }
+}
@@ -1,10 +1,18 @@
 use chrono::NaiveDate;
-use httpmock::{Method, Mock, MockServer};
+use httpmock::{Method, Mock, MockRef, MockServer};
 use maplit::hashmap;
+use orthanc::archive::*;
 use orthanc::entity::*;
+use orthanc::ids::*;
+use orthanc::import::*;
 use orthanc::models::*;
-use orthanc::{ApiError, Client, Error};
+use orthanc::phi::*;
+use orthanc::progress::Transfer;
+use orthanc::pseudonymization::*;
+use orthanc::walker::*;
+use orthanc::{ApiError, Client, Error, IntegrityMismatch};
 use serde_json::Value;
+use std::sync::{Arc, Mutex};
 
 #[test]
 fn test_get_system_info() {
@@ -117,6 +125,164 @@ fn test_list_patients() {
     assert_eq!(m.times_called(), 1);
 }
 
+#[test]
+fn test_list_generic() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/series")
+        .return_status(200)
+        .return_body(r#"["foo", "bar"]"#)
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let ids: Vec<SeriesId> = cl.list::<Series>().unwrap();
+
+    assert_eq!(ids, vec![SeriesId::from("foo"), SeriesId::from("bar")]);
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_list_expanded_generic() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances")
+        .expect_query_param_exists("expand")
+        .return_status(200)
+        .return_body(
+            r#"
+                    [
+                        {
+                            "FileSize": 139402,
+                            "FileUuid": "d8c5eff3-1b3f-4a5b-9b6e-f7e3a1b2c3d4",
+                            "ID": "foo",
+                            "IndexInSeries": 13,
+                            "MainDicomTags": {
+                                "SOPInstanceUID": "1.2.3.4"
+                            },
+                            "ParentSeries": "bar",
+                            "Type": "Instance"
+                        }
+                    ]
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let instances = cl.list_expanded::<Instance>().unwrap();
+
+    assert_eq!(instances.len(), 1);
+    assert_eq!(instances[0].id, "foo");
+    assert_eq!(instances[0].parent_series, "bar");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_generic() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/patients/foo")
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "ID": "foo",
+                        "IsStable": true,
+                        "LastUpdate": "20200101T154617",
+                        "MainDicomTags": {
+                            "PatientID": "c137"
+                        },
+                        "Studies": ["bar"],
+                        "Type": "Patient"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let patient = cl.get::<Patient>("foo").unwrap();
+
+    assert_eq!(patient.id, "foo");
+    assert_eq!(patient.studies, vec![StudyId::from("bar")]);
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_delete_generic() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::DELETE)
+        .expect_path("/series/foo")
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "RemainingAncestor": {
+                            "ID": "bar",
+                            "Path": "/studies/bar",
+                            "Type": "Study"
+                        }
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl.delete::<Series>(SeriesId::from("foo")).unwrap();
+
+    assert_eq!(
+        resp,
+        RemainingAncestor {
+            remaining_ancestor: Some(Ancestor {
+                id: "bar".to_string(),
+                path: "/studies/bar".to_string(),
+                entity: EntityKind::Study,
+            })
+        }
+    );
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_download_generic() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m1 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/studies/foo/archive")
+        .return_status(200)
+        .return_body("foo")
+        .create_on(&mock_server);
+    let m2 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/bar/file")
+        .return_status(200)
+        .return_body("bar")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut study: Vec<u8> = vec![];
+    cl.download::<Study>("foo", &mut study).unwrap();
+    let mut instance: Vec<u8> = vec![];
+    cl.download::<Instance>("bar", &mut instance).unwrap();
+
+    assert_eq!(&study, &b"foo");
+    assert_eq!(&instance, &b"bar");
+    assert_eq!(m1.times_called(), 1);
+    assert_eq!(m2.times_called(), 1);
+}
+
 #[test]
 fn test_list_studies() {
     let mock_server = MockServer::start();
@@ -386,7 +552,7 @@ fn test_list_patients_expanded() {
         patients,
         [
             Patient {
-                id: "f88cbd3f-a00dfc59-9ca1ac2d-7ce9851a-40e5b493".to_string(),
+                id: "f88cbd3f-a00dfc59-9ca1ac2d-7ce9851a-40e5b493".into(),
                 is_stable: true,
                 last_update: NaiveDate::from_ymd(2020, 1, 1).and_hms(15, 46, 17),
                 main_dicom_tags: hashmap! {
@@ -396,13 +562,12 @@ fn test_list_patients_expanded() {
                     "PatientName".to_string() => "Rick Sanchez".to_string(),
                     "PatientSex".to_string() => "M".to_string()
                 },
-                studies: ["e8cafcbe-caf08c39-6e205f15-18554bb8-b3f9ef04".to_string()]
-                    .to_vec(),
+                studies: ["e8cafcbe-caf08c39-6e205f15-18554bb8-b3f9ef04".into()].to_vec(),
                 entity: EntityKind::Patient,
                 anonymized_from: None
             },
             Patient {
-                id: "7e43f8d3-e50280e6-470079e9-02241af1-d286bdbe".to_string(),
+                id: "7e43f8d3-e50280e6-470079e9-02241af1-d286bdbe".into(),
                 is_stable: true,
                 last_update: NaiveDate::from_ymd(2020, 8, 26).and_hms(17, 45, 31),
                 main_dicom_tags: hashmap! {
@@ -411,8 +576,7 @@ fn test_list_patients_expanded() {
                     "PatientID".to_string() => "987654321".to_string(),
                     "PatientName".to_string() => "Morty Smith".to_string(),
                 },
-                studies: ["63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".to_string()]
-                    .to_vec(),
+                studies: ["63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".into()].to_vec(),
                 entity: EntityKind::Patient,
                 anonymized_from: None
             },
@@ -497,7 +661,7 @@ fn test_list_studies_expanded() {
         studies,
         [
             Study {
-                id: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".to_string(),
+                id: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".into(),
                 is_stable: true,
                 last_update: NaiveDate::from_ymd(2020, 8, 30).and_hms(19, 11, 09),
                 main_dicom_tags: hashmap! {
@@ -508,7 +672,7 @@ fn test_list_studies_expanded() {
                     "StudyInstanceUID".to_string() => "1.2.3.4.5.6789".to_string(),
                     "StudyTime".to_string() => "084707".to_string()
                 },
-                parent_patient: "7e43f8d3-e50280e6-470079e9-02241af1-d286bdbe".to_string(),
+                parent_patient: "7e43f8d3-e50280e6-470079e9-02241af1-d286bdbe".into(),
                 patient_main_dicom_tags: hashmap! {
                     "PatientBirthDate".to_string() => "19440101".to_string(),
                     "PatientID".to_string() => "c137".to_string(),
@@ -516,15 +680,15 @@ fn test_list_studies_expanded() {
                     "PatientSex".to_string() => "M".to_string(),
                 },
                 series: [
-                    "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".to_string(),
-                    "2ab7dbe7-f1a18a78-86145443-18a8ff93-0b65f2b2".to_string()
+                    "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".into(),
+                    "2ab7dbe7-f1a18a78-86145443-18a8ff93-0b65f2b2".into()
                 ]
                 .to_vec(),
                 entity: EntityKind::Study,
                 anonymized_from: None
             },
             Study {
-                id: "e8cafcbe-caf08c39-6e205f15-18554bb8-b3f9ef04".to_string(),
+                id: "e8cafcbe-caf08c39-6e205f15-18554bb8-b3f9ef04".into(),
                 is_stable: true,
                 last_update: NaiveDate::from_ymd(2020, 9, 1).and_hms(18, 52, 11),
                 main_dicom_tags: hashmap! {
@@ -535,7 +699,7 @@ fn test_list_studies_expanded() {
                     "StudyInstanceUID".to_string() => "1.2.3.4.5.67810".to_string(),
                     "StudyTime".to_string() => "130431".to_string()
                 },
-                parent_patient: "f88cbd3f-a00dfc59-9ca1ac2d-7ce9851a-40e5b493".to_string(),
+                parent_patient: "f88cbd3f-a00dfc59-9ca1ac2d-7ce9851a-40e5b493".into(),
                 patient_main_dicom_tags: hashmap! {
                     "PatientBirthDate".to_string() => "19670101".to_string(),
                     "PatientID".to_string() => "4217".to_string(),
@@ -543,8 +707,8 @@ fn test_list_studies_expanded() {
                     "PatientSex".to_string() => "F".to_string(),
                 },
                 series: [
-                    "222bbd7e-4dfbc5a8-ea58f933-f1747134-0810c7c8".to_string(),
-                    "54f8778a-75ba559c-db7c7c1a-c1056140-ef74d487".to_string()
+                    "222bbd7e-4dfbc5a8-ea58f933-f1747134-0810c7c8".into(),
+                    "54f8778a-75ba559c-db7c7c1a-c1056140-ef74d487".into()
                 ]
                 .to_vec(),
                 entity: EntityKind::Study,
@@ -626,7 +790,7 @@ fn test_list_seies_expanded() {
         series,
         [
             Series {
-                id: "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".to_string(),
+                id: "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".into(),
                 status: "Unknown".to_string(),
                 is_stable: true,
                 last_update: NaiveDate::from_ymd(2020, 8, 30).and_hms(19, 11, 09),
@@ -640,19 +804,19 @@ fn test_list_seies_expanded() {
                     "SeriesTime".to_string() => "091313.93".to_string(),
 
                 },
-                parent_study: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".to_string(),
+                parent_study: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".into(),
                 expected_number_of_instances: Some(17),
                 instances: [
-                    "556530b5-de7c487b-110b9d0e-12cfdbb9-f06b546e".to_string(),
-                    "c46605db-836489fa-cb55fbbc-13c8a913-b0bad6ac".to_string(),
-                    "9b63498d-cae4f25e-f52206b2-cbb4dc0e-dc55c788".to_string(),
+                    "556530b5-de7c487b-110b9d0e-12cfdbb9-f06b546e".into(),
+                    "c46605db-836489fa-cb55fbbc-13c8a913-b0bad6ac".into(),
+                    "9b63498d-cae4f25e-f52206b2-cbb4dc0e-dc55c788".into(),
                 ]
                 .to_vec(),
                 entity: EntityKind::Series,
                 anonymized_from: None
             },
             Series {
-                id: "2ab7dbe7-f1a18a78-86145443-18a8ff93-0b65f2b2".to_string(),
+                id: "2ab7dbe7-f1a18a78-86145443-18a8ff93-0b65f2b2".into(),
                 status: "Unknown".to_string(),
                 is_stable: true,
                 last_update: NaiveDate::from_ymd(2020, 8, 30).and_hms(19, 11, 09),
@@ -666,11 +830,11 @@ fn test_list_seies_expanded() {
                     "SeriesTime".to_string() => "091313.93".to_string(),
 
                 },
-                parent_study: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".to_string(),
+                parent_study: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".into(),
                 expected_number_of_instances: None,
                 instances: [
-                    "a17e85ca-380bb2dc-d29ea4b7-6e73c10a-ca6ba458".to_string(),
-                    "1c81e7e8-30642777-ffc2ca41-c7536670-7ad68124".to_string(),
+                    "a17e85ca-380bb2dc-d29ea4b7-6e73c10a-ca6ba458".into(),
+                    "1c81e7e8-30642777-ffc2ca41-c7536670-7ad68124".into(),
                 ]
                 .to_vec(),
                 entity: EntityKind::Series,
@@ -739,7 +903,7 @@ fn test_list_instances_expanded() {
         instances,
         [
             Instance {
-                id: "29fa4d9d-51a69d1d-70e2b29a-fd824316-50850d0c".to_string(),
+                id: "29fa4d9d-51a69d1d-70e2b29a-fd824316-50850d0c".into(),
                 main_dicom_tags: hashmap! {
                     "ImageOrientationPatient".to_string() => "1\\0\\0\\0\\1\\0".to_string(),
                     "ImagePositionPatient".to_string() => "-17\\42\\13".to_string(),
@@ -748,18 +912,16 @@ fn test_list_instances_expanded() {
                     "InstanceNumber".to_string() => "13".to_string(),
                     "SOPInstanceUID".to_string() => "1.2.3.4.5.6789".to_string(),
                 },
-                parent_series: "82081568-b6f8f4e6-ced76876-6504da25-ed0dfe03".to_string(),
+                parent_series: "82081568-b6f8f4e6-ced76876-6504da25-ed0dfe03".into(),
                 index_in_series: Some(13),
                 file_uuid: "d8c5eff3-986c-4fe4-b06e-7e52b2a4238e".to_string(),
                 file_size: 139402,
-                modified_from: Some(
-                    "22c54cb6-28302a69-3ff454a3-676b98f4-b84cd80a".to_string()
-                ),
+                modified_from: Some("22c54cb6-28302a69-3ff454a3-676b98f4-b84cd80a".into()),
                 entity: EntityKind::Instance,
                 anonymized_from: None
             },
             Instance {
-                id: "286a251e-46571bd6-0e14ab9a-1baadddc-d0146ea0".to_string(),
+                id: "286a251e-46571bd6-0e14ab9a-1baadddc-d0146ea0".into(),
                 main_dicom_tags: hashmap! {
                     "ImageOrientationPatient".to_string() => "-1\\0\\0\\0\\1\\0".to_string(),
                     "ImagePositionPatient".to_string() => "-17\\42\\14".to_string(),
@@ -768,7 +930,7 @@ fn test_list_instances_expanded() {
                     "InstanceNumber".to_string() => "75".to_string(),
                     "SOPInstanceUID".to_string() => "1.2.3.4.5.67810".to_string(),
                 },
-                parent_series: "a240e0d7-538699a0-7464bb4b-a906f72a-fa3a32c7".to_string(),
+                parent_series: "a240e0d7-538699a0-7464bb4b-a906f72a-fa3a32c7".into(),
                 index_in_series: None,
                 file_uuid: "86bbad65-2c98-4cb0-bf77-0ef0243410a4".to_string(),
                 file_size: 381642,
@@ -819,7 +981,7 @@ fn test_get_patient() {
     assert_eq!(
         patient,
         Patient {
-            id: "f88cbd3f-a00dfc59-9ca1ac2d-7ce9851a-40e5b493".to_string(),
+            id: "f88cbd3f-a00dfc59-9ca1ac2d-7ce9851a-40e5b493".into(),
             is_stable: true,
             last_update: NaiveDate::from_ymd(2020, 1, 1).and_hms(15, 46, 17),
             main_dicom_tags: hashmap! {
@@ -829,7 +991,7 @@ fn test_get_patient() {
                 "PatientName".to_string() => "Rick Sanchez".to_string(),
                 "PatientSex".to_string() => "M".to_string()
             },
-            studies: ["e8cafcbe-caf08c39-6e205f15-18554bb8-b3f9ef04".to_string()].to_vec(),
+            studies: ["e8cafcbe-caf08c39-6e205f15-18554bb8-b3f9ef04".into()].to_vec(),
             entity: EntityKind::Patient,
             anonymized_from: None
         },
@@ -884,7 +1046,7 @@ fn test_get_study() {
     assert_eq!(
         study,
         Study {
-            id: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".to_string(),
+            id: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".into(),
             is_stable: true,
             last_update: NaiveDate::from_ymd(2020, 8, 30).and_hms(19, 11, 09),
             main_dicom_tags: hashmap! {
@@ -895,7 +1057,7 @@ fn test_get_study() {
                 "StudyInstanceUID".to_string() => "1.2.3.4.5.6789".to_string(),
                 "StudyTime".to_string() => "084707".to_string()
             },
-            parent_patient: "7e43f8d3-e50280e6-470079e9-02241af1-d286bdbe".to_string(),
+            parent_patient: "7e43f8d3-e50280e6-470079e9-02241af1-d286bdbe".into(),
             patient_main_dicom_tags: hashmap! {
                 "PatientBirthDate".to_string() => "19440101".to_string(),
                 "PatientID".to_string() => "c137".to_string(),
@@ -903,8 +1065,8 @@ fn test_get_study() {
                 "PatientSex".to_string() => "M".to_string(),
             },
             series: [
-                "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".to_string(),
-                "2ab7dbe7-f1a18a78-86145443-18a8ff93-0b65f2b2".to_string()
+                "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".into(),
+                "2ab7dbe7-f1a18a78-86145443-18a8ff93-0b65f2b2".into()
             ]
             .to_vec(),
             entity: EntityKind::Study,
@@ -953,7 +1115,7 @@ fn test_get_instance() {
     assert_eq!(
         instance,
         Instance {
-            id: "29fa4d9d-51a69d1d-70e2b29a-fd824316-50850d0c".to_string(),
+            id: "29fa4d9d-51a69d1d-70e2b29a-fd824316-50850d0c".into(),
             main_dicom_tags: hashmap! {
                 "ImageOrientationPatient".to_string() => "1\\0\\0\\0\\1\\0".to_string(),
                 "ImagePositionPatient".to_string() => "-17\\42\\13".to_string(),
@@ -962,11 +1124,11 @@ fn test_get_instance() {
                 "InstanceNumber".to_string() => "13".to_string(),
                 "SOPInstanceUID".to_string() => "1.2.3.4.5.6789".to_string(),
             },
-            parent_series: "82081568-b6f8f4e6-ced76876-6504da25-ed0dfe03".to_string(),
+            parent_series: "82081568-b6f8f4e6-ced76876-6504da25-ed0dfe03".into(),
             index_in_series: Some(13),
             file_uuid: "d8c5eff3-986c-4fe4-b06e-7e52b2a4238e".to_string(),
             file_size: 139402,
-            modified_from: Some("22c54cb6-28302a69-3ff454a3-676b98f4-b84cd80a".to_string()),
+            modified_from: Some("22c54cb6-28302a69-3ff454a3-676b98f4-b84cd80a".into()),
             entity: EntityKind::Instance,
             anonymized_from: None
         }
@@ -975,206 +1137,394 @@ fn test_get_instance() {
 }
 
 #[test]
-fn test_get_series() {
+fn test_get_instance_study() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let m = Mock::new()
         .expect_method(Method::GET)
-        .expect_path("/series/foo")
+        .expect_path("/instances/foo/study")
         .return_status(200)
-        .return_header("Content-Type", "application/json")
         .return_body(
             r#"
                     {
-                        "ExpectedNumberOfInstances": 17,
-                        "ID": "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c",
-                        "Instances": [
-                            "556530b5-de7c487b-110b9d0e-12cfdbb9-f06b546e",
-                            "c46605db-836489fa-cb55fbbc-13c8a913-b0bad6ac",
-                            "9b63498d-cae4f25e-f52206b2-cbb4dc0e-dc55c788"
-                        ],
+                        "ID": "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5",
                         "IsStable": true,
                         "LastUpdate": "20200830T191109",
                         "MainDicomTags": {
-                            "BodyPartExamined": "ABDOMEN",
-                            "Modality": "MR",
-                            "ProtocolName": "TCP",
-                            "SeriesDate": "20110101",
-                            "SeriesInstanceUID": "1.2.3.4.5.6789",
-                            "SeriesNumber": "1101",
-                            "SeriesTime": "091313.93"
+                            "StudyInstanceUID": "1.2.3.4.5.6789"
                         },
-                        "ParentStudy": "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5",
-                        "Status": "Unknown",
-                        "Type": "Series"
+                        "ParentPatient": "7e43f8d3-e50280e6-470079e9-02241af1-d286bdbe",
+                        "PatientMainDicomTags": {
+                            "PatientID": "c137"
+                        },
+                        "Series": [
+                            "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c"
+                        ],
+                        "Type": "Study"
                     }
                 "#,
         )
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    let series = cl.series("foo").unwrap();
-
-    assert_eq!(
-        series,
-        Series {
-            id: "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".to_string(),
-            status: "Unknown".to_string(),
-            is_stable: true,
-            last_update: NaiveDate::from_ymd(2020, 8, 30).and_hms(19, 11, 09),
-            main_dicom_tags: hashmap! {
-                "BodyPartExamined".to_string() => "ABDOMEN".to_string(),
-                "Modality".to_string() => "MR".to_string(),
-                "ProtocolName".to_string() => "TCP".to_string(),
-                "SeriesDate".to_string() => "20110101".to_string(),
-                "SeriesInstanceUID".to_string() => "1.2.3.4.5.6789".to_string(),
-                "SeriesNumber".to_string() => "1101".to_string(),
-                "SeriesTime".to_string() => "091313.93".to_string(),
+    let study = cl.instance_study("foo").unwrap();
 
-            },
-            parent_study: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".to_string(),
-            expected_number_of_instances: Some(17),
-            instances: [
-                "556530b5-de7c487b-110b9d0e-12cfdbb9-f06b546e".to_string(),
-                "c46605db-836489fa-cb55fbbc-13c8a913-b0bad6ac".to_string(),
-                "9b63498d-cae4f25e-f52206b2-cbb4dc0e-dc55c788".to_string(),
-            ]
-            .to_vec(),
-            entity: EntityKind::Series,
-            anonymized_from: None
-        },
-    );
+    assert_eq!(study.id, "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5");
     assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_get_instance_tags() {
+fn test_get_patient_instances() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
-    let body = r#"
-            {
-                "AccessionNumber": "foobar",
-                "AcquisitionDate": "20110101",
-                "AcquisitionDuration": "219",
-                "ReferencedImageSequence": [
-                    {
-                        "ReferencedSOPClassUID": "1.2.3.4.5.6789",
-                        "ReferencedSOPInstanceUID": "1.2.3.4.5.67810"
-                    }
-                ]
-            }
-        "#;
     let m = Mock::new()
         .expect_method(Method::GET)
-        .expect_path("/instances/foo/simplified-tags")
+        .expect_path("/patients/foo/instances")
         .return_status(200)
-        .return_header("Content-Type", "application/json")
-        .return_body(body)
+        .return_body(
+            r#"
+                    [
+                        {
+                            "FileSize": 139402,
+                            "FileUuid": "d8c5eff3-1b3f-4a5b-9b6e-f7e3a1b2c3d4",
+                            "ID": "556530b5-de7c487b-110b9d0e-12cfdbb9-f06b546e",
+                            "IndexInSeries": 1,
+                            "MainDicomTags": {
+                                "SOPInstanceUID": "1.2.3.4"
+                            },
+                            "ParentSeries": "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c",
+                            "Type": "Instance"
+                        }
+                    ]
+                "#,
+        )
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    let resp = cl.instance_tags("foo").unwrap();
+    let instances = cl.patient_instances("foo").unwrap();
 
-    let expected_resp: Value = serde_json::from_str(body).unwrap();
-    assert_eq!(resp, expected_resp);
+    assert_eq!(instances.len(), 1);
+    assert_eq!(
+        instances[0].id,
+        "556530b5-de7c487b-110b9d0e-12cfdbb9-f06b546e"
+    );
     assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_instance_content() {
+fn test_entity_parent_and_child_entities() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
-    let body = r#"
-            [
-                "0040-0253",
-                "0040-0254",
-                "0040-0260"
 
-            ]
-        "#;
-    let m = Mock::new()
+    let m1 = Mock::new()
         .expect_method(Method::GET)
-        .expect_path("/instances/foo/content")
+        .expect_path("/studies/63bf5d42-b5382159-01971752-e0ceea3d-399bbca5")
         .return_status(200)
-        .return_header("Content-Type", "application/json")
-        .return_body(body)
+        .return_body(
+            r#"
+                    {
+                        "ID": "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5",
+                        "IsStable": true,
+                        "LastUpdate": "20200830T191109",
+                        "MainDicomTags": {
+                            "StudyInstanceUID": "1.2.3.4.5.6789"
+                        },
+                        "ParentPatient": "7e43f8d3-e50280e6-470079e9-02241af1-d286bdbe",
+                        "PatientMainDicomTags": {
+                            "PatientID": "c137"
+                        },
+                        "Series": [
+                            "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c"
+                        ],
+                        "Type": "Study"
+                    }
+                "#,
+        )
         .create_on(&mock_server);
-
-    let cl = Client::new(url);
-    let resp = cl.instance_content("foo").unwrap();
-
-    assert_eq!(resp, ["0040-0253", "0040-0254", "0040-0260"]);
-    assert_eq!(m.times_called(), 1);
-}
-
-#[test]
-fn test_instance_tag() {
-    let mock_server = MockServer::start();
-    let url = mock_server.url("");
-    let m = Mock::new()
+    let m2 = Mock::new()
         .expect_method(Method::GET)
-        .expect_path("/instances/foo/content/bar")
+        .expect_path("/series/cd00fffc-db25be29-0c6da430-c56796a5-ba06933c/instances")
         .return_status(200)
-        .return_header("Content-Type", "application/json")
-        .return_body("FOOBAR")
-        .create_on(&mock_server);
+        .return_body(
+            r#"
+                    [
+                        {
+                            "FileSize": 139402,
+                            "FileUuid": "d8c5eff3-1b3f-4a5b-9b6e-f7e3a1b2c3d4",
+                            "ID": "556530b5-de7c487b-110b9d0e-12cfdbb9-f06b546e",
+                            "IndexInSeries": 1,
+                            "MainDicomTags": {
+                                "SOPInstanceUID": "1.2.3.4"
+                            },
+                            "ParentSeries": "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c",
+                            "Type": "Instance"
+                        }
+                    ]
+                "#,
+        )
+        .create_on(&mock_server);
 
     let cl = Client::new(url);
-    let resp = cl.instance_tag("foo", "bar").unwrap();
+    let series: Series = serde_json::from_str(
+        r#"
+            {
+                "ExpectedNumberOfInstances": null,
+                "ID": "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c",
+                "Instances": ["556530b5-de7c487b-110b9d0e-12cfdbb9-f06b546e"],
+                "IsStable": true,
+                "LastUpdate": "20200830T191109",
+                "MainDicomTags": {},
+                "ParentStudy": "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5",
+                "Status": "Unknown",
+                "Type": "Series"
+            }
+        "#,
+    )
+    .unwrap();
 
-    assert_eq!(resp, "FOOBAR");
+    let study = series.parent(&cl).unwrap();
+    assert_eq!(study.id, series.parent_study);
+    let instances = series.child_entities(&cl).unwrap();
+    assert_eq!(instances[0].id, series.instances[0]);
+    assert_eq!(m1.times_called(), 1);
+    assert_eq!(m2.times_called(), 1);
+}
+
+fn walker_mocks(mock_server: &MockServer) -> Vec<MockRef<'_>> {
+    let study = r#"
+        {
+            "ID": "st",
+            "IsStable": true,
+            "LastUpdate": "20200830T191109",
+            "MainDicomTags": {},
+            "ParentPatient": "pa",
+            "PatientMainDicomTags": {},
+            "Series": ["se1", "se2"],
+            "Type": "Study"
+        }
+    "#;
+    let series = |id: &str, modality: &str, instance: &str| {
+        format!(
+            r#"
+                {{
+                    "ExpectedNumberOfInstances": null,
+                    "ID": "{}",
+                    "Instances": ["{}"],
+                    "IsStable": true,
+                    "LastUpdate": "20200830T191109",
+                    "MainDicomTags": {{"Modality": "{}"}},
+                    "ParentStudy": "st",
+                    "Status": "Unknown",
+                    "Type": "Series"
+                }}
+            "#,
+            id, instance, modality
+        )
+    };
+    let instance = |id: &str, series: &str| {
+        format!(
+            r#"
+                {{
+                    "FileSize": 139402,
+                    "FileUuid": "d8c5eff3-1b3f-4a5b-9b6e-f7e3a1b2c3d4",
+                    "ID": "{}",
+                    "IndexInSeries": 1,
+                    "MainDicomTags": {{}},
+                    "ParentSeries": "{}",
+                    "Type": "Instance"
+                }}
+            "#,
+            id, series
+        )
+    };
+    vec![
+        ("/studies/st", study.to_string()),
+        ("/series/se1", series("se1", "MR", "i1")),
+        ("/series/se2", series("se2", "CT", "i2")),
+        ("/instances/i1", instance("i1", "se1")),
+        ("/instances/i2", instance("i2", "se2")),
+    ]
+    .into_iter()
+    .map(|(path, body)| {
+        Mock::new()
+            .expect_method(Method::GET)
+            .expect_path(path)
+            .return_status(200)
+            .return_body(&body)
+            .create_on(mock_server)
+    })
+    .collect()
+}
+
+#[test]
+fn test_walker_from_entity() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+    let mocks = walker_mocks(&mock_server);
+
+    let cl = Client::new(url);
+    let nodes: Vec<(EntityKind, String)> =
+        Walker::from_entity(&cl, EntityKind::Study, "st")
+            .prefetch(2)
+            .map(|n| n.unwrap())
+            .map(|n| (n.kind(), n.id().to_string()))
+            .collect();
+
+    assert_eq!(
+        nodes,
+        vec![
+            (EntityKind::Study, "st".to_string()),
+            (EntityKind::Series, "se1".to_string()),
+            (EntityKind::Instance, "i1".to_string()),
+            (EntityKind::Series, "se2".to_string()),
+            (EntityKind::Instance, "i2".to_string()),
+        ]
+    );
+    for m in mocks {
+        assert_eq!(m.times_called(), 1);
+    }
+}
+
+#[test]
+fn test_walker_filter_and_max_level() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+    let mocks = walker_mocks(&mock_server);
+
+    let cl = Client::new(url);
+    let ids: Vec<String> = Walker::from_entity(&cl, EntityKind::Study, "st")
+        .filter_series(|s| s.main_dicom_tag("Modality") == Some("CT"))
+        .map(|n| n.unwrap().id().to_string())
+        .collect();
+    assert_eq!(ids, vec!["st", "se2", "i2"]);
+    assert_eq!(mocks[3].times_called(), 0);
+    assert_eq!(mocks[4].times_called(), 1);
+
+    let ids: Vec<String> = Walker::from_entity(&cl, EntityKind::Study, "st")
+        .max_level(EntityKind::Series)
+        .map(|n| n.unwrap().id().to_string())
+        .collect();
+    assert_eq!(ids, vec!["st", "se1", "se2"]);
+    assert_eq!(mocks[3].times_called(), 0);
+    assert_eq!(mocks[4].times_called(), 1);
+}
+
+#[test]
+fn test_walker_error() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let cl = Client::new(url);
+    let nodes: Vec<_> = Walker::from_entity(&cl, EntityKind::Series, "missing").collect();
+
+    assert_eq!(nodes.len(), 1);
+    assert!(nodes[0].is_err());
+}
+
+#[test]
+fn test_get_series() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/series/foo")
+        .return_status(200)
+        .return_header("Content-Type", "application/json")
+        .return_body(
+            r#"
+                    {
+                        "ExpectedNumberOfInstances": 17,
+                        "ID": "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c",
+                        "Instances": [
+                            "556530b5-de7c487b-110b9d0e-12cfdbb9-f06b546e",
+                            "c46605db-836489fa-cb55fbbc-13c8a913-b0bad6ac",
+                            "9b63498d-cae4f25e-f52206b2-cbb4dc0e-dc55c788"
+                        ],
+                        "IsStable": true,
+                        "LastUpdate": "20200830T191109",
+                        "MainDicomTags": {
+                            "BodyPartExamined": "ABDOMEN",
+                            "Modality": "MR",
+                            "ProtocolName": "TCP",
+                            "SeriesDate": "20110101",
+                            "SeriesInstanceUID": "1.2.3.4.5.6789",
+                            "SeriesNumber": "1101",
+                            "SeriesTime": "091313.93"
+                        },
+                        "ParentStudy": "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5",
+                        "Status": "Unknown",
+                        "Type": "Series"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let series = cl.series("foo").unwrap();
+
+    assert_eq!(
+        series,
+        Series {
+            id: "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".into(),
+            status: "Unknown".to_string(),
+            is_stable: true,
+            last_update: NaiveDate::from_ymd(2020, 8, 30).and_hms(19, 11, 09),
+            main_dicom_tags: hashmap! {
+                "BodyPartExamined".to_string() => "ABDOMEN".to_string(),
+                "Modality".to_string() => "MR".to_string(),
+                "ProtocolName".to_string() => "TCP".to_string(),
+                "SeriesDate".to_string() => "20110101".to_string(),
+                "SeriesInstanceUID".to_string() => "1.2.3.4.5.6789".to_string(),
+                "SeriesNumber".to_string() => "1101".to_string(),
+                "SeriesTime".to_string() => "091313.93".to_string(),
+
+            },
+            parent_study: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".into(),
+            expected_number_of_instances: Some(17),
+            instances: [
+                "556530b5-de7c487b-110b9d0e-12cfdbb9-f06b546e".into(),
+                "c46605db-836489fa-cb55fbbc-13c8a913-b0bad6ac".into(),
+                "9b63498d-cae4f25e-f52206b2-cbb4dc0e-dc55c788".into(),
+            ]
+            .to_vec(),
+            entity: EntityKind::Series,
+            anonymized_from: None
+        },
+    );
     assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_get_instance_tags_expanded() {
+fn test_get_instance_tags() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let body = r#"
             {
-                "0002,0003": {
-                    "Name": "MediaStorageSOPInstanceUID",
-                    "Type": "String",
-                    "Value": "1.2.3.4567"
-                },
-                "0008,0005": {
-                    "Name": "SpecificCharacterSet",
-                    "Type": "String",
-                    "Value": "ISO_IR 100"
-                },
-                "0008,1110": {
-                    "Name": "ReferencedStudySequence",
-                    "Type": "Sequence",
-                    "Value": [
-                        {
-                            "0008,1150": {
-                                "Name": "ReferencedSOPClassUID",
-                                "Type": "String",
-                                "Value": "1.2.3.4.5.6789"
-                            },
-                            "0008,1155": {
-                                "Name": "ReferencedSOPInstanceUID",
-                                "Type": "String",
-                                "Value": "1.2.3.4.5.67810"
-                            }
-                        }
-                    ]
-                }
+                "AccessionNumber": "foobar",
+                "AcquisitionDate": "20110101",
+                "AcquisitionDuration": "219",
+                "ReferencedImageSequence": [
+                    {
+                        "ReferencedSOPClassUID": "1.2.3.4.5.6789",
+                        "ReferencedSOPInstanceUID": "1.2.3.4.5.67810"
+                    }
+                ]
             }
         "#;
     let m = Mock::new()
         .expect_method(Method::GET)
-        .expect_path("/instances/foo/tags")
+        .expect_path("/instances/foo/simplified-tags")
         .return_status(200)
         .return_header("Content-Type", "application/json")
         .return_body(body)
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    let resp = cl.instance_tags_expanded("foo").unwrap();
+    let resp = cl.instance_tags("foo").unwrap();
 
     let expected_resp: Value = serde_json::from_str(body).unwrap();
     assert_eq!(resp, expected_resp);
@@ -1182,809 +1532,3436 @@ fn test_get_instance_tags_expanded() {
 }
 
 #[test]
-fn test_get_patient_dicom() {
+fn test_get_series_ordered_slices() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let m = Mock::new()
         .expect_method(Method::GET)
-        .expect_path("/patients/foo/archive")
+        .expect_path("/series/foo/ordered-slices")
         .return_status(200)
         .return_header("Content-Type", "application/json")
-        .return_body("foobar")
+        .return_body(
+            r#"
+                    {
+                        "Dicom": [
+                            "/instances/bar/file",
+                            "/instances/baz/file"
+                        ],
+                        "Slices": [
+                            "/instances/bar/frames/0",
+                            "/instances/baz/frames/0"
+                        ],
+                        "SlicesShort": [
+                            ["bar", 0, 1],
+                            ["baz", 0, 1]
+                        ],
+                        "Type": "Sequence"
+                    }
+                "#,
+        )
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    let mut writer: Vec<u8> = vec![];
-    cl.patient_dicom("foo", &mut writer).unwrap();
+    let slices = cl.series_ordered_slices("foo").unwrap();
 
-    assert_eq!(&writer, &b"foobar");
+    assert_eq!(
+        slices,
+        OrderedSlices {
+            dicom: vec![
+                "/instances/bar/file".to_string(),
+                "/instances/baz/file".to_string()
+            ],
+            slices: vec![
+                "/instances/bar/frames/0".to_string(),
+                "/instances/baz/frames/0".to_string()
+            ],
+            slices_short: vec![
+                Slice {
+                    instance: "bar".into(),
+                    first_frame: 0,
+                    frame_count: 1
+                },
+                Slice {
+                    instance: "baz".into(),
+                    first_frame: 0,
+                    frame_count: 1
+                },
+            ],
+            sorting: SliceSorting::Sequence,
+        }
+    );
     assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_get_study_dicom() {
+fn test_series_ordered_instances() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m1 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/series/foo/ordered-slices")
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "Dicom": [],
+                        "Slices": [],
+                        "SlicesShort": [["baz", 0, 1], ["bar", 0, 1]],
+                        "Type": "Volume"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+    let instance = |id: &str| {
+        format!(
+            r#"
+                {{
+                    "FileSize": 139402,
+                    "FileUuid": "d8c5eff3-1b3f-4a5b-9b6e-f7e3a1b2c3d4",
+                    "ID": "{}",
+                    "IndexInSeries": 1,
+                    "MainDicomTags": {{}},
+                    "ParentSeries": "foo",
+                    "Type": "Instance"
+                }}
+            "#,
+            id
+        )
+    };
+    let m2 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/bar")
+        .return_status(200)
+        .return_body(&instance("bar"))
+        .create_on(&mock_server);
+    let m3 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/baz")
+        .return_status(200)
+        .return_body(&instance("baz"))
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut instances = cl.series_ordered_instances("foo").unwrap();
+
+    assert_eq!(m1.times_called(), 1);
+    assert_eq!(m2.times_called(), 0);
+    assert_eq!(m3.times_called(), 0);
+    assert_eq!(instances.next().unwrap().unwrap().id, "baz");
+    assert_eq!(instances.next().unwrap().unwrap().id, "bar");
+    assert!(instances.next().is_none());
+    assert_eq!(m2.times_called(), 1);
+    assert_eq!(m3.times_called(), 1);
+}
+
+#[test]
+fn test_get_series_shared_tags() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let m = Mock::new()
         .expect_method(Method::GET)
-        .expect_path("/studies/foo/archive")
+        .expect_path("/series/foo/shared-tags")
+        .return_status(200)
+        .return_header("Content-Type", "application/json")
+        .return_body(
+            r#"
+                    {
+                        "0008,0060": {
+                            "Name": "Modality",
+                            "Type": "String",
+                            "Value": "MR"
+                        },
+                        "0008,1140": {
+                            "Name": "ReferencedImageSequence",
+                            "Type": "Sequence",
+                            "Value": [
+                                {
+                                    "0008,1150": {
+                                        "Name": "ReferencedSOPClassUID",
+                                        "Type": "String",
+                                        "Value": "1.2.3.4"
+                                    }
+                                }
+                            ]
+                        }
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let tags = cl.series_shared_tags("foo", TagsFormat::Full).unwrap();
+
+    match &tags {
+        Tags::Full(t) => {
+            assert_eq!(
+                t["0008,0060"],
+                DicomTag {
+                    name: "Modality".to_string(),
+                    value_type: "String".to_string(),
+                    value: Value::from("MR"),
+                }
+            );
+            assert_eq!(t["0008,1140"].value_type, "Sequence");
+        }
+        _ => panic!("Unexpected format: {:?}", tags),
+    }
+    assert_eq!(tags.value("0008,0060"), Some(&Value::from("MR")));
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_study_patient_module() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/studies/foo/module-patient")
+        .expect_query_param_exists("simplify")
+        .return_status(200)
+        .return_header("Content-Type", "application/json")
+        .return_body(
+            r#"
+                    {
+                        "PatientID": "c137",
+                        "PatientName": "Rick Sanchez",
+                        "PatientSex": "M"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let tags = cl
+        .study_patient_module("foo", TagsFormat::Simplified)
+        .unwrap();
+
+    assert_eq!(
+        tags,
+        Tags::Simplified(hashmap! {
+            "PatientID".to_string() => Value::from("c137"),
+            "PatientName".to_string() => Value::from("Rick Sanchez"),
+            "PatientSex".to_string() => Value::from("M"),
+        })
+    );
+    assert_eq!(tags.value("PatientID"), Some(&Value::from("c137")));
+    assert_eq!(tags.value("0010,0020"), None);
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_series_instances_tags() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/series/foo/instances-tags")
+        .expect_query_param_exists("short")
+        .return_status(200)
+        .return_header("Content-Type", "application/json")
+        .return_body(
+            r#"
+                    {
+                        "bar": {
+                            "0008,0018": "1.2.3.4",
+                            "0020,0013": "1"
+                        },
+                        "baz": {
+                            "0008,0018": "1.2.3.5",
+                            "0020,0013": "2"
+                        }
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let tags = cl.series_instances_tags("foo", TagsFormat::Short).unwrap();
+
+    assert_eq!(tags.len(), 2);
+    assert_eq!(
+        tags[&InstanceId::from("baz")],
+        Tags::Short(hashmap! {
+            "0008,0018".to_string() => Value::from("1.2.3.5"),
+            "0020,0013".to_string() => Value::from("2"),
+        })
+    );
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_instance_numpy() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/numpy")
+        .expect_query_param("compress", "false")
+        .expect_query_param("rescale", "false")
+        .return_status(200)
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut writer: Vec<u8> = vec![];
+    cl.instance_numpy("foo", NumpyOptions::default(), &mut writer)
+        .unwrap();
+
+    assert_eq!(writer, b"foobar");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_series_numpy() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/series/foo/numpy")
+        .expect_query_param("compress", "true")
+        .expect_query_param("rescale", "true")
+        .return_status(200)
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut writer: Vec<u8> = vec![];
+    cl.series_numpy(
+        "foo",
+        NumpyOptions {
+            compress: true,
+            rescale: true,
+        },
+        &mut writer,
+    )
+    .unwrap();
+
+    assert_eq!(writer, b"foobar");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_header() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/header")
+        .expect_query_param_exists("simplify")
+        .return_status(200)
+        .return_header("Content-Type", "application/json")
+        .return_body(
+            r#"
+                    {
+                        "FileMetaInformationVersion": null,
+                        "ImplementationClassUID": "1.2.276.0.7230010.3.0.3.6.4",
+                        "ImplementationVersionName": "OFFIS_DCMTK_364",
+                        "MediaStorageSOPClassUID": "1.2.840.10008.5.1.4.1.1.4",
+                        "MediaStorageSOPInstanceUID": "1.2.3.4.5.6789",
+                        "SourceApplicationEntityTitle": "MODALITY",
+                        "TransferSyntaxUID": "1.2.840.10008.1.2.1"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let header = cl.instance_header("foo").unwrap();
+
+    assert_eq!(
+        header,
+        FileMetaInformation {
+            media_storage_sop_class_uid: "1.2.840.10008.5.1.4.1.1.4".to_string(),
+            media_storage_sop_instance_uid: Some("1.2.3.4.5.6789".to_string()),
+            transfer_syntax_uid: TransferSyntax::ExplicitVrLittleEndian,
+            implementation_class_uid: "1.2.276.0.7230010.3.0.3.6.4".to_string(),
+            implementation_version_name: Some("OFFIS_DCMTK_364".to_string()),
+            source_application_entity_title: Some("MODALITY".to_string()),
+        }
+    );
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_header_expanded() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/header")
+        .return_status(200)
+        .return_header("Content-Type", "application/json")
+        .return_body(
+            r#"
+                    {
+                        "0002,0010": {
+                            "Name": "TransferSyntaxUID",
+                            "Type": "String",
+                            "Value": "1.2.840.10008.1.2.1"
+                        }
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let header = cl.instance_header_expanded("foo").unwrap();
+
+    assert_eq!(
+        header.value("0002,0010"),
+        Some(&Value::from("1.2.840.10008.1.2.1"))
+    );
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_instance_content() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+    let body = r#"
+            [
+                "0040-0253",
+                "0040-0254",
+                "0040-0260"
+
+            ]
+        "#;
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/content")
+        .return_status(200)
+        .return_header("Content-Type", "application/json")
+        .return_body(body)
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl.instance_content("foo").unwrap();
+
+    assert_eq!(resp, ["0040-0253", "0040-0254", "0040-0260"]);
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_instance_tag() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/content/bar")
+        .return_status(200)
+        .return_header("Content-Type", "application/json")
+        .return_body("FOOBAR")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl.instance_tag("foo", "bar").unwrap();
+
+    assert_eq!(resp, "FOOBAR");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_tags_expanded() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let body = r#"
+            {
+                "0002,0003": {
+                    "Name": "MediaStorageSOPInstanceUID",
+                    "Type": "String",
+                    "Value": "1.2.3.4567"
+                },
+                "0008,0005": {
+                    "Name": "SpecificCharacterSet",
+                    "Type": "String",
+                    "Value": "ISO_IR 100"
+                },
+                "0008,1110": {
+                    "Name": "ReferencedStudySequence",
+                    "Type": "Sequence",
+                    "Value": [
+                        {
+                            "0008,1150": {
+                                "Name": "ReferencedSOPClassUID",
+                                "Type": "String",
+                                "Value": "1.2.3.4.5.6789"
+                            },
+                            "0008,1155": {
+                                "Name": "ReferencedSOPInstanceUID",
+                                "Type": "String",
+                                "Value": "1.2.3.4.5.67810"
+                            }
+                        }
+                    ]
+                }
+            }
+        "#;
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/tags")
+        .return_status(200)
+        .return_header("Content-Type", "application/json")
+        .return_body(body)
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl.instance_tags_expanded("foo").unwrap();
+
+    let expected_resp: Value = serde_json::from_str(body).unwrap();
+    assert_eq!(resp, expected_resp);
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_check_instance_phi() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/tags")
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "0008,0080": {
+                            "Name": "InstitutionName",
+                            "Type": "String",
+                            "Value": "Citadel"
+                        },
+                        "0010,0010": {
+                            "Name": "PatientName",
+                            "Type": "String",
+                            "Value": "Anonymized1"
+                        }
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let report = PhiChecker::new()
+        .original("Sanchez^Rick")
+        .check_instance(&cl, "foo")
+        .unwrap();
+
+    assert_eq!(
+        report,
+        PhiReport {
+            findings: vec![PhiFinding {
+                location: "0008,0080".to_string(),
+                tag: Some("InstitutionName".to_string()),
+                value: "Citadel".to_string(),
+                reason: PhiReason::PhiTag,
+            }]
+        }
+    );
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_patient_dicom() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/patients/foo/archive")
+        .return_status(200)
+        .return_header("Content-Type", "application/json")
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut writer: Vec<u8> = vec![];
+    cl.patient_dicom("foo", &mut writer).unwrap();
+
+    assert_eq!(&writer, &b"foobar");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_study_dicom() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/studies/foo/archive")
+        .return_status(200)
+        .return_header("Content-Type", "application/json")
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut writer: Vec<u8> = vec![];
+    cl.study_dicom("foo", &mut writer).unwrap();
+
+    assert_eq!(&writer, &b"foobar");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_series_dicom() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/series/foo/archive")
+        .return_status(200)
+        .return_header("Content-Type", "application/json")
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut writer: Vec<u8> = vec![];
+    cl.series_dicom("foo", &mut writer).unwrap();
+
+    assert_eq!(&writer, &b"foobar");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_dicom() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/file")
+        .return_status(200)
+        .return_header("Content-Type", "application/json")
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut writer: Vec<u8> = vec![];
+    cl.instance_dicom("foo", &mut writer).unwrap();
+
+    assert_eq!(&writer, &b"foobar");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_dicom_transcoded() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/file")
+        .expect_header(
+            "Accept",
+            "application/dicom; transfer-syntax=1.2.840.10008.1.2.1",
+        )
+        .return_status(200)
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut writer: Vec<u8> = vec![];
+    cl.instance_dicom_transcoded(
+        "foo",
+        TransferSyntax::ExplicitVrLittleEndian,
+        &mut writer,
+    )
+    .unwrap();
+
+    assert_eq!(&writer, &b"foobar");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_transfer_syntax() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/metadata/TransferSyntax")
+        .return_status(200)
+        .return_body("1.2.840.10008.1.2.4.70")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl.instance_transfer_syntax("foo").unwrap();
+
+    assert_eq!(resp, TransferSyntax::JpegLosslessFirstOrder);
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_dicom_progress() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/file")
+        .return_status(200)
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let updates = Arc::new(Mutex::new(vec![]));
+    let u = updates.clone();
+    let cl = Client::new(url).progress(move |t: &Transfer| {
+        u.lock()
+            .unwrap()
+            .push((t.path.clone(), t.transferred, t.total))
+    });
+    let mut writer: Vec<u8> = vec![];
+    cl.instance_dicom("foo", &mut writer).unwrap();
+
+    assert_eq!(&writer, &b"foobar");
+    let updates = updates.lock().unwrap();
+    assert_eq!(
+        updates.first(),
+        Some(&("instances/foo/file".to_string(), 0, Some(6)))
+    );
+    assert_eq!(
+        updates.last(),
+        Some(&("instances/foo/file".to_string(), 6, Some(6)))
+    );
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_dicom_resume() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m1 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo")
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "FileSize": 6,
+                        "FileUuid": "d8c5eff3-986c-4fe4-b06e-7e52b2a4238e",
+                        "ID": "foo",
+                        "IndexInSeries": 13,
+                        "MainDicomTags": {},
+                        "ParentSeries": "bar",
+                        "Type": "Instance"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+    let m2 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/file")
+        .expect_header("Range", "bytes=3-")
+        .return_status(206)
+        .return_body("bar")
+        .create_on(&mock_server);
+
+    let path = std::env::temp_dir().join("orthanc_test_get_instance_dicom_resume");
+    std::fs::write(&path, "foo").unwrap();
+    let cl = Client::new(url);
+    let size = cl.instance_dicom_resume("foo", &path).unwrap();
+
+    assert_eq!(size, 6);
+    assert_eq!(std::fs::read(&path).unwrap(), b"foobar");
+    assert_eq!(m1.times_called(), 1);
+    assert_eq!(m2.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_dicom_resume_range_not_supported() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m1 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo")
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "FileSize": 6,
+                        "FileUuid": "d8c5eff3-986c-4fe4-b06e-7e52b2a4238e",
+                        "ID": "foo",
+                        "IndexInSeries": 13,
+                        "MainDicomTags": {},
+                        "ParentSeries": "bar",
+                        "Type": "Instance"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+    let m2 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/file")
+        .return_status(200)
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let path = std::env::temp_dir()
+        .join("orthanc_test_get_instance_dicom_resume_range_not_supported");
+    std::fs::write(&path, "foo").unwrap();
+    let cl = Client::new(url);
+    let size = cl.instance_dicom_resume("foo", &path).unwrap();
+
+    assert_eq!(size, 6);
+    assert_eq!(std::fs::read(&path).unwrap(), b"foobar");
+    assert_eq!(m1.times_called(), 1);
+    assert_eq!(m2.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_dicom_resume_complete() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo")
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "FileSize": 6,
+                        "FileUuid": "d8c5eff3-986c-4fe4-b06e-7e52b2a4238e",
+                        "ID": "foo",
+                        "IndexInSeries": 13,
+                        "MainDicomTags": {},
+                        "ParentSeries": "bar",
+                        "Type": "Instance"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let path = std::env::temp_dir().join("orthanc_test_get_instance_dicom_resume_complete");
+    std::fs::write(&path, "foobar").unwrap();
+    let cl = Client::new(url);
+    let size = cl.instance_dicom_resume("foo", &path).unwrap();
+
+    assert_eq!(size, 6);
+    assert_eq!(std::fs::read(&path).unwrap(), b"foobar");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_dicom_resume_size_mismatch() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m1 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo")
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "FileSize": 6,
+                        "FileUuid": "d8c5eff3-986c-4fe4-b06e-7e52b2a4238e",
+                        "ID": "foo",
+                        "IndexInSeries": 13,
+                        "MainDicomTags": {},
+                        "ParentSeries": "bar",
+                        "Type": "Instance"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+    let m2 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/file")
+        .expect_header("Range", "bytes=0-")
+        .return_status(206)
+        .return_body("foo")
+        .create_on(&mock_server);
+
+    let path =
+        std::env::temp_dir().join("orthanc_test_get_instance_dicom_resume_size_mismatch");
+    let _ = std::fs::remove_file(&path);
+    let cl = Client::new(url);
+    let resp = cl.instance_dicom_resume("foo", &path);
+
+    assert_eq!(
+        resp.unwrap_err(),
+        Error {
+            message: "Size of the downloaded file (3 bytes) does not match the expected size (6 bytes)"
+                .to_string(),
+            details: None,
+            integrity: None,
+        }
+    );
+    assert_eq!(m1.times_called(), 1);
+    assert_eq!(m2.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_dicom_range() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/file")
+        .expect_header("Range", "bytes=2-4")
+        .return_status(206)
+        .return_body("oba")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut writer: Vec<u8> = vec![];
+    cl.instance_dicom_range("foo", 2, Some(4), &mut writer)
+        .unwrap();
+
+    assert_eq!(&writer, &b"oba");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_dicom_range_not_supported() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/file")
+        .return_status(200)
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut writer: Vec<u8> = vec![];
+    let resp = cl.instance_dicom_range("foo", 2, None, &mut writer);
+
+    assert_eq!(
+        resp.unwrap_err(),
+        Error {
+            message: "Range requests are not supported".to_string(),
+            details: None,
+            integrity: None,
+        }
+    );
+    assert!(writer.is_empty());
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_attachments() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/attachments")
+        .return_status(200)
+        .return_body(r#"["dicom", "dicom-as-json"]"#)
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl.instance_attachments("foo").unwrap();
+
+    assert_eq!(resp, vec!["dicom", "dicom-as-json"]);
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_attachment_data() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/attachments/dicom/data")
+        .return_status(200)
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut writer: Vec<u8> = vec![];
+    cl.instance_attachment_data("foo", "dicom", &mut writer)
+        .unwrap();
+
+    assert_eq!(&writer, &b"foobar");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_attachment_resume() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m1 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/attachments/dicom/size")
+        .return_status(200)
+        .return_body("6")
+        .create_on(&mock_server);
+    let m2 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/attachments/dicom/data")
+        .expect_header("Range", "bytes=4-")
+        .return_status(206)
+        .return_body("ar")
+        .create_on(&mock_server);
+
+    let path = std::env::temp_dir().join("orthanc_test_get_instance_attachment_resume");
+    std::fs::write(&path, "foob").unwrap();
+    let cl = Client::new(url);
+    let size = cl
+        .instance_attachment_resume("foo", "dicom", &path)
+        .unwrap();
+
+    assert_eq!(size, 6);
+    assert_eq!(std::fs::read(&path).unwrap(), b"foobar");
+    assert_eq!(m1.times_called(), 1);
+    assert_eq!(m2.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_dicom_verified() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m1 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/attachments/dicom/md5")
+        .return_status(200)
+        .return_body("3858f62230ac3c915f300c664312c63f")
+        .create_on(&mock_server);
+    let m2 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/file")
+        .return_status(200)
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url).verify_integrity(true);
+    let mut writer: Vec<u8> = vec![];
+    cl.instance_dicom("foo", &mut writer).unwrap();
+
+    assert_eq!(&writer, &b"foobar");
+    assert_eq!(m1.times_called(), 1);
+    assert_eq!(m2.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_dicom_verified_mismatch() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m1 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/attachments/dicom/md5")
+        .return_status(200)
+        .return_body("3858f62230ac3c915f300c664312c63f")
+        .create_on(&mock_server);
+    let m2 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/file")
+        .return_status(200)
+        .return_body("foobaz")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url).verify_integrity(true);
+    let mut writer: Vec<u8> = vec![];
+    let err = cl.instance_dicom("foo", &mut writer).unwrap_err();
+
+    assert!(err.is_integrity_error());
+    assert_eq!(
+        err,
+        Error {
+            message: concat!(
+                "Integrity check failed: expected MD5 3858f62230ac3c915f300c664312c63f, ",
+                "got 80338e79d2ca9b9c090ebaaa2ef293c7"
+            )
+            .to_string(),
+            details: None,
+            integrity: Some(IntegrityMismatch {
+                expected: "3858f62230ac3c915f300c664312c63f".to_string(),
+                actual: "80338e79d2ca9b9c090ebaaa2ef293c7".to_string(),
+            }),
+        }
+    );
+    assert_eq!(m1.times_called(), 1);
+    assert_eq!(m2.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_attachment_resume_verified() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m1 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/attachments/dicom/size")
+        .return_status(200)
+        .return_body("6")
+        .create_on(&mock_server);
+    let m2 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/attachments/dicom/data")
+        .expect_header("Range", "bytes=3-")
+        .return_status(206)
+        .return_body("bar")
+        .create_on(&mock_server);
+    let m3 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/attachments/dicom/md5")
+        .return_status(200)
+        .return_body("3858f62230ac3c915f300c664312c63f")
+        .create_on(&mock_server);
+
+    let path =
+        std::env::temp_dir().join("orthanc_test_get_instance_attachment_resume_verified");
+    std::fs::write(&path, "foo").unwrap();
+    let cl = Client::new(url).verify_integrity(true);
+    let size = cl
+        .instance_attachment_resume("foo", "dicom", &path)
+        .unwrap();
+
+    assert_eq!(size, 6);
+    assert_eq!(m1.times_called(), 1);
+    assert_eq!(m2.times_called(), 1);
+    assert_eq!(m3.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_attachment_resume_verified_mismatch() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m1 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/attachments/dicom/size")
+        .return_status(200)
+        .return_body("6")
+        .create_on(&mock_server);
+    let m2 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/attachments/dicom/data")
+        .expect_header("Range", "bytes=3-")
+        .return_status(206)
+        .return_body("baz")
+        .create_on(&mock_server);
+    let m3 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/attachments/dicom/md5")
+        .return_status(200)
+        .return_body("3858f62230ac3c915f300c664312c63f")
+        .create_on(&mock_server);
+
+    let path = std::env::temp_dir()
+        .join("orthanc_test_get_instance_attachment_resume_verified_mismatch");
+    std::fs::write(&path, "foo").unwrap();
+    let cl = Client::new(url).verify_integrity(true);
+    let err = cl
+        .instance_attachment_resume("foo", "dicom", &path)
+        .unwrap_err();
+
+    assert_eq!(
+        err.integrity,
+        Some(IntegrityMismatch {
+            expected: "3858f62230ac3c915f300c664312c63f".to_string(),
+            actual: "80338e79d2ca9b9c090ebaaa2ef293c7".to_string(),
+        })
+    );
+    // The corrupt file is truncated, so that the next attempt starts over
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+    assert_eq!(m1.times_called(), 1);
+    assert_eq!(m2.times_called(), 1);
+    assert_eq!(m3.times_called(), 1);
+}
+
+#[test]
+fn test_get_instance_attachment_md5() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/foo/attachments/dicom/md5")
+        .return_status(200)
+        .return_body("3858f62230ac3c915f300c664312c63f")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl.instance_attachment_md5("foo", "dicom").unwrap();
+
+    assert_eq!(resp, "3858f62230ac3c915f300c664312c63f");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_study_media() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/studies/foo/media")
+        .return_status(200)
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut writer: Vec<u8> = vec![];
+    cl.study_media("foo", None, &mut writer).unwrap();
+
+    assert_eq!(&writer, &b"foobar");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_series_media_transcode() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/series/foo/media")
+        .expect_query_param("transcode", "1.2.840.10008.1.2.1")
+        .return_status(200)
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut writer: Vec<u8> = vec![];
+    cl.series_media(
+        "foo",
+        Some(TransferSyntax::ExplicitVrLittleEndian),
+        &mut writer,
+    )
+    .unwrap();
+
+    assert_eq!(&writer, &b"foobar");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_extract_study_with_template() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m1 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/studies/foo/instances")
+        .return_status(200)
+        .return_body(
+            r#"
+                    [
+                        {
+                            "FileSize": 139402,
+                            "FileUuid": "d8c5eff3-986c-4fe4-b06e-7e52b2a4238e",
+                            "ID": "inst1",
+                            "IndexInSeries": 1,
+                            "MainDicomTags": {
+                                "InstanceNumber": "1",
+                                "SOPInstanceUID": "1.2.3.4.5.6789.1"
+                            },
+                            "ParentSeries": "bar",
+                            "Type": "Instance"
+                        },
+                        {
+                            "FileSize": 139402,
+                            "FileUuid": "a8c5eff3-986c-4fe4-b06e-7e52b2a4238e",
+                            "ID": "inst2",
+                            "IndexInSeries": 2,
+                            "MainDicomTags": {
+                                "InstanceNumber": "2",
+                                "SOPInstanceUID": "1.2.3.4.5.6789.2"
+                            },
+                            "ParentSeries": "bar",
+                            "Type": "Instance"
+                        }
+                    ]
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let m2 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/series/bar")
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "ExpectedNumberOfInstances": null,
+                        "ID": "bar",
+                        "Instances": ["inst1", "inst2"],
+                        "IsStable": true,
+                        "LastUpdate": "20200830T191109",
+                        "MainDicomTags": {
+                            "Modality": "MR",
+                            "SeriesNumber": "1101"
+                        },
+                        "ParentStudy": "foo",
+                        "Status": "Unknown",
+                        "Type": "Series"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let m3 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/studies/foo")
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "ID": "foo",
+                        "IsStable": true,
+                        "LastUpdate": "20200830T191109",
+                        "MainDicomTags": {
+                            "StudyDate": "20110101",
+                            "StudyDescription": "CT/MR"
+                        },
+                        "ParentPatient": "baz",
+                        "PatientMainDicomTags": {
+                            "PatientID": "patient_1"
+                        },
+                        "Series": ["bar"],
+                        "Type": "Study"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let m4 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/inst1/file")
+        .return_status(200)
+        .return_body("foo")
+        .create_on(&mock_server);
+
+    let m5 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/instances/inst2/file")
+        .return_status(200)
+        .return_body("bar")
+        .create_on(&mock_server);
+
+    let dir = std::env::temp_dir().join("orthanc_test_extract_study_with_template");
+    let _ = std::fs::remove_dir_all(&dir);
+    let template = PathTemplate::new(
+        "{PatientID}/{StudyDate}_{StudyDescription}/{SeriesNumber}/{InstanceNumber}.dcm",
+    );
+    let cl = Client::new(url);
+    let paths = cl.extract_study("foo", &dir, Some(&template)).unwrap();
+
+    assert_eq!(
+        paths,
+        vec![
+            dir.join("patient_1/20110101_CT_MR/1101/1.dcm"),
+            dir.join("patient_1/20110101_CT_MR/1101/2.dcm"),
+        ]
+    );
+    assert_eq!(std::fs::read(&paths[0]).unwrap(), b"foo");
+    assert_eq!(std::fs::read(&paths[1]).unwrap(), b"bar");
+    assert_eq!(m1.times_called(), 1);
+    assert_eq!(m2.times_called(), 1);
+    assert_eq!(m3.times_called(), 1);
+    assert_eq!(m4.times_called(), 1);
+    assert_eq!(m5.times_called(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    // A duplicate path is detected before any file is written
+    let template = PathTemplate::new("{PatientID}/{SeriesNumber}.dcm");
+    let err = cl.extract_study("foo", &dir, Some(&template)).unwrap_err();
+    assert_eq!(
+        err.message,
+        format!(
+            "Path template renders to a duplicate path: {}",
+            dir.join("patient_1/1101.dcm").display()
+        )
+    );
+    assert!(!dir.exists());
+    assert_eq!(m4.times_called(), 1);
+    assert_eq!(m5.times_called(), 1);
+}
+
+#[test]
+fn test_create_archive() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/tools/create-archive")
+        .expect_json_body(&serde_json::json!({
+            "Resources": ["foo", "bar"],
+            "Synchronous": true,
+            "Transcode": "1.2.840.10008.1.2.1",
+        }))
+        .return_status(200)
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut writer: Vec<u8> = vec![];
+    cl.create_archive(
+        Archive {
+            resources: vec!["foo".to_string(), "bar".to_string()],
+            transcode: Some(TransferSyntax::ExplicitVrLittleEndian),
+            ..Default::default()
+        },
+        &mut writer,
+    )
+    .unwrap();
+
+    assert_eq!(&writer, &b"foobar");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_create_archive_async() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/tools/create-archive")
+        .expect_json_body(&serde_json::json!({
+            "Resources": ["foo"],
+            "Synchronous": false,
+            "Priority": 7,
+        }))
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "ID": "61e5b09d-9d44-4e46-bb3a-e2a4a6e7a4e3",
+                        "Path": "/jobs/61e5b09d-9d44-4e46-bb3a-e2a4a6e7a4e3"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl
+        .create_archive_async(Archive {
+            resources: vec!["foo".to_string()],
+            priority: Some(7),
+            ..Default::default()
+        })
+        .unwrap();
+
+    assert_eq!(
+        resp,
+        Job {
+            id: "61e5b09d-9d44-4e46-bb3a-e2a4a6e7a4e3".to_string(),
+            path: "/jobs/61e5b09d-9d44-4e46-bb3a-e2a4a6e7a4e3".to_string(),
+        }
+    );
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_create_media() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/tools/create-media")
+        .expect_json_body(&serde_json::json!({
+            "Resources": ["foo"],
+            "Synchronous": true,
+        }))
+        .return_status(200)
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut writer: Vec<u8> = vec![];
+    cl.create_media(
+        Archive {
+            resources: vec!["foo".to_string()],
+            ..Default::default()
+        },
+        &mut writer,
+    )
+    .unwrap();
+
+    assert_eq!(&writer, &b"foobar");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_create_media_extended() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/tools/create-media-extended")
+        .expect_json_body(&serde_json::json!({
+            "Resources": ["foo"],
+            "Synchronous": true,
+            "Filename": "foo.zip",
+        }))
+        .return_status(200)
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut writer: Vec<u8> = vec![];
+    cl.create_media_extended(
+        Archive {
+            resources: vec!["foo".to_string()],
+            filename: Some("foo.zip".to_string()),
+            ..Default::default()
+        },
+        &mut writer,
+    )
+    .unwrap();
+
+    assert_eq!(&writer, &b"foobar");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_job() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/jobs/foo")
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "CompletionTime": "20210309T191226.126212",
+                        "Content": {
+                            "ArchiveSize": "4132",
+                            "Description": "REST API",
+                            "InstancesCount": 1,
+                            "UncompressedSize": "4026"
+                        },
+                        "CreationTime": "20210309T191226.108361",
+                        "EffectiveRuntime": 0.017,
+                        "ErrorCode": 0,
+                        "ErrorDescription": "Success",
+                        "ID": "foo",
+                        "Priority": 0,
+                        "Progress": 100,
+                        "State": "Success",
+                        "Timestamp": "20210309T191230.512418",
+                        "Type": "Archive"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl.job("foo").unwrap();
+
+    assert_eq!(
+        resp,
+        JobInfo {
+            id: "foo".to_string(),
+            job_type: "Archive".to_string(),
+            state: "Success".to_string(),
+            progress: 100,
+            priority: 0,
+            creation_time: "20210309T191226.108361".to_string(),
+            completion_time: Some("20210309T191226.126212".to_string()),
+            error_code: 0,
+            error_description: "Success".to_string(),
+            content: serde_json::json!({
+                "ArchiveSize": "4132",
+                "Description": "REST API",
+                "InstancesCount": 1,
+                "UncompressedSize": "4026"
+            }),
+        }
+    );
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_get_job_archive() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/jobs/foo/archive")
+        .return_status(200)
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut writer: Vec<u8> = vec![];
+    cl.job_archive("foo", &mut writer).unwrap();
+
+    assert_eq!(&writer, &b"foobar");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_modality_store() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/modalities/them/store")
+        //.expect_body(r#"["bar", "baz", "qux"]"#)
+        .return_status(200)
+        .return_header("Content-Type", "application/json")
+        .return_body(
+            r#"
+                    {
+                       "Description" : "REST API",
+                       "FailedInstancesCount" : 17,
+                       "InstancesCount" : 42,
+                       "LocalAet" : "US",
+                       "ParentResources" : [ "bar", "baz", "qux" ],
+                       "RemoteAet" : "THEM"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    assert_eq!(
+        cl.modality_store("them", &["bar", "baz", "qux"]).unwrap(),
+        ModalityStoreResult {
+            description: "REST API".to_string(),
+            local_aet: "US".to_string(),
+            remote_aet: "THEM".to_string(),
+            parent_resources: vec!["bar".to_string(), "baz".to_string(), "qux".to_string()],
+            instances_count: 42,
+            failed_instances_count: 17
+        }
+    );
+
+    assert_eq!(
+        cl.store("them", &["bar", "baz", "qux"]).unwrap(),
+        StoreResult {
+            description: "REST API".to_string(),
+            local_aet: "US".to_string(),
+            remote_aet: "THEM".to_string(),
+            parent_resources: vec!["bar".to_string(), "baz".to_string(), "qux".to_string()],
+            instances_count: 42,
+            failed_instances_count: 17
+        }
+    );
+    assert_eq!(m.times_called(), 2);
+}
+
+#[test]
+fn test_peer_store() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/peers/foobar/store")
+        //.expect_body(r#"["bar", "baz", "qux"]"#)
+        .return_status(200)
+        .return_header("Content-Type", "application/json")
+        .return_body(
+            r#"
+                    {
+                       "Description" : "REST API",
+                       "FailedInstancesCount" : 17,
+                       "InstancesCount" : 42,
+                       "ParentResources" : [ "bar", "baz", "qux" ],
+                       "Peer": [ "foobar" ]
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl.peer_store("foobar", &["bar", "baz", "qux"]).unwrap();
+
+    assert_eq!(
+        resp,
+        PeerStoreResult {
+            description: "REST API".to_string(),
+            peer: vec!["foobar".to_string()],
+            parent_resources: vec!["bar".to_string(), "baz".to_string(), "qux".to_string()],
+            instances_count: 42,
+            failed_instances_count: 17
+        }
+    );
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_peer_store_transcoded() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/peers/foobar/store")
+        .expect_json_body(&serde_json::json!({
+            "Resources": ["bar", "baz"],
+            "Transcode": "1.2.840.10008.1.2.1",
+            "Synchronous": true,
+        }))
+        .return_status(200)
+        .return_header("Content-Type", "application/json")
+        .return_body(
+            r#"
+                    {
+                       "Description" : "REST API",
+                       "FailedInstancesCount" : 0,
+                       "InstancesCount" : 2,
+                       "ParentResources" : [ "bar", "baz" ],
+                       "Peer": [ "foobar" ]
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl
+        .peer_store_transcoded(
+            "foobar",
+            &["bar", "baz"],
+            TransferSyntax::ExplicitVrLittleEndian,
+        )
+        .unwrap();
+
+    assert_eq!(resp.instances_count, 2);
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_modify_patient() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/patients/foo/modify")
+        .expect_json_body(&Modification {
+            replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
+            remove: Some(vec!["Tag2".to_string()]),
+            force: Some(true),
+            ..Default::default()
+        })
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "ID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
+                        "Path": "/patients/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
+                        "PatientID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
+                        "Type": "Patient"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl
+        .modify_patient(
+            "foo",
+            Modification {
+                replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
+                remove: Some(vec!["Tag2".to_string()]),
+                force: Some(true),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        resp,
+        ModificationResult {
+            id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".into(),
+            patient_id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".into(),
+            path: "/patients/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
+            entity: EntityKind::Patient,
+        }
+    );
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_modify_study_async() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/studies/foo/modify")
+        .expect_json_body(&serde_json::json!({
+            "Replace": {
+                "StudyInstanceUID": "1.2.3.4",
+                "ProcedureCodeSequence": [{"CodeValue": "12345"}]
+            },
+            "KeepSource": false,
+            "Transcode": "1.2.840.10008.1.2.1",
+            "Permissive": true,
+            "Priority": 3,
+            "Asynchronous": true,
+            "Force": true
+        }))
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "ID": "61e5b09d-9d44-4e46-bb3a-e2a4a6e7a4e3",
+                        "Path": "/jobs/61e5b09d-9d44-4e46-bb3a-e2a4a6e7a4e3"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let modification = ModificationBuilder::new()
+        .replace("StudyInstanceUID", "1.2.3.4")
+        .replace(
+            "ProcedureCodeSequence",
+            serde_json::json!([{"CodeValue": "12345"}]),
+        )
+        .keep_source(false)
+        .transcode("1.2.840.10008.1.2.1")
+        .permissive(true)
+        .priority(3)
+        .force(true)
+        .build()
+        .unwrap();
+    let cl = Client::new(url);
+    let resp = cl.modify_study_async("foo", modification).unwrap();
+
+    assert_eq!(
+        resp,
+        Job {
+            id: "61e5b09d-9d44-4e46-bb3a-e2a4a6e7a4e3".to_string(),
+            path: "/jobs/61e5b09d-9d44-4e46-bb3a-e2a4a6e7a4e3".to_string(),
+        }
+    );
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_split_study() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/studies/foo/split")
+        .expect_json_body(&serde_json::json!({
+            "Series": ["bar", "baz"],
+            "Replace": {"StudyDescription": "Misfiled"},
+            "Remove": ["AccessionNumber"],
+            "KeepSource": false
+        }))
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "TargetStudy": "qux",
+                        "TargetStudyUID": "1.2.3.4"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl
+        .split_study(
+            "foo",
+            StudySplit {
+                series: Some(vec!["bar".into(), "baz".into()]),
+                replace: Some(
+                    hashmap! {"StudyDescription".to_string() => "Misfiled".to_string()},
+                ),
+                remove: Some(vec!["AccessionNumber".to_string()]),
+                keep_source: Some(false),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        resp,
+        StudySplitResult {
+            target_study: "qux".into(),
+            target_study_uid: "1.2.3.4".to_string(),
+        }
+    );
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_split_study_async() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/studies/foo/split")
+        .expect_json_body(&serde_json::json!({
+            "Instances": ["bar"],
+            "Priority": 5,
+            "Asynchronous": true
+        }))
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "ID": "61e5b09d-9d44-4e46-bb3a-e2a4a6e7a4e3",
+                        "Path": "/jobs/61e5b09d-9d44-4e46-bb3a-e2a4a6e7a4e3"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl
+        .split_study_async(
+            "foo",
+            StudySplit {
+                instances: Some(vec!["bar".into()]),
+                priority: Some(5),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(resp.id, "61e5b09d-9d44-4e46-bb3a-e2a4a6e7a4e3");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_merge_study() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/studies/foo/merge")
+        .expect_json_body(&serde_json::json!({
+            "Resources": ["bar", "baz"],
+            "KeepSource": true
+        }))
+        .return_status(200)
+        .return_body(r#"{"TargetStudy": "foo"}"#)
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl
+        .merge_study(
+            "foo",
+            StudyMerge {
+                resources: vec!["bar".to_string(), "baz".to_string()],
+                keep_source: Some(true),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        resp,
+        StudyMergeResult {
+            target_study: "foo".into()
+        }
+    );
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_merge_study_async() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/studies/foo/merge")
+        .expect_json_body(&serde_json::json!({
+            "Resources": ["bar"],
+            "Asynchronous": true
+        }))
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "ID": "61e5b09d-9d44-4e46-bb3a-e2a4a6e7a4e3",
+                        "Path": "/jobs/61e5b09d-9d44-4e46-bb3a-e2a4a6e7a4e3"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl
+        .merge_study_async(
+            "foo",
+            StudyMerge {
+                resources: vec!["bar".to_string()],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(resp.path, "/jobs/61e5b09d-9d44-4e46-bb3a-e2a4a6e7a4e3");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_anonymize_study_async() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/studies/foo/anonymize")
+        .expect_json_body(&serde_json::json!({
+            "Replace": {"PatientID": "SUBJ-0042", "PatientName": "SUBJ-0042"},
+            "Keep": ["StationName", "DeviceSerialNumber", "DeviceUID", "DetectorID", "GantryID",
+                     "PlateID", "CassetteID", "GeneratorID"],
+            "KeepSource": false,
+            "Asynchronous": true
+        }))
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "ID": "61e5b09d-9d44-4e46-bb3a-e2a4a6e7a4e3",
+                        "Path": "/jobs/61e5b09d-9d44-4e46-bb3a-e2a4a6e7a4e3"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let anonymization = AnonymizationBuilder::new()
+        .profile(AnonymizationProfile::RetainDeviceIdentity)
+        .replace("PatientID", "SUBJ-0042")
+        .replace("PatientName", "SUBJ-0042")
+        .keep_source(false)
+        .build()
+        .unwrap();
+    let cl = Client::new(url);
+    let resp = cl
+        .anonymize_study_async("foo", Some(anonymization))
+        .unwrap();
+
+    assert_eq!(
+        resp,
+        Job {
+            id: "61e5b09d-9d44-4e46-bb3a-e2a4a6e7a4e3".to_string(),
+            path: "/jobs/61e5b09d-9d44-4e46-bb3a-e2a4a6e7a4e3".to_string(),
+        }
+    );
+    assert_eq!(m.times_called(), 1);
+}
+
+/// Mocks a study `foo` of patient `c137` with the series `s1` and `s2`, as well as their
+/// anonymization with the pseudonyms of `pseudonymizer_store`
+fn mock_pseudonymized_study(mock_server: &MockServer) -> Vec<MockRef<'_>> {
+    let study = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/studies/foo")
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "ID": "foo",
+                        "IsStable": true,
+                        "LastUpdate": "20200830T191109",
+                        "MainDicomTags": {
+                            "StudyInstanceUID": "1.2.3.4.5.6789"
+                        },
+                        "ParentPatient": "bar",
+                        "PatientMainDicomTags": {
+                            "PatientID": "c137",
+                            "PatientName": "Rick Sanchez"
+                        },
+                        "Series": ["s1", "s2"],
+                        "Type": "Study"
+                    }
+                "#,
+        )
+        .create_on(mock_server);
+    let series = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/studies/foo/series")
+        .return_status(200)
+        .return_body(
+            r#"
+                    [
+                        {
+                            "ExpectedNumberOfInstances": null,
+                            "ID": "s1",
+                            "Instances": [],
+                            "IsStable": true,
+                            "LastUpdate": "20200830T191109",
+                            "MainDicomTags": {
+                                "SeriesInstanceUID": "1.2.3.4.5.6789.1"
+                            },
+                            "ParentStudy": "foo",
+                            "Status": "Unknown",
+                            "Type": "Series"
+                        },
+                        {
+                            "ExpectedNumberOfInstances": null,
+                            "ID": "s2",
+                            "Instances": [],
+                            "IsStable": true,
+                            "LastUpdate": "20200830T191109",
+                            "MainDicomTags": {
+                                "SeriesInstanceUID": "1.2.3.4.5.6789.2"
+                            },
+                            "ParentStudy": "foo",
+                            "Status": "Unknown",
+                            "Type": "Series"
+                        }
+                    ]
+                "#,
+        )
+        .create_on(mock_server);
+    let mut mocks = vec![study, series];
+    for (id, pseudonym) in &[("s1", "2.25.1"), ("s2", "2.25.2")] {
+        mocks.push(
+            Mock::new()
+                .expect_method(Method::POST)
+                .expect_path(&format!("/series/{}/anonymize", id))
+                .expect_json_body(&serde_json::json!({
+                    "Replace": {
+                        "PatientID": "ANON-1",
+                        "PatientName": "ANON-1",
+                        "StudyInstanceUID": "2.25.42",
+                        "SeriesInstanceUID": pseudonym
+                    },
+                    "Keep": ["StudyDate"],
+                    "Force": true
+                }))
+                .return_status(200)
+                .return_body(&format!(
+                    r#"
+                        {{
+                            "ID": "new_{}",
+                            "Path": "/series/new_{}",
+                            "PatientID": "qux",
+                            "Type": "Series"
+                        }}
+                    "#,
+                    id, id
+                ))
+                .create_on(mock_server),
+        );
+    }
+    mocks
+}
+
+fn pseudonymizer_store() -> MemoryStore {
+    let mut store = MemoryStore::new();
+    for (tag, original, pseudonym) in &[
+        ("PatientID", "c137", "ANON-1"),
+        ("StudyInstanceUID", "1.2.3.4.5.6789", "2.25.42"),
+        ("SeriesInstanceUID", "1.2.3.4.5.6789.1", "2.25.1"),
+        ("SeriesInstanceUID", "1.2.3.4.5.6789.2", "2.25.2"),
+    ] {
+        store
+            .insert(PseudonymEntry {
+                tag: tag.to_string(),
+                original: original.to_string(),
+                pseudonym: pseudonym.to_string(),
+            })
+            .unwrap();
+    }
+    store
+}
+
+#[test]
+fn test_pseudonymizer_anonymize_study() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+    let mocks = mock_pseudonymized_study(&mock_server);
+
+    let mut pseudonymizer = Pseudonymizer::new(pseudonymizer_store());
+    let cl = Client::new(url);
+    let anonymization = Anonymization {
+        keep: Some(vec!["StudyDate".to_string()]),
+        ..Default::default()
+    };
+    let resp = pseudonymizer
+        .anonymize_study(&cl, "foo", Some(anonymization))
+        .unwrap();
+
+    let id = study_id("ANON-1", "2.25.42");
+    assert_eq!(
+        resp,
+        ModificationResult {
+            id: id.to_string(),
+            patient_id: patient_id("ANON-1").to_string(),
+            path: format!("/studies/{}", id),
+            entity: EntityKind::Study,
+        }
+    );
+    assert_eq!(pseudonymizer.store().entries().unwrap().len(), 4);
+    for m in &mocks {
+        assert_eq!(m.times_called(), 1);
+    }
+}
+
+#[test]
+fn test_pseudonymizer_anonymize_patient() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+    let m = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/patients/bar")
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "ID": "bar",
+                        "IsStable": true,
+                        "LastUpdate": "20200830T191109",
+                        "MainDicomTags": {
+                            "PatientID": "c137",
+                            "PatientName": "Rick Sanchez"
+                        },
+                        "Studies": ["foo"],
+                        "Type": "Patient"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+    let mocks = mock_pseudonymized_study(&mock_server);
+
+    let mut pseudonymizer = Pseudonymizer::new(pseudonymizer_store());
+    let cl = Client::new(url);
+    let anonymization = Anonymization {
+        keep: Some(vec!["StudyDate".to_string()]),
+        ..Default::default()
+    };
+    let resp = pseudonymizer
+        .anonymize_patient(&cl, "bar", Some(anonymization))
+        .unwrap();
+
+    let id = patient_id("ANON-1");
+    assert_eq!(
+        resp,
+        ModificationResult {
+            id: id.to_string(),
+            patient_id: id.to_string(),
+            path: format!("/patients/{}", id),
+            entity: EntityKind::Patient,
+        }
+    );
+    assert_eq!(pseudonymizer.store().entries().unwrap().len(), 4);
+    assert_eq!(m.times_called(), 1);
+    for m in &mocks {
+        assert_eq!(m.times_called(), 1);
+    }
+}
+
+#[test]
+fn test_modify_study() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/studies/foo/modify")
+        .expect_json_body(&Modification {
+            replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
+            remove: Some(vec!["Tag2".to_string()]),
+            force: None,
+            ..Default::default()
+        })
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "ID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
+                        "Path": "/studies/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
+                        "PatientID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
+                        "Type": "Study"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl
+        .modify_study(
+            "foo",
+            Modification {
+                replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
+                remove: Some(vec!["Tag2".to_string()]),
+                force: None,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        resp,
+        ModificationResult {
+            id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".into(),
+            patient_id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".into(),
+            path: "/studies/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
+            entity: EntityKind::Study,
+        }
+    );
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_modify_series() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/series/foo/modify")
+        .expect_json_body(&Modification {
+            replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
+            remove: Some(vec!["Tag2".to_string()]),
+            force: None,
+            ..Default::default()
+        })
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "ID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
+                        "Path": "/series/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
+                        "PatientID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
+                        "Type": "Series"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl
+        .modify_series(
+            "foo",
+            Modification {
+                replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
+                remove: Some(vec!["Tag2".to_string()]),
+                force: None,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        resp,
+        ModificationResult {
+            id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".into(),
+            patient_id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".into(),
+            path: "/series/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
+            entity: EntityKind::Series,
+        }
+    );
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_modify_instance() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/instances/foo/modify")
+        .expect_json_body(&Modification {
+            replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
+            remove: Some(vec!["Tag2".to_string()]),
+            force: None,
+            ..Default::default()
+        })
+        .return_status(200)
+        .return_body("foobar")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let mut writer: Vec<u8> = vec![];
+
+    cl.modify_instance(
+        "foo",
+        Modification {
+            replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
+            remove: Some(vec!["Tag2".to_string()]),
+            force: None,
+            ..Default::default()
+        },
+        &mut writer,
+    )
+    .unwrap();
+
+    assert_eq!(&writer, &b"foobar");
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_anonymize_patient() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/patients/foo/anonymize")
+        .expect_json_body(&Anonymization {
+            replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
+            keep: Some(vec!["Tag2".to_string(), "Tag3".to_string()]),
+            keep_private_tags: None,
+            dicom_version: None,
+            force: None,
+            ..Default::default()
+        })
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "ID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
+                        "Path": "/patients/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
+                        "PatientID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
+                        "Type": "Patient"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    let resp = cl
+        .anonymize_patient(
+            "foo",
+            Some(Anonymization {
+                replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
+                keep: Some(vec!["Tag2".to_string(), "Tag3".to_string()]),
+                keep_private_tags: None,
+                dicom_version: None,
+                force: None,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
+
+    assert_eq!(
+        resp,
+        ModificationResult {
+            id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".into(),
+            patient_id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".into(),
+            path: "/patients/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
+            entity: EntityKind::Patient,
+        }
+    );
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_anonymize_study() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/studies/foo/anonymize")
+        .expect_json_body(&Anonymization {
+            replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
+            keep: Some(vec!["Tag2".to_string(), "Tag3".to_string()]),
+            keep_private_tags: Some(true),
+            dicom_version: None,
+            force: None,
+            ..Default::default()
+        })
         .return_status(200)
-        .return_header("Content-Type", "application/json")
-        .return_body("foobar")
+        .return_body(
+            r#"
+                    {
+                        "ID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
+                        "Path": "/studies/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
+                        "PatientID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
+                        "Type": "Study"
+                    }
+                "#,
+        )
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    let mut writer: Vec<u8> = vec![];
-    cl.study_dicom("foo", &mut writer).unwrap();
+    let resp = cl
+        .anonymize_study(
+            "foo",
+            Some(Anonymization {
+                replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
+                keep: Some(vec!["Tag2".to_string(), "Tag3".to_string()]),
+                keep_private_tags: Some(true),
+                dicom_version: None,
+                force: None,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
 
-    assert_eq!(&writer, &b"foobar");
+    assert_eq!(
+        resp,
+        ModificationResult {
+            id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".into(),
+            patient_id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".into(),
+            path: "/studies/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
+            entity: EntityKind::Study,
+        }
+    );
     assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_get_series_dicom() {
+fn test_anonymize_series() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let m = Mock::new()
-        .expect_method(Method::GET)
-        .expect_path("/series/foo/archive")
+        .expect_method(Method::POST)
+        .expect_path("/series/foo/anonymize")
+        .expect_json_body(&Anonymization {
+            replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
+            keep: Some(vec!["Tag2".to_string(), "Tag3".to_string()]),
+            keep_private_tags: Some(false),
+            dicom_version: None,
+            force: None,
+            ..Default::default()
+        })
         .return_status(200)
-        .return_header("Content-Type", "application/json")
-        .return_body("foobar")
+        .return_body(
+            r#"
+                    {
+                        "ID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
+                        "Path": "/series/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
+                        "PatientID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
+                        "Type": "Series"
+                    }
+                "#,
+        )
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    let mut writer: Vec<u8> = vec![];
-    cl.series_dicom("foo", &mut writer).unwrap();
+    let resp = cl
+        .anonymize_series(
+            "foo",
+            Some(Anonymization {
+                replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
+                keep: Some(vec!["Tag2".to_string(), "Tag3".to_string()]),
+                keep_private_tags: Some(false),
+                dicom_version: None,
+                force: None,
+                ..Default::default()
+            }),
+        )
+        .unwrap();
 
-    assert_eq!(&writer, &b"foobar");
+    assert_eq!(
+        resp,
+        ModificationResult {
+            id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".into(),
+            patient_id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".into(),
+            path: "/series/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
+            entity: EntityKind::Series,
+        }
+    );
     assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_get_instance_dicom() {
+fn test_anonymize_instance() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let m = Mock::new()
-        .expect_method(Method::GET)
-        .expect_path("/instances/foo/file")
+        .expect_method(Method::POST)
+        .expect_path("/instances/foo/anonymize")
+        .expect_json_body(&Anonymization {
+            replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
+            keep: Some(vec!["Tag2".to_string(), "Tag3".to_string()]),
+            keep_private_tags: None,
+            dicom_version: None,
+            force: None,
+            ..Default::default()
+        })
         .return_status(200)
-        .return_header("Content-Type", "application/json")
         .return_body("foobar")
         .create_on(&mock_server);
 
     let cl = Client::new(url);
     let mut writer: Vec<u8> = vec![];
-    cl.instance_dicom("foo", &mut writer).unwrap();
+    cl.anonymize_instance(
+        "foo",
+        Some(Anonymization {
+            replace: Some(hashmap! {"Tag1".to_string() => "value1".into()}),
+            keep: Some(vec!["Tag2".to_string(), "Tag3".to_string()]),
+            keep_private_tags: None,
+            dicom_version: None,
+            force: None,
+            // Ignored, an instance is always anonymized synchronously
+            asynchronous: Some(true),
+            ..Default::default()
+        }),
+        &mut writer,
+    )
+    .unwrap();
 
     assert_eq!(&writer, &b"foobar");
     assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_modality_store() {
+fn test_delete_patient() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let m = Mock::new()
-        .expect_method(Method::POST)
-        .expect_path("/modalities/them/store")
-        //.expect_body(r#"["bar", "baz", "qux"]"#)
+        .expect_method(Method::DELETE)
+        .expect_path("/patients/foo")
         .return_status(200)
-        .return_header("Content-Type", "application/json")
-        .return_body(
-            r#"
-                    {
-                       "Description" : "REST API",
-                       "FailedInstancesCount" : 17,
-                       "InstancesCount" : 42,
-                       "LocalAet" : "US",
-                       "ParentResources" : [ "bar", "baz", "qux" ],
-                       "RemoteAet" : "THEM"
-                    }
-                "#,
-        )
+        .return_body(r#"{"RemainingAncestor": null}"#)
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    assert_eq!(
-        cl.modality_store("them", &["bar", "baz", "qux"]).unwrap(),
-        ModalityStoreResult {
-            description: "REST API".to_string(),
-            local_aet: "US".to_string(),
-            remote_aet: "THEM".to_string(),
-            parent_resources: vec!["bar".to_string(), "baz".to_string(), "qux".to_string()],
-            instances_count: 42,
-            failed_instances_count: 17
-        }
-    );
+    let resp = cl.delete_patient("foo").unwrap();
 
     assert_eq!(
-        cl.store("them", &["bar", "baz", "qux"]).unwrap(),
-        StoreResult {
-            description: "REST API".to_string(),
-            local_aet: "US".to_string(),
-            remote_aet: "THEM".to_string(),
-            parent_resources: vec!["bar".to_string(), "baz".to_string(), "qux".to_string()],
-            instances_count: 42,
-            failed_instances_count: 17
+        resp,
+        RemainingAncestor {
+            remaining_ancestor: None
         }
     );
-    assert_eq!(m.times_called(), 2);
+    assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_peer_store() {
+fn test_delete_study_typed_id() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let m = Mock::new()
-        .expect_method(Method::POST)
-        .expect_path("/peers/foobar/store")
-        //.expect_body(r#"["bar", "baz", "qux"]"#)
+        .expect_method(Method::DELETE)
+        .expect_path("/studies/foo")
         .return_status(200)
-        .return_header("Content-Type", "application/json")
-        .return_body(
-            r#"
-                    {
-                       "Description" : "REST API",
-                       "FailedInstancesCount" : 17,
-                       "InstancesCount" : 42,
-                       "ParentResources" : [ "bar", "baz", "qux" ],
-                       "Peer": [ "foobar" ]
-                    }
-                "#,
-        )
+        .return_body(r#"{"RemainingAncestor": null}"#)
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    let resp = cl.peer_store("foobar", &["bar", "baz", "qux"]).unwrap();
+    let id = StudyId::from("foo");
+    let resp = cl.delete_study(&id).unwrap();
 
     assert_eq!(
         resp,
-        PeerStoreResult {
-            description: "REST API".to_string(),
-            peer: vec!["foobar".to_string()],
-            parent_resources: vec!["bar".to_string(), "baz".to_string(), "qux".to_string()],
-            instances_count: 42,
-            failed_instances_count: 17
+        RemainingAncestor {
+            remaining_ancestor: None
         }
     );
     assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_modify_patient() {
+fn test_delete_study() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let m = Mock::new()
-        .expect_method(Method::POST)
-        .expect_path("/patients/foo/modify")
-        .expect_json_body(&Modification {
-            replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
-            remove: Some(vec!["Tag2".to_string()]),
-            force: Some(true),
-        })
+        .expect_method(Method::DELETE)
+        .expect_path("/studies/foo")
         .return_status(200)
         .return_body(
             r#"
                     {
-                        "ID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
-                        "Path": "/patients/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
-                        "PatientID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
-                        "Type": "Patient"
+                        "RemainingAncestor": {
+                            "ID": "bar",
+                            "Path": "/patients/bar",
+                            "Type": "Patient"
+                        }
                     }
                 "#,
         )
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    let resp = cl
-        .modify_patient(
-            "foo",
-            Modification {
-                replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
-                remove: Some(vec!["Tag2".to_string()]),
-                force: Some(true),
-            },
-        )
-        .unwrap();
+    let resp = cl.delete_study("foo").unwrap();
 
     assert_eq!(
         resp,
-        ModificationResult {
-            id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-            patient_id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-            path: "/patients/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-            entity: EntityKind::Patient,
+        RemainingAncestor {
+            remaining_ancestor: Some(Ancestor {
+                id: "bar".to_string(),
+                path: "/patients/bar".to_string(),
+                entity: EntityKind::Patient,
+            })
         }
     );
     assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_modify_study() {
+fn test_delete_series() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let m = Mock::new()
-        .expect_method(Method::POST)
-        .expect_path("/studies/foo/modify")
-        .expect_json_body(&Modification {
-            replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
-            remove: Some(vec!["Tag2".to_string()]),
-            force: None,
-        })
+        .expect_method(Method::DELETE)
+        .expect_path("/series/foo")
         .return_status(200)
         .return_body(
             r#"
                     {
-                        "ID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
-                        "Path": "/studies/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
-                        "PatientID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
-                        "Type": "Study"
+                        "RemainingAncestor": {
+                            "ID": "bar",
+                            "Path": "/studies/bar",
+                            "Type": "Study"
+                        }
                     }
                 "#,
         )
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    let resp = cl
-        .modify_study(
-            "foo",
-            Modification {
-                replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
-                remove: Some(vec!["Tag2".to_string()]),
-                force: None,
-            },
-        )
-        .unwrap();
+    let resp = cl.delete_series("foo").unwrap();
 
     assert_eq!(
         resp,
-        ModificationResult {
-            id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-            patient_id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-            path: "/studies/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-            entity: EntityKind::Study,
+        RemainingAncestor {
+            remaining_ancestor: Some(Ancestor {
+                id: "bar".to_string(),
+                path: "/studies/bar".to_string(),
+                entity: EntityKind::Study,
+            })
         }
     );
     assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_modify_series() {
+fn test_delete_instance() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let m = Mock::new()
-        .expect_method(Method::POST)
-        .expect_path("/series/foo/modify")
-        .expect_json_body(&Modification {
-            replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
-            remove: Some(vec!["Tag2".to_string()]),
-            force: None,
-        })
+        .expect_method(Method::DELETE)
+        .expect_path("/instances/foo")
         .return_status(200)
         .return_body(
             r#"
                     {
-                        "ID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
-                        "Path": "/series/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
-                        "PatientID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
-                        "Type": "Series"
+                        "RemainingAncestor": {
+                            "ID": "bar",
+                            "Path": "/series/bar",
+                            "Type": "Series"
+                        }
                     }
                 "#,
         )
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    let resp = cl
-        .modify_series(
-            "foo",
-            Modification {
-                replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
-                remove: Some(vec!["Tag2".to_string()]),
-                force: None,
-            },
-        )
-        .unwrap();
+    let resp = cl.delete_instance("foo").unwrap();
 
     assert_eq!(
         resp,
-        ModificationResult {
-            id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-            patient_id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-            path: "/series/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-            entity: EntityKind::Series,
+        RemainingAncestor {
+            remaining_ancestor: Some(Ancestor {
+                id: "bar".to_string(),
+                path: "/series/bar".to_string(),
+                entity: EntityKind::Series,
+            })
         }
     );
     assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_modify_instance() {
+fn test_modality_echo() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let m = Mock::new()
         .expect_method(Method::POST)
-        .expect_path("/instances/foo/modify")
-        .expect_json_body(&Modification {
-            replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
-            remove: Some(vec!["Tag2".to_string()]),
-            force: None,
-        })
+        .expect_path("/modalities/foo/echo")
         .return_status(200)
-        .return_body("foobar")
+        .return_body("{}")
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    let mut writer: Vec<u8> = vec![];
+    assert_eq!(cl.modality_echo("foo", None).unwrap(), ());
+    assert_eq!(cl.echo("foo", None).unwrap(), ());
+    assert_eq!(m.times_called(), 2);
+}
 
-    cl.modify_instance(
-        "foo",
-        Modification {
-            replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
-            remove: Some(vec!["Tag2".to_string()]),
-            force: None,
-        },
-        &mut writer,
-    )
-    .unwrap();
+#[test]
+fn test_echo_with_timeout() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
 
-    assert_eq!(&writer, &b"foobar");
-    assert_eq!(m.times_called(), 1);
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/modalities/foo/echo")
+        .expect_json_body(&hashmap! {"Timeout" => 42})
+        .return_status(200)
+        .return_body("{}")
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    assert_eq!(cl.modality_echo("foo", Some(42)).unwrap(), ());
+    assert_eq!(cl.echo("foo", Some(42)).unwrap(), ());
+    assert_eq!(m.times_called(), 2);
 }
 
 #[test]
-fn test_anonymize_patient() {
+fn test_echo_failed() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let m = Mock::new()
         .expect_method(Method::POST)
-        .expect_path("/patients/foo/anonymize")
-        .expect_json_body(&Anonymization {
-            replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
-            keep: Some(vec!["Tag2".to_string(), "Tag3".to_string()]),
-            keep_private_tags: None,
-            dicom_version: None,
-            force: None,
-        })
+        .expect_path("/modalities/foo/echo")
+        .return_status(500)
+        .create_on(&mock_server);
+
+    let cl = Client::new(url);
+    assert_eq!(
+        cl.modality_echo("foo", None).unwrap_err(),
+        Error {
+            message: "API error: 500 Internal Server Error".to_string(),
+            details: None,
+            integrity: None,
+        }
+    );
+    assert_eq!(
+        cl.echo("foo", None).unwrap_err(),
+        Error {
+            message: "API error: 500 Internal Server Error".to_string(),
+            details: None,
+            integrity: None,
+        }
+    );
+    assert_eq!(m.times_called(), 2);
+}
+
+#[test]
+fn test_upload_dicom() {
+    let mock_server = MockServer::start();
+    let url = mock_server.url("");
+
+    let m = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/instances")
+        .expect_body("quux")
         .return_status(200)
         .return_body(
             r#"
                     {
-                        "ID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
-                        "Path": "/patients/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
-                        "PatientID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
-                        "Type": "Patient"
+                        "ID": "foo",
+                        "ParentPatient": "bar",
+                        "ParentSeries": "baz",
+                        "ParentStudy": "qux",
+                        "Path": "/instances/foo",
+                        "Status": "Success"
                     }
                 "#,
         )
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    let resp = cl
-        .anonymize_patient(
-            "foo",
-            Some(Anonymization {
-                replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
-                keep: Some(vec!["Tag2".to_string(), "Tag3".to_string()]),
-                keep_private_tags: None,
-                dicom_version: None,
-                force: None,
-            }),
-        )
-        .unwrap();
+    let resp = cl.upload("quux".as_bytes()).unwrap();
 
     assert_eq!(
         resp,
-        ModificationResult {
-            id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-            patient_id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-            path: "/patients/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-            entity: EntityKind::Patient,
+        UploadResult {
+            id: Some("foo".into()),
+            status: UploadStatus::Success,
+            path: Some("/instances/foo".to_string()),
+            parent_patient: Some("bar".into()),
+            parent_study: Some("qux".into()),
+            parent_series: Some("baz".into()),
         }
     );
     assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_anonymize_study() {
+fn test_upload_archive() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let m = Mock::new()
         .expect_method(Method::POST)
-        .expect_path("/studies/foo/anonymize")
-        .expect_json_body(&Anonymization {
-            replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
-            keep: Some(vec!["Tag2".to_string(), "Tag3".to_string()]),
-            keep_private_tags: Some(true),
-            dicom_version: None,
-            force: None,
-        })
+        .expect_path("/instances")
+        .expect_body("quux")
         .return_status(200)
         .return_body(
             r#"
-                    {
-                        "ID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
-                        "Path": "/studies/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
-                        "PatientID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
-                        "Type": "Study"
-                    }
+                    [
+                        {
+                            "ID": "foo",
+                            "ParentPatient": "bar",
+                            "ParentSeries": "baz",
+                            "ParentStudy": "qux",
+                            "Path": "/instances/foo",
+                            "Status": "Success"
+                        },
+                        {
+                            "ID": "quuz",
+                            "ParentPatient": "bar",
+                            "ParentSeries": "baz",
+                            "ParentStudy": "qux",
+                            "Path": "/instances/quuz",
+                            "Status": "AlreadyStored"
+                        },
+                        {
+                            "Status": "Failure"
+                        }
+                    ]
                 "#,
         )
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    let resp = cl
-        .anonymize_study(
-            "foo",
-            Some(Anonymization {
-                replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
-                keep: Some(vec!["Tag2".to_string(), "Tag3".to_string()]),
-                keep_private_tags: Some(true),
-                dicom_version: None,
-                force: None,
-            }),
-        )
-        .unwrap();
+    let resp = cl.upload_archive(std::io::Cursor::new("quux"), 4).unwrap();
 
     assert_eq!(
         resp,
-        ModificationResult {
-            id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-            patient_id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-            path: "/studies/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-            entity: EntityKind::Study,
-        }
+        vec![
+            UploadResult {
+                id: Some("foo".into()),
+                status: UploadStatus::Success,
+                path: Some("/instances/foo".to_string()),
+                parent_patient: Some("bar".into()),
+                parent_study: Some("qux".into()),
+                parent_series: Some("baz".into()),
+            },
+            UploadResult {
+                id: Some("quuz".into()),
+                status: UploadStatus::AlreadyStored,
+                path: Some("/instances/quuz".to_string()),
+                parent_patient: Some("bar".into()),
+                parent_study: Some("qux".into()),
+                parent_series: Some("baz".into()),
+            },
+            UploadResult {
+                id: None,
+                status: UploadStatus::Failure,
+                path: None,
+                parent_patient: None,
+                parent_study: None,
+                parent_series: None,
+            },
+        ]
     );
     assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_anonymize_series() {
+fn test_upload_dicom_reader() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let m = Mock::new()
         .expect_method(Method::POST)
-        .expect_path("/series/foo/anonymize")
-        .expect_json_body(&Anonymization {
-            replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
-            keep: Some(vec!["Tag2".to_string(), "Tag3".to_string()]),
-            keep_private_tags: Some(false),
-            dicom_version: None,
-            force: None,
-        })
+        .expect_path("/instances")
+        .expect_body("quux")
+        .expect_header("Content-Length", "4")
         .return_status(200)
         .return_body(
             r#"
                     {
-                        "ID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
-                        "Path": "/series/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
-                        "PatientID": "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c",
-                        "Type": "Series"
+                        "ID": "foo",
+                        "ParentPatient": "bar",
+                        "ParentSeries": "baz",
+                        "ParentStudy": "qux",
+                        "Path": "/instances/foo",
+                        "Status": "Success"
                     }
                 "#,
         )
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    let resp = cl
-        .anonymize_series(
-            "foo",
-            Some(Anonymization {
-                replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
-                keep: Some(vec!["Tag2".to_string(), "Tag3".to_string()]),
-                keep_private_tags: Some(false),
-                dicom_version: None,
-                force: None,
-            }),
-        )
-        .unwrap();
+    let resp = cl.upload_reader(std::io::Cursor::new("quux"), 4).unwrap();
 
     assert_eq!(
         resp,
-        ModificationResult {
-            id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-            patient_id: "86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-            path: "/series/86a3054b-32bb888a-e5f42e28-4b2e82d2-b1d7e14c".to_string(),
-            entity: EntityKind::Series,
+        UploadResult {
+            id: Some("foo".into()),
+            status: UploadStatus::Success,
+            path: Some("/instances/foo".to_string()),
+            parent_patient: Some("bar".into()),
+            parent_study: Some("qux".into()),
+            parent_series: Some("baz".into()),
         }
     );
     assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_anonymize_instance() {
+fn test_upload_dicom_file() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let m = Mock::new()
         .expect_method(Method::POST)
-        .expect_path("/instances/foo/anonymize")
-        .expect_json_body(&Anonymization {
-            replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
-            keep: Some(vec!["Tag2".to_string(), "Tag3".to_string()]),
-            keep_private_tags: None,
-            dicom_version: None,
-            force: None,
-        })
+        .expect_path("/instances")
+        .expect_body("quux")
+        .expect_header("Content-Length", "4")
         .return_status(200)
-        .return_body("foobar")
-        .create_on(&mock_server);
-
-    let cl = Client::new(url);
-    let mut writer: Vec<u8> = vec![];
-    cl.anonymize_instance(
-        "foo",
-        Some(Anonymization {
-            replace: Some(hashmap! {"Tag1".to_string() => "value1".to_string()}),
-            keep: Some(vec!["Tag2".to_string(), "Tag3".to_string()]),
-            keep_private_tags: None,
-            dicom_version: None,
-            force: None,
-        }),
-        &mut writer,
-    )
-    .unwrap();
+        .return_body(
+            r#"
+                    {
+                        "ID": "foo",
+                        "ParentPatient": "bar",
+                        "ParentSeries": "baz",
+                        "ParentStudy": "qux",
+                        "Path": "/instances/foo",
+                        "Status": "Success"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
 
-    assert_eq!(&writer, &b"foobar");
+    let path = std::env::temp_dir().join("orthanc_test_upload_dicom_file");
+    std::fs::write(&path, "quux").unwrap();
+    let cl = Client::new(url);
+    let resp = cl.upload_file(&path).unwrap();
+
+    assert_eq!(
+        resp,
+        UploadResult {
+            id: Some("foo".into()),
+            status: UploadStatus::Success,
+            path: Some("/instances/foo".to_string()),
+            parent_patient: Some("bar".into()),
+            parent_study: Some("qux".into()),
+            parent_series: Some("baz".into()),
+        }
+    );
     assert_eq!(m.times_called(), 1);
+    std::fs::remove_file(&path).unwrap();
 }
 
 #[test]
-fn test_delete_patient() {
+fn test_upload_dicom_progress() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let m = Mock::new()
-        .expect_method(Method::DELETE)
-        .expect_path("/patients/foo")
+        .expect_method(Method::POST)
+        .expect_path("/instances")
+        .expect_body("quux")
         .return_status(200)
-        .return_body(r#"{"RemainingAncestor": null}"#)
+        .return_body(
+            r#"
+                    {
+                        "ID": "foo",
+                        "ParentPatient": "bar",
+                        "ParentSeries": "baz",
+                        "ParentStudy": "qux",
+                        "Path": "/instances/foo",
+                        "Status": "Success"
+                    }
+                "#,
+        )
         .create_on(&mock_server);
 
-    let cl = Client::new(url);
-    let resp = cl.delete_patient("foo").unwrap();
+    let updates = Arc::new(Mutex::new(vec![]));
+    let u = updates.clone();
+    let cl = Client::new(url)
+        .progress(move |t: &Transfer| u.lock().unwrap().push((t.transferred, t.total)));
+    cl.upload(b"quux").unwrap();
+
+    let updates = updates.lock().unwrap();
+    assert_eq!(updates.first(), Some(&(0, Some(4))));
+    assert_eq!(updates.last(), Some(&(4, Some(4))));
+    assert_eq!(m.times_called(), 1);
+}
+
+#[test]
+fn test_upload_dicom_file_not_found() {
+    let cl = Client::new("http://localhost:8042");
+    let resp = cl.upload_file("/foo/bar/baz.dcm");
 
     assert_eq!(
-        resp,
-        RemainingAncestor {
-            remaining_ancestor: None
+        resp.unwrap_err(),
+        Error {
+            message: "No such file or directory (os error 2)".to_string(),
+            details: None,
+            integrity: None,
         }
     );
-    assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_delete_study() {
+fn test_import_directory() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
+    let dicom = |payload: &str| format!("{}DICM{}", "\0".repeat(128), payload);
 
-    let m = Mock::new()
-        .expect_method(Method::DELETE)
-        .expect_path("/studies/foo")
+    let m1 = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/instances")
+        .expect_body(&dicom("foo"))
         .return_status(200)
         .return_body(
             r#"
                     {
-                        "RemainingAncestor": {
-                            "ID": "bar",
-                            "Path": "/patients/bar",
-                            "Type": "Patient"
-                        }
+                        "ID": "foo",
+                        "ParentPatient": "bar",
+                        "ParentSeries": "baz",
+                        "ParentStudy": "qux",
+                        "Path": "/instances/foo",
+                        "Status": "Success"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+    let m2 = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/instances")
+        .expect_body(&dicom("bar"))
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "ID": "bar",
+                        "ParentPatient": "bar",
+                        "ParentSeries": "baz",
+                        "ParentStudy": "qux",
+                        "Path": "/instances/bar",
+                        "Status": "AlreadyStored"
+                    }
+                "#,
+        )
+        .create_on(&mock_server);
+    let m3 = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/instances")
+        .expect_body(&dicom("baz"))
+        .return_status(400)
+        .return_body(
+            r#"
+                    {
+                        "Details" : "Cannot parse an invalid DICOM file (size: 135 bytes)",
+                        "HttpError" : "Bad Request",
+                        "HttpStatus" : 400,
+                        "Message" : "Bad file format",
+                        "Method" : "POST",
+                        "OrthancError" : "Bad file format",
+                        "OrthancStatus" : 15,
+                        "Uri" : "/instances"
                     }
                 "#,
         )
         .create_on(&mock_server);
 
-    let cl = Client::new(url);
-    let resp = cl.delete_study("foo").unwrap();
+    let dir = std::env::temp_dir().join("orthanc_test_import_directory");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("foo/bar")).unwrap();
+    std::fs::write(dir.join("IM0"), dicom("foo")).unwrap();
+    std::fs::write(dir.join("foo/IM1"), dicom("bar")).unwrap();
+    std::fs::write(dir.join("foo/bar/IM2"), dicom("baz")).unwrap();
+    std::fs::write(dir.join("foo/DICOMDIR.txt"), "quux").unwrap();
+
+    let transfers = Arc::new(Mutex::new(vec![]));
+    let t = transfers.clone();
+    let cl = Client::new(url)
+        .progress(move |transfer: &Transfer| t.lock().unwrap().push(transfer.clone()));
+    let report = cl.import_directory(&dir, 2).unwrap();
 
     assert_eq!(
-        resp,
-        RemainingAncestor {
-            remaining_ancestor: Some(Ancestor {
-                id: "bar".to_string(),
-                path: "/patients/bar".to_string(),
-                entity: EntityKind::Patient,
-            })
+        report,
+        ImportReport {
+            success: vec![dir.join("IM0")],
+            already_stored: vec![dir.join("foo/IM1")],
+            failures: vec![ImportFailure {
+                path: dir.join("foo/bar/IM2"),
+                reason: "API error: 400 Bad Request: Cannot parse an invalid DICOM file (size: 135 bytes)"
+                    .to_string(),
+            }],
+            skipped: vec![dir.join("foo/DICOMDIR.txt")],
+            total_bytes: 270,
         }
     );
-    assert_eq!(m.times_called(), 1);
+    // Updates of concurrent uploads are told apart by the transfer ID
+    let transfers = transfers.lock().unwrap();
+    let mut completed: Vec<_> = transfers
+        .iter()
+        .filter(|t| t.transferred == 135)
+        .map(|t| (t.id, t.path.as_str(), t.file.clone().unwrap()))
+        .collect();
+    completed.sort_by(|a, b| a.2.cmp(&b.2));
+    assert_eq!(
+        completed.iter().map(|t| t.2.clone()).collect::<Vec<_>>(),
+        vec![
+            dir.join("IM0"),
+            dir.join("foo/IM1"),
+            dir.join("foo/bar/IM2")
+        ]
+    );
+    assert!(completed.iter().all(|t| t.1 == "instances"));
+    assert_ne!(completed[0].0, completed[1].0);
+    assert_ne!(completed[1].0, completed[2].0);
+    assert_eq!(m1.times_called(), 1);
+    assert_eq!(m2.times_called(), 1);
+    assert_eq!(m3.times_called(), 1);
+    std::fs::remove_dir_all(&dir).unwrap();
 }
 
 #[test]
-fn test_delete_series() {
+fn test_import_directory_not_found() {
+    let cl = Client::new("http://localhost:8042");
+    let resp = cl.import_directory("/foo/bar/baz", 4);
+
+    assert_eq!(
+        resp.unwrap_err(),
+        Error {
+            message: "No such file or directory (os error 2)".to_string(),
+            details: None,
+            integrity: None,
+        }
+    );
+}
+
+#[test]
+fn test_lookup() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let m = Mock::new()
-        .expect_method(Method::DELETE)
-        .expect_path("/series/foo")
+        .expect_method(Method::POST)
+        .expect_path("/tools/lookup")
+        .expect_body("1.2.3.4.5.6789")
         .return_status(200)
         .return_body(
             r#"
-                    {
-                        "RemainingAncestor": {
-                            "ID": "bar",
-                            "Path": "/studies/bar",
+                    [
+                        {
+                            "ID": "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5",
+                            "Path": "/studies/63bf5d42-b5382159-01971752-e0ceea3d-399bbca5",
                             "Type": "Study"
                         }
-                    }
+                    ]
                 "#,
         )
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    let resp = cl.delete_series("foo").unwrap();
+    let resp = cl.lookup("1.2.3.4.5.6789").unwrap();
 
     assert_eq!(
         resp,
-        RemainingAncestor {
-            remaining_ancestor: Some(Ancestor {
-                id: "bar".to_string(),
-                path: "/studies/bar".to_string(),
-                entity: EntityKind::Study,
-            })
-        }
+        vec![LookupResult {
+            id: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".into(),
+            path: "/studies/63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".to_string(),
+            entity: EntityKind::Study,
+        }]
     );
     assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_delete_instance() {
+fn test_find_study_by_uid() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
-    let m = Mock::new()
-        .expect_method(Method::DELETE)
-        .expect_path("/instances/foo")
+    let m1 = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/tools/lookup")
+        .expect_body("1.2.3.4.5.6789")
         .return_status(200)
         .return_body(
             r#"
-                    {
-                        "RemainingAncestor": {
-                            "ID": "bar",
-                            "Path": "/series/bar",
-                            "Type": "Series"
+                    [
+                        {
+                            "ID": "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5",
+                            "Path": "/studies/63bf5d42-b5382159-01971752-e0ceea3d-399bbca5",
+                            "Type": "Study"
                         }
+                    ]
+                "#,
+        )
+        .create_on(&mock_server);
+    let m2 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/studies/63bf5d42-b5382159-01971752-e0ceea3d-399bbca5")
+        .return_status(200)
+        .return_body(
+            r#"
+                    {
+                        "ID": "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5",
+                        "IsStable": true,
+                        "LastUpdate": "20200830T191109",
+                        "MainDicomTags": {
+                            "StudyInstanceUID": "1.2.3.4.5.6789"
+                        },
+                        "ParentPatient": "7e43f8d3-e50280e6-470079e9-02241af1-d286bdbe",
+                        "PatientMainDicomTags": {
+                            "PatientID": "c137"
+                        },
+                        "Series": [
+                            "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c"
+                        ],
+                        "Type": "Study"
                     }
                 "#,
         )
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    let resp = cl.delete_instance("foo").unwrap();
+    let study = cl.find_study_by_uid("1.2.3.4.5.6789").unwrap().unwrap();
 
-    assert_eq!(
-        resp,
-        RemainingAncestor {
-            remaining_ancestor: Some(Ancestor {
-                id: "bar".to_string(),
-                path: "/series/bar".to_string(),
-                entity: EntityKind::Series,
-            })
-        }
-    );
-    assert_eq!(m.times_called(), 1);
+    assert_eq!(study.id, "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5");
+    assert_eq!(study.main_dicom_tags["StudyInstanceUID"], "1.2.3.4.5.6789");
+    assert_eq!(m1.times_called(), 1);
+    assert_eq!(m2.times_called(), 1);
 }
 
 #[test]
-fn test_modality_echo() {
+fn test_find_series_by_uid_not_found() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
+    // The UID belongs to a study, not a series
     let m = Mock::new()
         .expect_method(Method::POST)
-        .expect_path("/modalities/foo/echo")
+        .expect_path("/tools/lookup")
         .return_status(200)
-        .return_body("{}")
+        .return_body(
+            r#"
+                    [
+                        {
+                            "ID": "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5",
+                            "Path": "/studies/63bf5d42-b5382159-01971752-e0ceea3d-399bbca5",
+                            "Type": "Study"
+                        }
+                    ]
+                "#,
+        )
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    assert_eq!(cl.modality_echo("foo", None).unwrap(), ());
-    assert_eq!(cl.echo("foo", None).unwrap(), ());
-    assert_eq!(m.times_called(), 2);
+    assert_eq!(cl.find_series_by_uid("1.2.3.4.5.6789").unwrap(), None);
+    assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_echo_with_timeout() {
+fn test_find_instance_by_uid_not_found() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
     let m = Mock::new()
         .expect_method(Method::POST)
-        .expect_path("/modalities/foo/echo")
-        .expect_json_body(&hashmap! {"Timeout" => 42})
+        .expect_path("/tools/lookup")
         .return_status(200)
-        .return_body("{}")
+        .return_body("[]")
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    assert_eq!(cl.modality_echo("foo", Some(42)).unwrap(), ());
-    assert_eq!(cl.echo("foo", Some(42)).unwrap(), ());
-    assert_eq!(m.times_called(), 2);
+    assert_eq!(cl.find_instance_by_uid("1.2.3").unwrap(), None);
+    assert_eq!(m.times_called(), 1);
 }
 
 #[test]
-fn test_echo_failed() {
+fn test_reconstruct() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
-    let m = Mock::new()
+    let m1 = Mock::new()
         .expect_method(Method::POST)
-        .expect_path("/modalities/foo/echo")
-        .return_status(500)
+        .expect_path("/series/foo/reconstruct")
+        .return_status(200)
+        .return_body("{}")
+        .create_on(&mock_server);
+    let m2 = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/instances/bar/reconstruct")
+        .return_status(200)
+        .return_body("{}")
         .create_on(&mock_server);
 
     let cl = Client::new(url);
-    assert_eq!(
-        cl.modality_echo("foo", None).unwrap_err(),
-        Error {
-            message: "API error: 500 Internal Server Error".to_string(),
-            details: None
-        }
-    );
-    assert_eq!(
-        cl.echo("foo", None).unwrap_err(),
-        Error {
-            message: "API error: 500 Internal Server Error".to_string(),
-            details: None
-        }
-    );
-    assert_eq!(m.times_called(), 2);
+    cl.reconstruct(EntityKind::Series, "foo").unwrap();
+    cl.reconstruct(EntityKind::Instance, "bar").unwrap();
+
+    assert_eq!(m1.times_called(), 1);
+    assert_eq!(m2.times_called(), 1);
 }
 
 #[test]
-fn test_upload_dicom() {
+fn test_reconstruct_studies() {
     let mock_server = MockServer::start();
     let url = mock_server.url("");
 
-    let m = Mock::new()
+    let m1 = Mock::new()
+        .expect_method(Method::GET)
+        .expect_path("/studies")
+        .return_status(200)
+        .return_body(r#"["foo", "bar", "baz"]"#)
+        .create_on(&mock_server);
+    let m2 = Mock::new()
         .expect_method(Method::POST)
-        .expect_path("/instances")
-        .expect_body("quux")
+        .expect_path("/studies/foo/reconstruct")
         .return_status(200)
+        .return_body("{}")
+        .create_on(&mock_server);
+    let m3 = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/studies/bar/reconstruct")
+        .return_status(404)
         .return_body(
             r#"
                     {
-                        "ID": "foo",
-                        "ParentPatient": "bar",
-                        "ParentSeries": "baz",
-                        "ParentStudy": "qux",
-                        "Path": "/instances/foo",
-                        "Status": "Success"
+                        "HttpError" : "Not Found",
+                        "HttpStatus" : 404,
+                        "Message" : "Unknown resource",
+                        "Method" : "POST",
+                        "OrthancError" : "Unknown resource",
+                        "OrthancStatus" : 17,
+                        "Uri" : "/studies/bar/reconstruct"
                     }
                 "#,
         )
         .create_on(&mock_server);
+    let m4 = Mock::new()
+        .expect_method(Method::POST)
+        .expect_path("/studies/baz/reconstruct")
+        .return_status(200)
+        .return_body("{}")
+        .create_on(&mock_server);
 
+    let updates = Arc::new(Mutex::new(vec![]));
+    let u = updates.clone();
     let cl = Client::new(url);
-    let resp = cl.upload("quux".as_bytes()).unwrap();
+    let report = cl
+        .reconstruct_studies(2, move |done, total| u.lock().unwrap().push((done, total)))
+        .unwrap();
 
     assert_eq!(
-        resp,
-        UploadResult {
-            id: "foo".to_string(),
-            status: "Success".to_string(),
-            path: "/instances/foo".to_string(),
-            parent_patient: "bar".to_string(),
-            parent_study: "qux".to_string(),
-            parent_series: "baz".to_string(),
+        report,
+        ReconstructReport {
+            reconstructed: vec!["foo".into(), "baz".into()],
+            failures: vec![ReconstructFailure {
+                id: "bar".into(),
+                reason: "API error: 404 Not Found: Unknown resource".to_string(),
+            }],
         }
     );
-    assert_eq!(m.times_called(), 1);
+    let mut updates = updates.lock().unwrap().clone();
+    updates.sort();
+    assert_eq!(updates, vec![(1, 3), (2, 3), (3, 3)]);
+    assert_eq!(m1.times_called(), 1);
+    assert_eq!(m2.times_called(), 1);
+    assert_eq!(m3.times_called(), 1);
+    assert_eq!(m4.times_called(), 1);
 }
 
 // The following 2 tests are exactly the same except one calls `create_modality`,
@@ -2210,7 +5187,7 @@ fn test_delete_peer() {
 #[test]
 fn test_get_dicom_tag_value_patient() {
     let patient = Patient {
-        id: "f88cbd3f-a00dfc59-9ca1ac2d-7ce9851a-40e5b493".to_string(),
+        id: "f88cbd3f-a00dfc59-9ca1ac2d-7ce9851a-40e5b493".into(),
         is_stable: true,
         last_update: NaiveDate::from_ymd(2020, 1, 1).and_hms(15, 46, 17),
         main_dicom_tags: hashmap! {
@@ -2220,7 +5197,7 @@ fn test_get_dicom_tag_value_patient() {
             "PatientName".to_string() => "Rick Sanchez".to_string(),
             "PatientSex".to_string() => "M".to_string()
         },
-        studies: ["e8cafcbe-caf08c39-6e205f15-18554bb8-b3f9ef04".to_string()].to_vec(),
+        studies: ["e8cafcbe-caf08c39-6e205f15-18554bb8-b3f9ef04".into()].to_vec(),
         entity: EntityKind::Patient,
         anonymized_from: None,
     };
@@ -2231,7 +5208,7 @@ fn test_get_dicom_tag_value_patient() {
 #[test]
 fn test_get_dicom_tag_value_study() {
     let study = Study {
-        id: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".to_string(),
+        id: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".into(),
         is_stable: true,
         last_update: NaiveDate::from_ymd(2020, 8, 30).and_hms(19, 11, 09),
         main_dicom_tags: hashmap! {
@@ -2242,7 +5219,7 @@ fn test_get_dicom_tag_value_study() {
             "StudyInstanceUID".to_string() => "1.2.3.4.5.6789".to_string(),
             "StudyTime".to_string() => "084707".to_string()
         },
-        parent_patient: "7e43f8d3-e50280e6-470079e9-02241af1-d286bdbe".to_string(),
+        parent_patient: "7e43f8d3-e50280e6-470079e9-02241af1-d286bdbe".into(),
         patient_main_dicom_tags: hashmap! {
             "PatientBirthDate".to_string() => "19440101".to_string(),
             "PatientID".to_string() => "c137".to_string(),
@@ -2250,8 +5227,8 @@ fn test_get_dicom_tag_value_study() {
             "PatientSex".to_string() => "M".to_string(),
         },
         series: [
-            "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".to_string(),
-            "2ab7dbe7-f1a18a78-86145443-18a8ff93-0b65f2b2".to_string(),
+            "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".into(),
+            "2ab7dbe7-f1a18a78-86145443-18a8ff93-0b65f2b2".into(),
         ]
         .to_vec(),
         entity: EntityKind::Study,
@@ -2265,7 +5242,7 @@ fn test_get_dicom_tag_value_study() {
 #[test]
 fn test_get_dicom_tag_value_series() {
     let series = Series {
-        id: "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".to_string(),
+        id: "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".into(),
         status: "Unknown".to_string(),
         is_stable: true,
         last_update: NaiveDate::from_ymd(2020, 8, 30).and_hms(19, 11, 09),
@@ -2279,12 +5256,12 @@ fn test_get_dicom_tag_value_series() {
             "SeriesTime".to_string() => "091313.93".to_string(),
 
         },
-        parent_study: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".to_string(),
+        parent_study: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".into(),
         expected_number_of_instances: Some(17),
         instances: [
-            "556530b5-de7c487b-110b9d0e-12cfdbb9-f06b546e".to_string(),
-            "c46605db-836489fa-cb55fbbc-13c8a913-b0bad6ac".to_string(),
-            "9b63498d-cae4f25e-f52206b2-cbb4dc0e-dc55c788".to_string(),
+            "556530b5-de7c487b-110b9d0e-12cfdbb9-f06b546e".into(),
+            "c46605db-836489fa-cb55fbbc-13c8a913-b0bad6ac".into(),
+            "9b63498d-cae4f25e-f52206b2-cbb4dc0e-dc55c788".into(),
         ]
         .to_vec(),
         entity: EntityKind::Series,
@@ -2297,7 +5274,7 @@ fn test_get_dicom_tag_value_series() {
 #[test]
 fn test_get_dicom_tag_value_instance() {
     let instance = Instance {
-        id: "29fa4d9d-51a69d1d-70e2b29a-fd824316-50850d0c".to_string(),
+        id: "29fa4d9d-51a69d1d-70e2b29a-fd824316-50850d0c".into(),
         main_dicom_tags: hashmap! {
             "ImageOrientationPatient".to_string() => "1\\0\\0\\0\\1\\0".to_string(),
             "ImagePositionPatient".to_string() => "-17\\42\\13".to_string(),
@@ -2306,11 +5283,11 @@ fn test_get_dicom_tag_value_instance() {
             "InstanceNumber".to_string() => "13".to_string(),
             "SOPInstanceUID".to_string() => "1.2.3.4.5.6789".to_string(),
         },
-        parent_series: "82081568-b6f8f4e6-ced76876-6504da25-ed0dfe03".to_string(),
+        parent_series: "82081568-b6f8f4e6-ced76876-6504da25-ed0dfe03".into(),
         index_in_series: Some(13),
         file_uuid: "d8c5eff3-986c-4fe4-b06e-7e52b2a4238e".to_string(),
         file_size: 139402,
-        modified_from: Some("22c54cb6-28302a69-3ff454a3-676b98f4-b84cd80a".to_string()),
+        modified_from: Some("22c54cb6-28302a69-3ff454a3-676b98f4-b84cd80a".into()),
         entity: EntityKind::Instance,
         anonymized_from: None,
     };
@@ -2381,7 +5358,7 @@ fn test_search_patient_level() {
         patients,
         [
             Patient {
-                id: "f88cbd3f-a00dfc59-9ca1ac2d-7ce9851a-40e5b493".to_string(),
+                id: "f88cbd3f-a00dfc59-9ca1ac2d-7ce9851a-40e5b493".into(),
                 is_stable: true,
                 last_update: NaiveDate::from_ymd(2020, 1, 1).and_hms(15, 46, 17),
                 main_dicom_tags: hashmap! {
@@ -2391,13 +5368,12 @@ fn test_search_patient_level() {
                     "PatientName".to_string() => "Rick Sanchez".to_string(),
                     "PatientSex".to_string() => "M".to_string()
                 },
-                studies: ["e8cafcbe-caf08c39-6e205f15-18554bb8-b3f9ef04".to_string()]
-                    .to_vec(),
+                studies: ["e8cafcbe-caf08c39-6e205f15-18554bb8-b3f9ef04".into()].to_vec(),
                 entity: EntityKind::Patient,
                 anonymized_from: None
             },
             Patient {
-                id: "7e43f8d3-e50280e6-470079e9-02241af1-d286bdbe".to_string(),
+                id: "7e43f8d3-e50280e6-470079e9-02241af1-d286bdbe".into(),
                 is_stable: true,
                 last_update: NaiveDate::from_ymd(2020, 8, 26).and_hms(17, 45, 31),
                 main_dicom_tags: hashmap! {
@@ -2406,8 +5382,7 @@ fn test_search_patient_level() {
                     "PatientID".to_string() => "987654321".to_string(),
                     "PatientName".to_string() => "Morty Smith".to_string(),
                 },
-                studies: ["63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".to_string()]
-                    .to_vec(),
+                studies: ["63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".into()].to_vec(),
                 entity: EntityKind::Patient,
                 anonymized_from: None
             },
@@ -2498,7 +5473,7 @@ fn test_search_study_level() {
         studies,
         [
             Study {
-                id: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".to_string(),
+                id: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".into(),
                 is_stable: true,
                 last_update: NaiveDate::from_ymd(2020, 8, 30).and_hms(19, 11, 09),
                 main_dicom_tags: hashmap! {
@@ -2509,7 +5484,7 @@ fn test_search_study_level() {
                     "StudyInstanceUID".to_string() => "1.2.3.4.5.6789".to_string(),
                     "StudyTime".to_string() => "084707".to_string()
                 },
-                parent_patient: "7e43f8d3-e50280e6-470079e9-02241af1-d286bdbe".to_string(),
+                parent_patient: "7e43f8d3-e50280e6-470079e9-02241af1-d286bdbe".into(),
                 patient_main_dicom_tags: hashmap! {
                     "PatientBirthDate".to_string() => "19440101".to_string(),
                     "PatientID".to_string() => "c137".to_string(),
@@ -2517,15 +5492,15 @@ fn test_search_study_level() {
                     "PatientSex".to_string() => "M".to_string(),
                 },
                 series: [
-                    "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".to_string(),
-                    "2ab7dbe7-f1a18a78-86145443-18a8ff93-0b65f2b2".to_string()
+                    "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".into(),
+                    "2ab7dbe7-f1a18a78-86145443-18a8ff93-0b65f2b2".into()
                 ]
                 .to_vec(),
                 entity: EntityKind::Study,
                 anonymized_from: None
             },
             Study {
-                id: "e8cafcbe-caf08c39-6e205f15-18554bb8-b3f9ef04".to_string(),
+                id: "e8cafcbe-caf08c39-6e205f15-18554bb8-b3f9ef04".into(),
                 is_stable: true,
                 last_update: NaiveDate::from_ymd(2020, 9, 1).and_hms(18, 52, 11),
                 main_dicom_tags: hashmap! {
@@ -2536,7 +5511,7 @@ fn test_search_study_level() {
                     "StudyInstanceUID".to_string() => "1.2.3.4.5.67810".to_string(),
                     "StudyTime".to_string() => "130431".to_string()
                 },
-                parent_patient: "f88cbd3f-a00dfc59-9ca1ac2d-7ce9851a-40e5b493".to_string(),
+                parent_patient: "f88cbd3f-a00dfc59-9ca1ac2d-7ce9851a-40e5b493".into(),
                 patient_main_dicom_tags: hashmap! {
                     "PatientBirthDate".to_string() => "19670101".to_string(),
                     "PatientID".to_string() => "4217".to_string(),
@@ -2544,8 +5519,8 @@ fn test_search_study_level() {
                     "PatientSex".to_string() => "F".to_string(),
                 },
                 series: [
-                    "222bbd7e-4dfbc5a8-ea58f933-f1747134-0810c7c8".to_string(),
-                    "54f8778a-75ba559c-db7c7c1a-c1056140-ef74d487".to_string()
+                    "222bbd7e-4dfbc5a8-ea58f933-f1747134-0810c7c8".into(),
+                    "54f8778a-75ba559c-db7c7c1a-c1056140-ef74d487".into()
                 ]
                 .to_vec(),
                 entity: EntityKind::Study,
@@ -2633,7 +5608,7 @@ fn test_search_series_level() {
         series,
         [
             Series {
-                id: "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".to_string(),
+                id: "cd00fffc-db25be29-0c6da430-c56796a5-ba06933c".into(),
                 status: "Unknown".to_string(),
                 is_stable: true,
                 last_update: NaiveDate::from_ymd(2020, 8, 30).and_hms(19, 11, 09),
@@ -2647,19 +5622,19 @@ fn test_search_series_level() {
                     "SeriesTime".to_string() => "091313.93".to_string(),
 
                 },
-                parent_study: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".to_string(),
+                parent_study: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".into(),
                 expected_number_of_instances: Some(17),
                 instances: [
-                    "556530b5-de7c487b-110b9d0e-12cfdbb9-f06b546e".to_string(),
-                    "c46605db-836489fa-cb55fbbc-13c8a913-b0bad6ac".to_string(),
-                    "9b63498d-cae4f25e-f52206b2-cbb4dc0e-dc55c788".to_string(),
+                    "556530b5-de7c487b-110b9d0e-12cfdbb9-f06b546e".into(),
+                    "c46605db-836489fa-cb55fbbc-13c8a913-b0bad6ac".into(),
+                    "9b63498d-cae4f25e-f52206b2-cbb4dc0e-dc55c788".into(),
                 ]
                 .to_vec(),
                 entity: EntityKind::Series,
                 anonymized_from: None
             },
             Series {
-                id: "2ab7dbe7-f1a18a78-86145443-18a8ff93-0b65f2b2".to_string(),
+                id: "2ab7dbe7-f1a18a78-86145443-18a8ff93-0b65f2b2".into(),
                 status: "Unknown".to_string(),
                 is_stable: true,
                 last_update: NaiveDate::from_ymd(2020, 8, 30).and_hms(19, 11, 09),
@@ -2673,11 +5648,11 @@ fn test_search_series_level() {
                     "SeriesTime".to_string() => "091313.93".to_string(),
 
                 },
-                parent_study: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".to_string(),
+                parent_study: "63bf5d42-b5382159-01971752-e0ceea3d-399bbca5".into(),
                 expected_number_of_instances: None,
                 instances: [
-                    "a17e85ca-380bb2dc-d29ea4b7-6e73c10a-ca6ba458".to_string(),
-                    "1c81e7e8-30642777-ffc2ca41-c7536670-7ad68124".to_string(),
+                    "a17e85ca-380bb2dc-d29ea4b7-6e73c10a-ca6ba458".into(),
+                    "1c81e7e8-30642777-ffc2ca41-c7536670-7ad68124".into(),
                 ]
                 .to_vec(),
                 entity: EntityKind::Series,
@@ -2752,7 +5727,7 @@ fn test_search_instance_level() {
         instances,
         [
             Instance {
-                id: "29fa4d9d-51a69d1d-70e2b29a-fd824316-50850d0c".to_string(),
+                id: "29fa4d9d-51a69d1d-70e2b29a-fd824316-50850d0c".into(),
                 main_dicom_tags: hashmap! {
                     "ImageOrientationPatient".to_string() => "1\\0\\0\\0\\1\\0".to_string(),
                     "ImagePositionPatient".to_string() => "-17\\42\\13".to_string(),
@@ -2761,18 +5736,16 @@ fn test_search_instance_level() {
                     "InstanceNumber".to_string() => "13".to_string(),
                     "SOPInstanceUID".to_string() => "1.2.3.4.5.6789".to_string(),
                 },
-                parent_series: "82081568-b6f8f4e6-ced76876-6504da25-ed0dfe03".to_string(),
+                parent_series: "82081568-b6f8f4e6-ced76876-6504da25-ed0dfe03".into(),
                 index_in_series: Some(13),
                 file_uuid: "d8c5eff3-986c-4fe4-b06e-7e52b2a4238e".to_string(),
                 file_size: 139402,
-                modified_from: Some(
-                    "22c54cb6-28302a69-3ff454a3-676b98f4-b84cd80a".to_string()
-                ),
+                modified_from: Some("22c54cb6-28302a69-3ff454a3-676b98f4-b84cd80a".into()),
                 entity: EntityKind::Instance,
                 anonymized_from: None
             },
             Instance {
-                id: "286a251e-46571bd6-0e14ab9a-1baadddc-d0146ea0".to_string(),
+                id: "286a251e-46571bd6-0e14ab9a-1baadddc-d0146ea0".into(),
                 main_dicom_tags: hashmap! {
                     "ImageOrientationPatient".to_string() => "-1\\0\\0\\0\\1\\0".to_string(),
                     "ImagePositionPatient".to_string() => "-17\\42\\14".to_string(),
@@ -2781,7 +5754,7 @@ fn test_search_instance_level() {
                     "InstanceNumber".to_string() => "75".to_string(),
                     "SOPInstanceUID".to_string() => "1.2.3.4.5.67810".to_string(),
                 },
-                parent_series: "a240e0d7-538699a0-7464bb4b-a906f72a-fa3a32c7".to_string(),
+                parent_series: "a240e0d7-538699a0-7464bb4b-a906f72a-fa3a32c7".into(),
                 index_in_series: None,
                 file_uuid: "86bbad65-2c98-4cb0-bf77-0ef0243410a4".to_string(),
                 file_size: 381642,
@@ -2842,6 +5815,7 @@ fn test_search_error() {
                 orthanc_status: 27,
                 orthanc_error: "Unknown DICOM tag".to_string(),
             },),
+            integrity: None,
         },
     );
     assert_eq!(m.times_called(), 1);
@@ -2945,6 +5919,7 @@ fn test_modality_move_error() {
                 orthanc_status: 27,
                 orthanc_error: "Boom!".to_string(),
             },),
+            integrity: None,
         },
     );
     assert_eq!(m.times_called(), 1);
@@ -3054,7 +6029,8 @@ fn test_modality_find_error() {
                 orthanc_status: 9,
                 orthanc_error: "Error in the network protocol".to_string(),
 
-            })
+            }),
+            integrity: None,
         }
     );
 
@@ -3123,7 +6099,8 @@ fn test_get_query_level_error() {
         cl.query_level("foo").unwrap_err(),
         Error {
             message: "Unknown entity kind: Foobar".to_string(),
-            details: None
+            details: None,
+            integrity: None,
         }
     );
     assert_eq!(m.times_called(), 1);
@@ -1,8 +1,16 @@
 use dicom_object::{open_file, Error as DicomError};
 use maplit::hashmap;
+use orthanc::archive::*;
 use orthanc::entity::*;
 use orthanc::error::ApiError;
+use orthanc::ids;
+use orthanc::ids::{InstanceId, PatientId, SeriesId, StudyId};
 use orthanc::models::*;
+use orthanc::numpy;
+use orthanc::phi::*;
+use orthanc::progress::Transfer;
+use orthanc::pseudonymization::*;
+use orthanc::walker::*;
 use orthanc::Client;
 use orthanc::Error;
 use regex::Regex;
@@ -12,6 +20,9 @@ use serde_json::{from_str, json, Value};
 use std::env;
 use std::fs;
 use std::io::BufReader;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use zip;
 
 const DEFAULT_DINO_HOST: &str = "dino"; // docker-compose
@@ -23,6 +34,8 @@ const SOP_INSTANCE_UID_DELETE: &str = "1.3.46.670589.11.1.5.0.7080.2012100313435
 const SERIES_INSTANCE_UID: &str = "1.3.46.670589.11.1.5.0.3724.2011072815265926000";
 const STUDY_INSTANCE_UID: &str = "1.3.46.670589.11.1.5.0.6560.2011072814060507000";
 const PATIENT_ID: &str = "patient_2";
+/// Transfer syntax of the instance with `SOP_INSTANCE_UID`
+const TRANSFER_SYNTAX: TransferSyntax = TransferSyntax::ExplicitVrLittleEndian;
 
 const UPLOAD_INSTANCE_FILE_PATH: &str = "upload";
 
@@ -67,19 +80,19 @@ fn client_modality_two() -> Client {
     )
 }
 
-fn first_patient() -> String {
+fn first_patient() -> PatientId {
     client_main().patients().unwrap().remove(0)
 }
 
-fn first_study() -> String {
+fn first_study() -> StudyId {
     client_main().studies().unwrap().remove(0)
 }
 
-fn first_series() -> String {
+fn first_series() -> SeriesId {
     client_main().series_list().unwrap().remove(0)
 }
 
-fn first_instance() -> String {
+fn first_instance() -> InstanceId {
     client_main().instances().unwrap().remove(0)
 }
 
@@ -203,7 +216,8 @@ fn test_no_auth() {
         resp.unwrap_err(),
         Error {
             message: "API error: 401 Unauthorized".to_string(),
-            details: None
+            details: None,
+            integrity: None,
         }
     );
 }
@@ -216,7 +230,8 @@ fn test_wrong_auth() {
         resp.unwrap_err(),
         Error {
             message: "API error: 401 Unauthorized".to_string(),
-            details: None
+            details: None,
+            integrity: None,
         }
     );
 }
@@ -425,6 +440,100 @@ fn test_get_series_dicom() {
     );
 }
 
+#[test]
+fn test_extract_study() {
+    let study = find_study_by_study_instance_uid(STUDY_INSTANCE_UID).unwrap();
+    let dir = "/tmp/extracted_study";
+    let _ = fs::remove_dir_all(dir);
+    let mut paths = client_main().extract_study(&study.id, dir, None).unwrap();
+    paths.sort();
+
+    assert_eq!(
+        paths,
+        vec![
+            Path::new(dir)
+                .join("patient_2 Patient 2/REMOVED Study 1/MR Series 1/MR000000.dcm"),
+            Path::new(dir).join("patient_2 Patient 2/REMOVED Study 1/PR/PR000000.dcm"),
+        ]
+    );
+    assert_tag_value_contains(
+        paths[0].to_str().unwrap(),
+        "SOPInstanceUID",
+        SOP_INSTANCE_UID,
+    );
+}
+
+#[test]
+fn test_extract_study_with_template() {
+    let study = find_study_by_study_instance_uid(STUDY_INSTANCE_UID).unwrap();
+    let dir = "/tmp/extracted_study_with_template";
+    let _ = fs::remove_dir_all(dir);
+    let template =
+        PathTemplate::new("{PatientID}/{StudyDescription}/{Modality}/{SOPInstanceUID}.dcm");
+    let paths = client_main()
+        .extract_study(&study.id, dir, Some(&template))
+        .unwrap();
+
+    assert_eq!(paths.len(), 2);
+    let path =
+        Path::new(dir).join(format!("patient_2/Study 1/MR/{}.dcm", SOP_INSTANCE_UID));
+    assert!(paths.contains(&path));
+    assert_tag_value_contains(path.to_str().unwrap(), "SOPInstanceUID", SOP_INSTANCE_UID);
+}
+
+#[test]
+fn test_get_study_media() {
+    let study = find_study_by_study_instance_uid(STUDY_INSTANCE_UID).unwrap();
+    let mut file = fs::File::create("/tmp/study_media.zip").unwrap();
+    client_main()
+        .study_media(&study.id, None, &mut file)
+        .unwrap();
+
+    let file = fs::File::open("/tmp/study_media.zip").unwrap();
+    let reader = BufReader::new(file);
+    let zip = zip::ZipArchive::new(reader).unwrap();
+    let files: Vec<&str> = zip.file_names().collect();
+
+    assert_eq!(files.len(), 3);
+    assert!(files.contains(&"DICOMDIR"));
+}
+
+#[test]
+fn test_get_study_dicom_progress() {
+    let study = find_study_by_study_instance_uid(STUDY_INSTANCE_UID).unwrap();
+    let transferred = Arc::new(AtomicU64::new(0));
+    let t = transferred.clone();
+    let client = client_main()
+        .progress(move |p: &Transfer| t.store(p.transferred, Ordering::SeqCst));
+    let mut data: Vec<u8> = vec![];
+    client.study_dicom(&study.id, &mut data).unwrap();
+
+    assert!(!data.is_empty());
+    assert_eq!(transferred.load(Ordering::SeqCst), data.len() as u64);
+}
+
+#[test]
+fn test_create_archive() {
+    let series = find_series_by_series_instance_uid(SERIES_INSTANCE_UID).unwrap();
+    let instance = find_instance_by_sop_instance_uid(SOP_INSTANCE_UID_DELETE).unwrap();
+    let mut file = fs::File::create("/tmp/archive.zip").unwrap();
+    client_main()
+        .create_archive(
+            Archive {
+                resources: vec![series.id.into(), instance.id.into()],
+                ..Default::default()
+            },
+            &mut file,
+        )
+        .unwrap();
+
+    let file = fs::File::open("/tmp/archive.zip").unwrap();
+    let reader = BufReader::new(file);
+    let zip = zip::ZipArchive::new(reader).unwrap();
+
+    assert_eq!(zip.len(), 2);
+}
+
 #[test]
 fn test_get_intance_dicom() {
     let instance = find_instance_by_sop_instance_uid(SOP_INSTANCE_UID).unwrap();
@@ -436,6 +545,94 @@ fn test_get_intance_dicom() {
     assert_tag_value_contains("/tmp/instance_dicom", "SOPInstanceUID", SOP_INSTANCE_UID);
 }
 
+#[test]
+fn test_get_instance_dicom_verified() {
+    let instance = find_instance_by_sop_instance_uid(SOP_INSTANCE_UID).unwrap();
+    let mut data: Vec<u8> = vec![];
+    client_main()
+        .verify_integrity(true)
+        .instance_dicom(&instance.id, &mut data)
+        .unwrap();
+    assert_eq!(data.len() as u64, instance.file_size);
+}
+
+#[test]
+fn test_get_instance_dicom_transcoded() {
+    let instance = find_instance_by_sop_instance_uid(SOP_INSTANCE_UID).unwrap();
+    let mut file = fs::File::create("/tmp/instance_dicom_transcoded").unwrap();
+    // Differs from `TRANSFER_SYNTAX`, so that the instance is actually transcoded
+    let transfer_syntax = TransferSyntax::ImplicitVrLittleEndian;
+    client_main()
+        .instance_dicom_transcoded(&instance.id, transfer_syntax.clone(), &mut file)
+        .unwrap();
+    assert_tag_value_contains(
+        "/tmp/instance_dicom_transcoded",
+        "SOPInstanceUID",
+        SOP_INSTANCE_UID,
+    );
+    let obj = open_file("/tmp/instance_dicom_transcoded").unwrap();
+    assert_eq!(obj.meta().transfer_syntax(), transfer_syntax.uid());
+}
+
+#[test]
+fn test_get_instance_transfer_syntax() {
+    let instance = find_instance_by_sop_instance_uid(SOP_INSTANCE_UID).unwrap();
+    let transfer_syntax = client_main()
+        .instance_transfer_syntax(&instance.id)
+        .unwrap();
+    assert_eq!(transfer_syntax, TRANSFER_SYNTAX);
+}
+
+#[test]
+fn test_get_instance_dicom_resume() {
+    let instance = find_instance_by_sop_instance_uid(SOP_INSTANCE_UID).unwrap();
+    let mut file = fs::File::create("/tmp/instance_dicom_resume").unwrap();
+    client_main()
+        .instance_dicom_range(&instance.id, 0, Some(1023), &mut file)
+        .unwrap();
+    assert_eq!(
+        fs::metadata("/tmp/instance_dicom_resume").unwrap().len(),
+        1024
+    );
+
+    let size = client_main()
+        .instance_dicom_resume(&instance.id, "/tmp/instance_dicom_resume")
+        .unwrap();
+    assert_eq!(size, instance.file_size);
+    assert_tag_value_contains(
+        "/tmp/instance_dicom_resume",
+        "SOPInstanceUID",
+        SOP_INSTANCE_UID,
+    );
+}
+
+#[test]
+fn test_get_instance_attachments() {
+    let instance = find_instance_by_sop_instance_uid(SOP_INSTANCE_UID).unwrap();
+    let attachments = client_main().instance_attachments(&instance.id).unwrap();
+    assert!(attachments.contains(&"dicom".to_string()));
+
+    let size = client_main()
+        .instance_attachment_size(&instance.id, "dicom")
+        .unwrap();
+    assert_eq!(size, instance.file_size);
+
+    let md5 = client_main()
+        .instance_attachment_md5(&instance.id, "dicom")
+        .unwrap();
+    assert_eq!(md5.len(), 32);
+
+    let _ = fs::remove_file("/tmp/instance_attachment_resume");
+    let size = client_main()
+        .instance_attachment_resume(
+            &instance.id,
+            "dicom",
+            "/tmp/instance_attachment_resume",
+        )
+        .unwrap();
+    assert_eq!(size, instance.file_size);
+}
+
 #[test]
 fn test_delete() {
     let instance = find_instance_by_sop_instance_uid(SOP_INSTANCE_UID_DELETE).unwrap();
@@ -449,7 +646,7 @@ fn test_delete() {
         resp,
         RemainingAncestor {
             remaining_ancestor: Some(Ancestor {
-                id: instance.parent_series,
+                id: instance.parent_series.into(),
                 path: format!("/series/{}", series.id),
                 entity: EntityKind::Series,
             })
@@ -461,6 +658,7 @@ fn test_delete() {
         Error {
             message: "API error: 404 Not Found".to_string(),
             details: None,
+            integrity: None,
         },
     );
 
@@ -470,7 +668,7 @@ fn test_delete() {
         resp,
         RemainingAncestor {
             remaining_ancestor: Some(Ancestor {
-                id: series.parent_study,
+                id: series.parent_study.into(),
                 path: format!("/studies/{}", study.id),
                 entity: EntityKind::Study,
             })
@@ -482,6 +680,7 @@ fn test_delete() {
         Error {
             message: "API error: 404 Not Found".to_string(),
             details: None,
+            integrity: None,
         },
     );
 
@@ -491,7 +690,7 @@ fn test_delete() {
         resp,
         RemainingAncestor {
             remaining_ancestor: Some(Ancestor {
-                id: study.parent_patient,
+                id: study.parent_patient.into(),
                 path: format!("/patients/{}", patient.id),
                 entity: EntityKind::Patient,
             })
@@ -503,6 +702,7 @@ fn test_delete() {
         Error {
             message: "API error: 404 Not Found".to_string(),
             details: None,
+            integrity: None,
         },
     );
 
@@ -520,6 +720,7 @@ fn test_delete() {
         Error {
             message: "API error: 404 Not Found".to_string(),
             details: None,
+            integrity: None,
         },
     );
 }
@@ -529,14 +730,15 @@ fn test_modify_instance() {
     let instance = find_instance_by_sop_instance_uid(SOP_INSTANCE_UID).unwrap();
 
     let replace = hashmap! {
-        "SpecificCharacterSet".to_string() => "ISO_IR 13".to_string(),
-        "OperatorsName".to_string() => "Summer Smith".to_string()
+        "SpecificCharacterSet".to_string() => "ISO_IR 13".into(),
+        "OperatorsName".to_string() => "Summer Smith".into()
     };
     let remove = vec!["SeriesTime".to_string(), "AcquisitionTime".to_string()];
     let modification = Modification {
         replace: Some(replace),
         remove: Some(remove),
         force: None,
+        ..Default::default()
     };
     let path = "/tmp/modified_instance";
     let mut file = fs::File::create(path).unwrap();
@@ -560,14 +762,15 @@ fn test_modify_series() {
     assert!(tags.contains_key("SeriesDate"));
 
     let replace = hashmap! {
-        "BodyPartExamined".to_string() => "PINKY".to_string(),
-        "OperatorsName".to_string() => "Summer Smith".to_string()
+        "BodyPartExamined".to_string() => "PINKY".into(),
+        "OperatorsName".to_string() => "Summer Smith".into()
     };
     let remove = vec!["StationName".to_string(), "SeriesDate".to_string()];
     let modification = Modification {
         replace: Some(replace),
         remove: Some(remove),
         force: None,
+        ..Default::default()
     };
     let resp = client_main()
         .modify_series(&series.id, modification)
@@ -591,14 +794,15 @@ fn test_modify_study() {
     assert!(tags.contains_key("StudyTime"));
 
     let replace = hashmap! {
-        "StudyID".to_string() => "foobar".to_string(),
-        "ReferringPhysicianName".to_string() => "Summer Smith".to_string()
+        "StudyID".to_string() => "foobar".into(),
+        "ReferringPhysicianName".to_string() => "Summer Smith".into()
     };
     let remove = vec!["InstitutionName".to_string(), "StudyTime".to_string()];
     let modification = Modification {
         replace: Some(replace),
         remove: Some(remove),
         force: None,
+        ..Default::default()
     };
     let resp = client_main().modify_study(&study.id, modification).unwrap();
     let modified_study = client_main().study(&resp.id).unwrap();
@@ -610,6 +814,57 @@ fn test_modify_study() {
     assert!(!modified_tags.contains_key("StudyTime"));
 }
 
+#[test]
+fn test_modify_study_async() {
+    let study = find_study_by_study_instance_uid(STUDY_INSTANCE_UID).unwrap();
+    let modification = ModificationBuilder::new()
+        .replace("StudyDescription", "Modified asynchronously")
+        .keep_source(true)
+        .build()
+        .unwrap();
+    let job = client_main()
+        .modify_study_async(&study.id, modification)
+        .unwrap();
+
+    let mut info = client_main().job(&job.id).unwrap();
+    for _ in 0..60 {
+        if info.state == "Success" {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(500));
+        info = client_main().job(&job.id).unwrap();
+    }
+    assert_eq!(info.state, "Success");
+    assert_eq!(info.job_type, "ResourceModification");
+}
+
+#[test]
+fn test_split_and_merge_study() {
+    let study = find_study_by_study_instance_uid(STUDY_INSTANCE_UID).unwrap();
+    let split = StudySplit {
+        series: Some(vec![study.series[0].clone()]),
+        replace: Some(hashmap! {"StudyDescription".to_string() => "Split".to_string()}),
+        keep_source: Some(true),
+        ..Default::default()
+    };
+    let split_result = client_main().split_study(&study.id, split).unwrap();
+    let new_study = client_main().study(&split_result.target_study).unwrap();
+    assert_eq!(new_study.main_dicom_tags["StudyDescription"], "Split");
+    assert_eq!(new_study.series.len(), 1);
+    assert_ne!(split_result.target_study_uid, STUDY_INSTANCE_UID);
+
+    let merge = StudyMerge {
+        resources: vec![study.series[0].to_string()],
+        keep_source: Some(true),
+        ..Default::default()
+    };
+    let merge_result = client_main().merge_study(&new_study.id, merge).unwrap();
+    assert_eq!(merge_result.target_study, new_study.id);
+    assert_eq!(client_main().study(&new_study.id).unwrap().series.len(), 2);
+
+    client_main().delete_study(&new_study.id).unwrap();
+}
+
 #[test]
 fn test_modify_patient() {
     let patient = find_patient_by_patient_id(PATIENT_ID).unwrap();
@@ -620,15 +875,16 @@ fn test_modify_patient() {
     assert!(tags.contains_key("PatientSex"));
 
     let replace = hashmap! {
-        "PatientID".to_string() => "gazorpazorp".to_string(),
-        "PatientName".to_string() => "Summer Smith".to_string(),
-        "PatientBirthDate".to_string() => "20330303".to_string(),
+        "PatientID".to_string() => "gazorpazorp".into(),
+        "PatientName".to_string() => "Summer Smith".into(),
+        "PatientBirthDate".to_string() => "20330303".into(),
     };
     let remove = vec!["PatientSex".to_string()];
     let modification = Modification {
         replace: Some(replace),
         remove: Some(remove),
         force: Some(true),
+        ..Default::default()
     };
     let resp = client_main()
         .modify_patient(&patient.id, modification)
@@ -655,15 +911,16 @@ fn test_modify_patient_keep_patient_id() {
     assert!(tags.contains_key("PatientSex"));
 
     let replace = hashmap! {
-        "PatientID".to_string() => PATIENT_ID.to_string(),
-        "PatientName".to_string() => "Summer Smith".to_string(),
-        "PatientBirthDate".to_string() => "20330303".to_string(),
+        "PatientID".to_string() => PATIENT_ID.into(),
+        "PatientName".to_string() => "Summer Smith".into(),
+        "PatientBirthDate".to_string() => "20330303".into(),
     };
     let remove = vec!["PatientSex".to_string()];
     let modification = Modification {
         replace: Some(replace),
         remove: Some(remove),
         force: Some(true),
+        ..Default::default()
     };
     let resp = client_main()
         .modify_patient(&patient.id, modification)
@@ -681,12 +938,13 @@ fn test_modify_patient_keep_patient_id() {
 fn test_modify_without_patient_id() {
     let patient = find_patient_by_patient_id(PATIENT_ID).unwrap();
     let replace = hashmap! {
-        "PatientSex".to_string() => "F".to_string(),
+        "PatientSex".to_string() => "F".into(),
     };
     let modification = Modification {
         replace: Some(replace),
         remove: None,
         force: None,
+        ..Default::default()
     };
     let resp = client_main().modify_patient(&patient.id, modification);
 
@@ -707,6 +965,7 @@ fn test_modify_without_patient_id() {
                 orthanc_status: 8,
                 orthanc_error: "Bad request".to_string(),
             },),
+            integrity: None,
         },
     );
 }
@@ -715,12 +974,13 @@ fn test_modify_without_patient_id() {
 fn test_modify_patient_id_without_force() {
     let patient = find_patient_by_patient_id(PATIENT_ID).unwrap();
     let replace = hashmap! {
-        "PatientID".to_string() => "C137".to_string(),
+        "PatientID".to_string() => "C137".into(),
     };
     let modification = Modification {
         replace: Some(replace),
         remove: None,
         force: None,
+        ..Default::default()
     };
     let resp = client_main().modify_patient(&patient.id, modification);
 
@@ -740,6 +1000,7 @@ fn test_modify_patient_id_without_force() {
                 orthanc_status: 8,
                 orthanc_error: "Bad request".to_string(),
             },),
+            integrity: None,
         },
     );
 }
@@ -749,8 +1010,8 @@ fn test_anonymize_instance() {
     let instance = find_instance_by_sop_instance_uid(SOP_INSTANCE_UID).unwrap();
 
     let replace = hashmap! {
-        "SpecificCharacterSet".to_string() => "ISO_IR 13".to_string(),
-        "OperatorsName".to_string() => "Summer Smith".to_string()
+        "SpecificCharacterSet".to_string() => "ISO_IR 13".into(),
+        "OperatorsName".to_string() => "Summer Smith".into()
     };
     let keep = vec![
         "AccessionNumber".to_string(),
@@ -762,6 +1023,7 @@ fn test_anonymize_instance() {
         keep_private_tags: None,
         dicom_version: None,
         force: None,
+        ..Default::default()
     };
     let path = "/tmp/anonymized_instance";
     let mut file = fs::File::create(path).unwrap();
@@ -803,8 +1065,8 @@ fn test_anonymize_series() {
     assert_ne!(initial_tags["OperatorsName"], "Summer Smith");
 
     let replace = hashmap! {
-        "SpecificCharacterSet".to_string() => "ISO_IR 13".to_string(),
-        "OperatorsName".to_string() => "Summer Smith".to_string()
+        "SpecificCharacterSet".to_string() => "ISO_IR 13".into(),
+        "OperatorsName".to_string() => "Summer Smith".into()
     };
     let keep = vec![
         "AccessionNumber".to_string(),
@@ -816,6 +1078,7 @@ fn test_anonymize_series() {
         keep_private_tags: None,
         dicom_version: None,
         force: None,
+        ..Default::default()
     };
     let resp = client_main()
         .anonymize_series(&series.id, Some(anonymization))
@@ -865,8 +1128,8 @@ fn test_anonymize_study() {
     assert_ne!(initial_tags["OperatorsName"], "Summer Smith");
 
     let replace = hashmap! {
-        "SpecificCharacterSet".to_string() => "ISO_IR 13".to_string(),
-        "OperatorsName".to_string() => "Summer Smith".to_string()
+        "SpecificCharacterSet".to_string() => "ISO_IR 13".into(),
+        "OperatorsName".to_string() => "Summer Smith".into()
     };
     let keep = vec![
         "AccessionNumber".to_string(),
@@ -878,6 +1141,7 @@ fn test_anonymize_study() {
         keep_private_tags: None,
         dicom_version: None,
         force: None,
+        ..Default::default()
     };
     let resp = client_main()
         .anonymize_study(&study.id, Some(anonymization))
@@ -895,6 +1159,83 @@ fn test_anonymize_study() {
     assert_eq!(tags["StudyDescription"], initial_tags["StudyDescription"]);
 }
 
+#[test]
+fn test_pseudonymizer_anonymize_study() {
+    let study = find_study_by_study_instance_uid(STUDY_INSTANCE_UID).unwrap();
+    let mut pseudonymizer = Pseudonymizer::new(MemoryStore::new());
+
+    let first = pseudonymizer
+        .anonymize_study(&client_main(), &study.id, None)
+        .unwrap();
+    let second = pseudonymizer
+        .anonymize_study(&client_main(), &study.id, None)
+        .unwrap();
+    let first = client_main().study(&first.id).unwrap();
+    let second = client_main().study(&second.id).unwrap();
+
+    let pseudonym = pseudonymizer
+        .pseudonym("PatientID", study.main_dicom_tag("PatientID").unwrap())
+        .unwrap();
+    assert_eq!(first.patient_main_dicom_tags["PatientID"], pseudonym);
+    assert_eq!(second.patient_main_dicom_tags["PatientID"], pseudonym);
+    assert_eq!(
+        first.main_dicom_tags["StudyInstanceUID"],
+        second.main_dicom_tags["StudyInstanceUID"]
+    );
+    assert_eq!(first.parent_patient, second.parent_patient);
+    // The series are anonymized with the same pseudonyms, so they end up in the same series
+    assert_eq!(first.id, second.id);
+    assert_eq!(first.series, second.series);
+}
+
+#[test]
+fn test_anonymize_instance_phi() {
+    let instance = find_instance_by_sop_instance_uid(SOP_INSTANCE_UID).unwrap();
+    let checker = PhiChecker::new()
+        .originals_from_tags(&client_main().instance_tags_expanded(&instance.id).unwrap());
+    assert!(!checker
+        .check_instance(&client_main(), &instance.id)
+        .unwrap()
+        .is_clean());
+
+    let mut data: Vec<u8> = vec![];
+    client_main()
+        .anonymize_instance(&instance.id, None, &mut data)
+        .unwrap();
+    let report = checker.check_file(&data);
+    assert!(report.is_clean(), "{:#?}", report.findings);
+}
+
+#[test]
+fn test_anonymize_study_profile() {
+    let study = find_study_by_study_instance_uid(STUDY_INSTANCE_UID).unwrap();
+    let initial_series = client_main().series(&study.series[0]).unwrap();
+    let initial_tags = client_main()
+        .instance_tags(&initial_series.instances[0])
+        .unwrap();
+
+    let anonymization = AnonymizationBuilder::new()
+        .profile(AnonymizationProfile::ResearchPseudonymization)
+        .replace("PatientID", "SUBJ-0042")
+        .replace("PatientName", "SUBJ-0042")
+        .build()
+        .unwrap();
+    let resp = client_main()
+        .anonymize_study(&study.id, Some(anonymization))
+        .unwrap();
+
+    let modified_study = client_main().study(&resp.id).unwrap();
+    let modified_series = client_main().series(&modified_study.series[0]).unwrap();
+    let tags = client_main()
+        .instance_tags(&modified_series.instances[0])
+        .unwrap();
+
+    assert_eq!(tags["PatientID"], "SUBJ-0042");
+    assert_eq!(tags["PatientName"], "SUBJ-0042");
+    assert_eq!(tags["StudyDate"], initial_tags["StudyDate"]);
+    assert_eq!(tags["PatientSex"], initial_tags["PatientSex"]);
+}
+
 #[test]
 fn test_anonymize_study_empty_body() {
     let study = find_study_by_study_instance_uid(STUDY_INSTANCE_UID).unwrap();
@@ -931,8 +1272,8 @@ fn test_anonymize_patient() {
     assert_ne!(initial_tags["OperatorsName"], "Summer Smith");
 
     let replace = hashmap! {
-        "SpecificCharacterSet".to_string() => "ISO_IR 13".to_string(),
-        "OperatorsName".to_string() => "Summer Smith".to_string(),
+        "SpecificCharacterSet".to_string() => "ISO_IR 13".into(),
+        "OperatorsName".to_string() => "Summer Smith".into(),
     };
     let keep = vec![
         "AccessionNumber".to_string(),
@@ -944,6 +1285,7 @@ fn test_anonymize_patient() {
         keep_private_tags: None,
         dicom_version: None,
         force: None,
+        ..Default::default()
     };
     let resp = client_main()
         .anonymize_patient(&patient.id, Some(anonymization))
@@ -991,7 +1333,7 @@ fn test_anonymize_patient_empty_body() {
 fn test_anonymize_without_force() {
     let patient = find_patient_by_patient_id(PATIENT_ID).unwrap();
     let replace = hashmap! {
-        "PatientID".to_string() => "C137".to_string(),
+        "PatientID".to_string() => "C137".into(),
     };
     let anonymization = Anonymization {
         replace: Some(replace),
@@ -999,6 +1341,7 @@ fn test_anonymize_without_force() {
         keep_private_tags: None,
         dicom_version: None,
         force: None,
+        ..Default::default()
     };
     let resp = client_main().anonymize_patient(&patient.id, Some(anonymization));
 
@@ -1018,6 +1361,7 @@ fn test_anonymize_without_force() {
                 orthanc_status: 8,
                 orthanc_error: "Bad request".to_string(),
             },),
+            integrity: None,
         },
     );
 }
@@ -1026,7 +1370,7 @@ fn test_anonymize_without_force() {
 fn test_anonymize_with_force() {
     let patient = find_patient_by_patient_id(PATIENT_ID).unwrap();
     let replace = hashmap! {
-        "PatientID".to_string() => "C137".to_string(),
+        "PatientID".to_string() => "C137".into(),
     };
     let anonymization = Anonymization {
         replace: Some(replace),
@@ -1034,6 +1378,7 @@ fn test_anonymize_with_force() {
         keep_private_tags: None,
         dicom_version: None,
         force: Some(true),
+        ..Default::default()
     };
     let resp = client_main()
         .anonymize_patient(&patient.id, Some(anonymization))
@@ -1059,18 +1404,291 @@ fn test_upload_dicom() {
     .unwrap();
 
     let resp = client_main().upload(&data).unwrap();
-    assert_eq!(resp.status, "Success");
+    assert_eq!(resp.status, UploadStatus::Success);
 
     let resp = client_main().upload(&data).unwrap();
-    assert_eq!(resp.status, "AlreadyStored");
+    assert_eq!(resp.status, UploadStatus::AlreadyStored);
+}
+
+#[test]
+fn test_upload_archive() {
+    let series = find_series_by_series_instance_uid(SERIES_INSTANCE_UID).unwrap();
+    let path = "/tmp/upload_archive.zip";
+    let file = fs::File::create(path).unwrap();
+    client_main().series_dicom(&series.id, file).unwrap();
+
+    let resp = client_main().upload_archive_file(path).unwrap();
+    assert_eq!(resp.len(), series.instances.len());
+    assert!(resp.iter().all(|r| r.status == UploadStatus::AlreadyStored));
+    assert!(resp
+        .iter()
+        .all(|r| r.parent_series.as_ref() == Some(&series.id)));
+}
+
+#[test]
+fn test_upload_dicom_file() {
+    let path = format!(
+        "{}/{}",
+        env::var("ORC_DATAFILES_PATH").unwrap_or("./data/dicom".to_string()),
+        UPLOAD_INSTANCE_FILE_PATH
+    );
+    let resp = client_main().upload_file(&path).unwrap();
+    assert_eq!(resp.status, UploadStatus::AlreadyStored);
+
+    let file = fs::File::open(&path).unwrap();
+    let size = file.metadata().unwrap().len();
+    let resp = client_main().upload_reader(file, size).unwrap();
+    assert_eq!(resp.status, UploadStatus::AlreadyStored);
+}
+
+#[test]
+fn test_import_directory() {
+    let path = format!(
+        "{}/initial",
+        env::var("ORC_DATAFILES_PATH").unwrap_or("./data/dicom".to_string()),
+    );
+    let report = client_main().import_directory(&path, 4).unwrap();
+    assert!(report.success.is_empty());
+    assert!(report.failures.is_empty());
+    assert!(!report.already_stored.is_empty());
+    assert!(report.total_bytes > 0);
 }
 
 // These just test the method access
+#[test]
+fn test_navigation() {
+    let instance = find_instance_by_sop_instance_uid(SOP_INSTANCE_UID).unwrap();
+    let series = find_series_by_series_instance_uid(SERIES_INSTANCE_UID).unwrap();
+    let study = find_study_by_study_instance_uid(STUDY_INSTANCE_UID).unwrap();
+    let patient = find_patient_by_patient_id(PATIENT_ID).unwrap();
+
+    assert_eq!(
+        client_main().instance_series(&instance.id).unwrap().id,
+        series.id
+    );
+    assert_eq!(
+        client_main().instance_study(&instance.id).unwrap().id,
+        study.id
+    );
+    assert_eq!(
+        client_main().instance_patient(&instance.id).unwrap().id,
+        patient.id
+    );
+    assert_eq!(client_main().series_study(&series.id).unwrap().id, study.id);
+    assert_eq!(
+        client_main().series_patient(&series.id).unwrap().id,
+        patient.id
+    );
+    assert_eq!(
+        client_main().study_patient(&study.id).unwrap().id,
+        patient.id
+    );
+    assert!(client_main()
+        .patient_instances(&patient.id)
+        .unwrap()
+        .iter()
+        .any(|i| i.id == instance.id));
+    assert!(client_main()
+        .study_instances(&study.id)
+        .unwrap()
+        .iter()
+        .any(|i| i.id == instance.id));
+    assert_eq!(
+        client_main().patient_series(&patient.id).unwrap().len(),
+        client_main()
+            .patient_studies(&patient.id)
+            .unwrap()
+            .iter()
+            .map(|s| s.series.len())
+            .sum::<usize>()
+    );
+
+    assert_eq!(instance.parent(&client_main()).unwrap().id, series.id);
+    assert_eq!(series.parent(&client_main()).unwrap().id, study.id);
+    assert_eq!(study.parent(&client_main()).unwrap().id, patient.id);
+    assert_eq!(
+        study.child_entities(&client_main()).unwrap().len(),
+        study.series.len()
+    );
+    assert_eq!(
+        patient.child_entities(&client_main()).unwrap().len(),
+        patient.studies.len()
+    );
+}
+
+#[test]
+fn test_series_ordered_slices() {
+    let series = find_series_by_series_instance_uid(SERIES_INSTANCE_UID).unwrap();
+    let slices = client_main().series_ordered_slices(&series.id).unwrap();
+    assert_eq!(slices.dicom.len(), series.instances.len());
+    let mut ids = slices.instance_ids();
+    ids.sort();
+    let mut expected = series.instances.clone();
+    expected.sort();
+    assert_eq!(ids, expected);
+
+    let ordered: Vec<InstanceId> = client_main()
+        .series_ordered_instances(&series.id)
+        .unwrap()
+        .map(|i| i.unwrap().id)
+        .collect();
+    assert_eq!(ordered, slices.instance_ids());
+}
+
+#[test]
+fn test_numpy() {
+    let instance = find_instance_by_sop_instance_uid(SOP_INSTANCE_UID).unwrap();
+    let tags = client_main().instance_tags(&instance.id).unwrap();
+    let rows: usize = tags["Rows"].as_str().unwrap().parse().unwrap();
+    let columns: usize = tags["Columns"].as_str().unwrap().parse().unwrap();
+
+    let mut data: Vec<u8> = vec![];
+    client_main()
+        .instance_numpy(&instance.id, NumpyOptions::default(), &mut data)
+        .unwrap();
+    let array = numpy::read_array(data.as_slice()).unwrap();
+    assert!(array.header.shape.contains(&rows));
+    assert!(array.header.shape.contains(&columns));
+
+    let mut data: Vec<u8> = vec![];
+    client_main()
+        .series_numpy(
+            &instance.parent_series,
+            NumpyOptions {
+                compress: true,
+                rescale: true,
+            },
+            &mut data,
+        )
+        .unwrap();
+    let arrays = numpy::read_npz(&data).unwrap();
+    assert_eq!(arrays.len(), 1);
+    let array = arrays.values().next().unwrap();
+    assert_eq!(array.header.dtype, numpy::Dtype::F32);
+    assert!(array.header.shape.contains(&rows));
+}
+
+#[test]
+fn test_instance_header() {
+    let instance = find_instance_by_sop_instance_uid(SOP_INSTANCE_UID).unwrap();
+    let header = client_main().instance_header(&instance.id).unwrap();
+    assert_eq!(
+        header.media_storage_sop_instance_uid.as_deref(),
+        Some(SOP_INSTANCE_UID)
+    );
+    assert_eq!(
+        header.transfer_syntax_uid,
+        client_main()
+            .instance_transfer_syntax(&instance.id)
+            .unwrap()
+    );
+
+    let header = client_main()
+        .instance_header_expanded(&instance.id)
+        .unwrap();
+    assert_eq!(
+        header.value("0002,0003"),
+        Some(&Value::from(SOP_INSTANCE_UID))
+    );
+}
+
+#[test]
+fn test_shared_and_module_tags() {
+    let series = find_series_by_series_instance_uid(SERIES_INSTANCE_UID).unwrap();
+    let study = find_study_by_study_instance_uid(STUDY_INSTANCE_UID).unwrap();
+
+    let shared = client_main()
+        .series_shared_tags(&series.id, TagsFormat::Simplified)
+        .unwrap();
+    assert_eq!(
+        shared.value("SeriesInstanceUID"),
+        Some(&Value::from(SERIES_INSTANCE_UID))
+    );
+    let shared = client_main()
+        .study_shared_tags(&study.id, TagsFormat::Full)
+        .unwrap();
+    assert_eq!(
+        shared.value("0020,000d"),
+        Some(&Value::from(STUDY_INSTANCE_UID))
+    );
+
+    let module = client_main()
+        .study_patient_module(&study.id, TagsFormat::Short)
+        .unwrap();
+    assert_eq!(module.value("0010,0020"), Some(&Value::from(PATIENT_ID)));
+    let module = client_main()
+        .series_module(&series.id, TagsFormat::Simplified)
+        .unwrap();
+    assert_eq!(
+        module.value("SeriesInstanceUID"),
+        Some(&Value::from(SERIES_INSTANCE_UID))
+    );
+
+    let tags = client_main()
+        .series_instances_tags(&series.id, TagsFormat::Simplified)
+        .unwrap();
+    assert_eq!(tags.len(), series.instances.len());
+    for id in &series.instances {
+        assert_eq!(
+            tags[id].value("SeriesInstanceUID"),
+            Some(&Value::from(SERIES_INSTANCE_UID))
+        );
+    }
+}
+
+#[test]
+fn test_walker() {
+    let client = client_main();
+    let nodes: Vec<Node> = Walker::new(&client)
+        .prefetch(4)
+        .map(|n| n.unwrap())
+        .collect();
+    let count = |kind: EntityKind| nodes.iter().filter(|n| n.kind() == kind).count();
+    assert_eq!(count(EntityKind::Patient), client.patients().unwrap().len());
+    assert_eq!(count(EntityKind::Study), client.studies().unwrap().len());
+    assert_eq!(
+        count(EntityKind::Series),
+        client.series_list().unwrap().len()
+    );
+    assert_eq!(
+        count(EntityKind::Instance),
+        client.instances().unwrap().len()
+    );
+
+    let study = find_study_by_study_instance_uid(STUDY_INSTANCE_UID).unwrap();
+    let nodes: Vec<Node> = Walker::from_entity(&client, EntityKind::Study, &study.id)
+        .max_level(EntityKind::Series)
+        .map(|n| n.unwrap())
+        .collect();
+    assert_eq!(nodes[0].id(), study.id.as_str());
+    assert_eq!(nodes.len(), 1 + study.series.len());
+}
+
+#[test]
+fn test_generic_entity_methods() {
+    assert_eq!(
+        client_main().list::<Study>().unwrap(),
+        client_main().studies().unwrap()
+    );
+    assert_eq!(
+        client_main().list_expanded::<Series>().unwrap().len(),
+        client_main().series_list().unwrap().len()
+    );
+    let study = client_main().get::<Study>(first_study()).unwrap();
+    assert_eq!(study.id, first_study());
+
+    let mut data: Vec<u8> = vec![];
+    client_main()
+        .download::<Instance>(first_instance(), &mut data)
+        .unwrap();
+    assert_eq!(&data[128..132], b"DICM");
+}
+
 #[test]
 fn test_get_dicom_tag_value_patient() {
     assert_eq!(
         client_main()
-            .patient(&first_patient())
+            .patient(first_patient())
             .unwrap()
             .main_dicom_tag("FooBar"),
         None
@@ -1081,7 +1699,7 @@ fn test_get_dicom_tag_value_patient() {
 fn test_get_dicom_tag_value_study() {
     assert_eq!(
         client_main()
-            .study(&first_study())
+            .study(first_study())
             .unwrap()
             .main_dicom_tag("FooBar"),
         None
@@ -1092,7 +1710,7 @@ fn test_get_dicom_tag_value_study() {
 fn test_get_dicom_tag_value_series() {
     assert_eq!(
         client_main()
-            .series(&first_series())
+            .series(first_series())
             .unwrap()
             .main_dicom_tag("FooBar"),
         None
@@ -1103,7 +1721,7 @@ fn test_get_dicom_tag_value_series() {
 fn test_get_dicom_tag_value_instance() {
     assert_eq!(
         client_main()
-            .instance(&first_instance())
+            .instance(first_instance())
             .unwrap()
             .main_dicom_tag("FooBar"),
         None
@@ -1461,7 +2079,7 @@ fn test_modality_store() {
             description: "REST API".to_string(),
             local_aet: "ORTHANC".to_string(),
             remote_aet: "DINO".to_string(),
-            parent_resources: vec!(first_study()),
+            parent_resources: vec!(first_study().into()),
             instances_count: 2,
             failed_instances_count: 0,
         }
@@ -1472,7 +2090,7 @@ fn test_modality_store() {
             description: "REST API".to_string(),
             local_aet: "ORTHANC".to_string(),
             remote_aet: "DINO".to_string(),
-            parent_resources: vec!(first_study()),
+            parent_resources: vec!(first_study().into()),
             instances_count: 2,
             failed_instances_count: 0,
         }
@@ -1501,6 +2119,95 @@ fn test_peer_store() {
     assert_eq!(client_peer().studies().unwrap().len(), 1);
 }
 
+#[test]
+fn test_compute_ids() {
+    let instance = find_instance_by_sop_instance_uid(SOP_INSTANCE_UID).unwrap();
+    let series = find_series_by_series_instance_uid(SERIES_INSTANCE_UID).unwrap();
+    let study = find_study_by_study_instance_uid(STUDY_INSTANCE_UID).unwrap();
+    let patient = find_patient_by_patient_id(PATIENT_ID).unwrap();
+
+    assert_eq!(ids::patient_id(PATIENT_ID), patient.id);
+    assert_eq!(ids::study_id(PATIENT_ID, STUDY_INSTANCE_UID), study.id);
+    assert_eq!(
+        ids::series_id(PATIENT_ID, STUDY_INSTANCE_UID, SERIES_INSTANCE_UID),
+        series.id
+    );
+    assert_eq!(
+        ids::instance_id(
+            PATIENT_ID,
+            STUDY_INSTANCE_UID,
+            SERIES_INSTANCE_UID,
+            SOP_INSTANCE_UID
+        ),
+        instance.id
+    );
+}
+
+#[test]
+fn test_lookup() {
+    let study = find_study_by_study_instance_uid(STUDY_INSTANCE_UID).unwrap();
+    let resp = client_main().lookup(STUDY_INSTANCE_UID).unwrap();
+    assert_eq!(
+        resp,
+        vec![LookupResult {
+            id: study.id.to_string(),
+            path: format!("/studies/{}", study.id),
+            entity: EntityKind::Study,
+        }]
+    );
+    assert_eq!(client_main().lookup("1.2.3.4.5.6.7.8.9").unwrap(), vec![]);
+}
+
+#[test]
+fn test_find_by_uid() {
+    assert_eq!(
+        client_main()
+            .find_patient_by_patient_id(PATIENT_ID)
+            .unwrap(),
+        find_patient_by_patient_id(PATIENT_ID)
+    );
+    assert_eq!(
+        client_main().find_study_by_uid(STUDY_INSTANCE_UID).unwrap(),
+        find_study_by_study_instance_uid(STUDY_INSTANCE_UID)
+    );
+    assert_eq!(
+        client_main()
+            .find_series_by_uid(SERIES_INSTANCE_UID)
+            .unwrap(),
+        find_series_by_series_instance_uid(SERIES_INSTANCE_UID)
+    );
+    assert_eq!(
+        client_main()
+            .find_instance_by_uid(SOP_INSTANCE_UID)
+            .unwrap(),
+        find_instance_by_sop_instance_uid(SOP_INSTANCE_UID)
+    );
+    assert_eq!(client_main().find_study_by_uid("1.2.3.4.5").unwrap(), None);
+}
+
+#[test]
+fn test_reconstruct() {
+    let instance = find_instance_by_sop_instance_uid(SOP_INSTANCE_UID).unwrap();
+    client_main()
+        .reconstruct(EntityKind::Instance, &instance.id)
+        .unwrap();
+    assert_eq!(
+        client_main()
+            .instance(&instance.id)
+            .unwrap()
+            .main_dicom_tags["SOPInstanceUID"],
+        SOP_INSTANCE_UID
+    );
+}
+
+#[test]
+fn test_reconstruct_studies() {
+    let total = client_main().studies().unwrap().len();
+    let report = client_main().reconstruct_studies(2, |_, _| ()).unwrap();
+    assert_eq!(report.reconstructed.len(), total);
+    assert!(report.failures.is_empty());
+}
+
 #[test]
 fn test_search_patient_level() {
     let res: Vec<Patient> = client_main()